@@ -10,7 +10,7 @@ fn bench_node_operations(c: &mut Criterion) {
             for i in 0..1000 {
                 let data = serde_json::json!({"id": i, "value": i * 2});
                 let node_id = Uuid::new_v4();
-                black_box(graph.add_node(node_id, data).unwrap());
+                graph.add_node(black_box(node_id), data).unwrap();
             }
             
             assert_eq!(graph.node_count(), 1000);
@@ -60,12 +60,12 @@ fn bench_edge_operations(c: &mut Criterion) {
                     node_ids[i + 50],
                 ];
                 
-                black_box(graph.add_hyperedge(
-                    edge_id,
+                graph.add_hyperedge(
+                    black_box(edge_id),
                     connected_nodes,
                     format!("relationship_{}", i),
                     0.5 + (i as f64 / 100.0)
-                ).unwrap());
+                ).unwrap();
             }
             
             assert_eq!(graph.edge_count(), 50);
@@ -109,7 +109,7 @@ fn bench_graph_analysis(c: &mut Criterion) {
         let mut graph = HyperGraph::new();
         
         for i in 0..1000 {
-            let mut data = serde_json::json!({"id": i, "category": i % 10});
+            let data = serde_json::json!({"id": i, "category": i % 10});
             let node_id = Uuid::new_v4();
             graph.add_node(node_id, data).unwrap();
             
@@ -147,7 +147,7 @@ fn bench_graph_analysis(c: &mut Criterion) {
         }
         
         b.iter(|| {
-            black_box(graph.apply_decay(0.99));
+            graph.apply_decay(black_box(0.99));
         })
     });
 }
@@ -1,7 +1,6 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use neurographite::{Database, HyperGraph};
+use neurographite::Database;
 use tokio::runtime::Runtime;
-use uuid::Uuid;
 
 fn bench_spike_propagation(c: &mut Criterion) {
     let rt = Runtime::new().unwrap();
@@ -9,7 +8,8 @@ fn bench_spike_propagation(c: &mut Criterion) {
     c.bench_function("spike_propagation_small_network", |b| {
         b.iter(|| {
             rt.block_on(async {
-                let db = Database::new("./bench_data").await.unwrap();
+                let data_dir = tempfile::tempdir().unwrap();
+                let db = Database::new(data_dir.path()).await.unwrap();
                 
                 // Create a small network
                 let mut node_ids = Vec::new();
@@ -41,7 +41,8 @@ fn bench_spike_propagation(c: &mut Criterion) {
     c.bench_function("spike_propagation_large_network", |b| {
         b.iter(|| {
             rt.block_on(async {
-                let db = Database::new("./bench_data").await.unwrap();
+                let data_dir = tempfile::tempdir().unwrap();
+                let db = Database::new(data_dir.path()).await.unwrap();
                 
                 // Create a larger network
                 let mut node_ids = Vec::new();
@@ -89,7 +90,8 @@ fn bench_similarity_search(c: &mut Criterion) {
     c.bench_function("similarity_search", |b| {
         b.iter(|| {
             rt.block_on(async {
-                let db = Database::new("./bench_data").await.unwrap();
+                let data_dir = tempfile::tempdir().unwrap();
+                let db = Database::new(data_dir.path()).await.unwrap();
                 
                 // Create nodes with varying similarity
                 let mut node_ids = Vec::new();
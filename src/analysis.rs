@@ -0,0 +1,439 @@
+use uuid::Uuid;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+use crate::history::SpikeHistory;
+
+/// Poisson firing has an interspike-interval CV of 1; trains above it are irregular
+const IRREGULAR_CV: f64 = 1.0;
+
+/// Most histogram bins, correlogram bins or rate points in one analysis
+pub const MAX_BINS: usize = 1_000_000;
+
+/// Parameters of spike train analysis, all times in ms
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisConfig {
+    /// Bin width of ISI histograms and cross-correlograms
+    pub bin_width: f64,
+    /// Largest lag of cross-correlograms, either way
+    pub max_lag: f64,
+    /// Width of the sliding window for firing rates
+    pub rate_window: f64,
+    /// Step between sliding windows
+    pub rate_step: f64,
+    /// Victor–Purpura cost of shifting a spike by 1 ms
+    pub shift_cost: f64,
+    /// Time constant of the van Rossum kernel
+    pub tau: f64,
+    pub bursts: BurstConfig,
+}
+
+impl Default for AnalysisConfig {
+    fn default() -> Self {
+        Self {
+            bin_width: 5.0,
+            max_lag: 100.0,
+            rate_window: 1000.0,
+            rate_step: 500.0,
+            shift_cost: 0.1,
+            tau: 20.0,
+            bursts: BurstConfig::default(),
+        }
+    }
+}
+
+/// When spikes count as a burst
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BurstConfig {
+    /// Longest interval between consecutive spikes of a burst (ms)
+    pub max_isi: f64,
+    /// Fewest spikes in a burst
+    pub min_spikes: usize,
+    /// Share of spikes in bursts above which a train is classed as bursting
+    pub min_fraction: f64,
+}
+
+impl Default for BurstConfig {
+    fn default() -> Self {
+        Self {
+            max_isi: 10.0,
+            min_spikes: 3,
+            min_fraction: 0.5,
+        }
+    }
+}
+
+/// Spike times of one node, in ms since `start`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpikeTrain {
+    pub node_id: Uuid,
+    pub start: DateTime<Utc>,
+    /// Length of the observed window (ms)
+    pub duration: f64,
+    /// Spike times in ascending order
+    pub times: Vec<f64>,
+}
+
+/// Distribution of interspike intervals (ms)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IsiDistribution {
+    pub count: usize,
+    pub mean: f64,
+    pub std_dev: f64,
+    /// Coefficient of variation, `std_dev / mean`
+    pub cv: f64,
+    pub min: f64,
+    pub max: f64,
+    pub bin_width: f64,
+    /// Interval counts per bin, starting at 0
+    pub histogram: Vec<usize>,
+}
+
+/// Firing rate of a window starting at `time` (ms)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RatePoint {
+    pub time: f64,
+    /// Spikes per second
+    pub rate: f64,
+}
+
+/// Counts of spike pairs by lag from one train to another
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Correlogram {
+    pub bin_width: f64,
+    /// Centre of each bin (ms); positive lags mean the second train fires later
+    pub lags: Vec<f64>,
+    pub counts: Vec<usize>,
+}
+
+impl Correlogram {
+    /// Lag of the fullest bin, if any pair fell within range
+    pub fn peak_lag(&self) -> Option<f64> {
+        let (index, count) = self
+            .counts
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(&a.0)))?;
+        (*count > 0).then(|| self.lags[index])
+    }
+}
+
+/// A run of closely spaced spikes
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Burst {
+    /// Time of the first spike (ms)
+    pub start: f64,
+    /// Time of the last spike (ms)
+    pub end: f64,
+    pub spikes: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FiringPattern {
+    /// Too few spikes to tell
+    Silent,
+    /// Regularly spaced spikes
+    Tonic,
+    /// Most spikes fall in bursts
+    Bursting,
+    /// Spikes spaced at least as irregularly as a Poisson process
+    Irregular,
+}
+
+/// Analysis of a single spike train
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpikeTrainSummary {
+    pub node_id: Uuid,
+    pub spike_count: usize,
+    /// Spikes per second over the whole window
+    pub mean_rate: f64,
+    pub isi: IsiDistribution,
+    pub rates: Vec<RatePoint>,
+    pub bursts: Vec<Burst>,
+    pub pattern: FiringPattern,
+}
+
+/// Comparison of two spike trains over the same window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpikeTrainComparison {
+    pub node_a: Uuid,
+    pub node_b: Uuid,
+    pub victor_purpura: f64,
+    pub van_rossum: f64,
+    /// Correlation of the filtered trains, from 0 (unrelated) to 1 (identical)
+    pub synchrony: f64,
+    pub correlogram: Correlogram,
+    pub peak_lag: Option<f64>,
+}
+
+impl SpikeTrain {
+    pub fn new(node_id: Uuid, start: DateTime<Utc>, duration: f64, mut times: Vec<f64>) -> Self {
+        times.sort_by(f64::total_cmp);
+        Self { node_id, start, duration, times }
+    }
+    
+    /// Spikes a node fired between `from` and `to` (inclusive)
+    pub fn from_history(history: &SpikeHistory, node_id: Uuid, from: DateTime<Utc>, to: DateTime<Utc>) -> Self {
+        let times = history
+            .node_window(&node_id, from, to)
+            .map(|spike| millis(spike.timestamp - from))
+            .collect();
+        Self {
+            node_id,
+            start: from,
+            duration: millis(to - from),
+            times,
+        }
+    }
+    
+    pub fn len(&self) -> usize {
+        self.times.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.times.is_empty()
+    }
+    
+    /// Intervals between consecutive spikes
+    pub fn intervals(&self) -> Vec<f64> {
+        self.times.windows(2).map(|pair| pair[1] - pair[0]).collect()
+    }
+    
+    /// Spikes per second over the whole window
+    pub fn mean_rate(&self) -> f64 {
+        if self.duration <= 0.0 {
+            return 0.0;
+        }
+        self.len() as f64 * 1000.0 / self.duration
+    }
+    
+    pub fn isi_distribution(&self, bin_width: f64) -> Result<IsiDistribution> {
+        positive("bin_width", bin_width)?;
+        let intervals = self.intervals();
+        if intervals.is_empty() {
+            return Ok(IsiDistribution { bin_width, ..Default::default() });
+        }
+        
+        let count = intervals.len() as f64;
+        let mean = intervals.iter().sum::<f64>() / count;
+        let std_dev = (intervals.iter().map(|isi| (isi - mean).powi(2)).sum::<f64>() / count).sqrt();
+        let min = intervals.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = intervals.iter().copied().fold(0.0, f64::max);
+        
+        let mut histogram = vec![0; bin_count((max / bin_width).floor() + 1.0)?];
+        for isi in &intervals {
+            histogram[(isi / bin_width) as usize] += 1;
+        }
+        
+        Ok(IsiDistribution {
+            count: intervals.len(),
+            mean,
+            std_dev,
+            cv: if mean > 0.0 { std_dev / mean } else { 0.0 },
+            min,
+            max,
+            bin_width,
+            histogram,
+        })
+    }
+    
+    /// Firing rates in windows of `window` ms, `step` ms apart
+    ///
+    /// The last window is cut short at the end of the train.
+    pub fn firing_rates(&self, window: f64, step: f64) -> Result<Vec<RatePoint>> {
+        positive("rate_window", window)?;
+        positive("rate_step", step)?;
+        let points = bin_count((self.duration / step).ceil())?;
+        
+        let rates = (0..points)
+            .map(|point| point as f64 * step)
+            .take_while(|time| *time < self.duration)
+            .map(|time| {
+                let end = (time + window).min(self.duration);
+                let first = self.times.partition_point(|t| *t < time);
+                let last = if end >= self.duration {
+                    self.times.partition_point(|t| *t <= end)
+                } else {
+                    self.times.partition_point(|t| *t < end)
+                };
+                RatePoint {
+                    time,
+                    rate: (last - first) as f64 * 1000.0 / (end - time),
+                }
+            })
+            .collect();
+        Ok(rates)
+    }
+    
+    /// Spike pairs by lag from this train to `other`, within `max_lag` either way
+    pub fn cross_correlogram(&self, other: &SpikeTrain, max_lag: f64, bin_width: f64) -> Result<Correlogram> {
+        positive("max_lag", max_lag)?;
+        positive("bin_width", bin_width)?;
+        let bins = bin_count((2.0 * max_lag / bin_width).ceil())?.max(1);
+        let lags = (0..bins).map(|bin| -max_lag + (bin as f64 + 0.5) * bin_width).collect();
+        let mut counts = vec![0; bins];
+        
+        let offset = millis(other.start - self.start);
+        for t in &self.times {
+            let from = other.times.partition_point(|u| u + offset < t - max_lag);
+            for u in &other.times[from..] {
+                let lag = u + offset - t;
+                if lag > max_lag {
+                    break;
+                }
+                let bin = ((lag + max_lag) / bin_width) as usize;
+                counts[bin.min(bins - 1)] += 1;
+            }
+        }
+        
+        Ok(Correlogram { bin_width, lags, counts })
+    }
+    
+    /// Victor–Purpura distance: the cheapest way to turn one train into the
+    /// other, where inserting or deleting a spike costs 1 and moving one costs
+    /// `cost` per ms
+    pub fn victor_purpura(&self, other: &SpikeTrain, cost: f64) -> f64 {
+        let offset = millis(other.start - self.start);
+        let mut previous: Vec<f64> = (0..=other.len()).map(|j| j as f64).collect();
+        
+        for (i, t) in self.times.iter().enumerate() {
+            let mut row = vec![(i + 1) as f64; other.len() + 1];
+            for (j, u) in other.times.iter().enumerate() {
+                let shift = previous[j] + cost * (u + offset - t).abs();
+                row[j + 1] = (previous[j + 1] + 1.0).min(row[j] + 1.0).min(shift);
+            }
+            previous = row;
+        }
+        previous[other.len()]
+    }
+    
+    /// van Rossum distance between the trains filtered by an exponential
+    /// kernel with time constant `tau`
+    pub fn van_rossum(&self, other: &SpikeTrain, tau: f64) -> f64 {
+        let own = self.kernel_sum(self, tau);
+        let theirs = other.kernel_sum(other, tau);
+        let shared = self.kernel_sum(other, tau);
+        ((own + theirs - 2.0 * shared) / 2.0).max(0.0).sqrt()
+    }
+    
+    /// Correlation of the trains filtered by an exponential kernel with time
+    /// constant `tau`, from 0 (no overlap) to 1 (identical)
+    pub fn synchrony(&self, other: &SpikeTrain, tau: f64) -> f64 {
+        if self.is_empty() || other.is_empty() {
+            return 0.0;
+        }
+        let norm = (self.kernel_sum(self, tau) * other.kernel_sum(other, tau)).sqrt();
+        (self.kernel_sum(other, tau) / norm).clamp(0.0, 1.0)
+    }
+    
+    /// Runs of spikes no more than `max_isi` apart, with at least `min_spikes`
+    pub fn bursts(&self, config: &BurstConfig) -> Vec<Burst> {
+        let mut bursts = Vec::new();
+        let mut first = 0;
+        
+        for i in 1..=self.len() {
+            let continues = i < self.len() && self.times[i] - self.times[i - 1] <= config.max_isi;
+            if continues {
+                continue;
+            }
+            if i - first >= config.min_spikes.max(2) {
+                bursts.push(Burst {
+                    start: self.times[first],
+                    end: self.times[i - 1],
+                    spikes: i - first,
+                });
+            }
+            first = i;
+        }
+        bursts
+    }
+    
+    pub fn pattern(&self, config: &BurstConfig) -> FiringPattern {
+        if self.len() < 3 {
+            return FiringPattern::Silent;
+        }
+        
+        let in_bursts: usize = self.bursts(config).iter().map(|burst| burst.spikes).sum();
+        if in_bursts as f64 >= config.min_fraction * self.len() as f64 {
+            FiringPattern::Bursting
+        } else if self.isi_distribution(f64::MAX).is_ok_and(|isi| isi.cv >= IRREGULAR_CV) {
+            FiringPattern::Irregular
+        } else {
+            FiringPattern::Tonic
+        }
+    }
+    
+    /// Sum of the kernel over all spike pairs of the two trains
+    fn kernel_sum(&self, other: &SpikeTrain, tau: f64) -> f64 {
+        let offset = millis(other.start - self.start);
+        self.times
+            .iter()
+            .flat_map(|t| other.times.iter().map(move |u| (-(u + offset - t).abs() / tau).exp()))
+            .sum()
+    }
+}
+
+impl AnalysisConfig {
+    /// Check that bin widths, lags, windows, costs and time constants are usable
+    pub fn validate(&self) -> Result<()> {
+        positive("bin_width", self.bin_width)?;
+        positive("max_lag", self.max_lag)?;
+        positive("rate_window", self.rate_window)?;
+        positive("rate_step", self.rate_step)?;
+        positive("tau", self.tau)?;
+        if !(self.shift_cost >= 0.0 && self.shift_cost.is_finite()) {
+            return Err(anyhow::anyhow!("Analysis shift_cost must be non-negative and finite, got {}", self.shift_cost));
+        }
+        Ok(())
+    }
+    
+    pub fn summarize(&self, train: &SpikeTrain) -> Result<SpikeTrainSummary> {
+        self.validate()?;
+        Ok(SpikeTrainSummary {
+            node_id: train.node_id,
+            spike_count: train.len(),
+            mean_rate: train.mean_rate(),
+            isi: train.isi_distribution(self.bin_width)?,
+            rates: train.firing_rates(self.rate_window, self.rate_step)?,
+            bursts: train.bursts(&self.bursts),
+            pattern: train.pattern(&self.bursts),
+        })
+    }
+    
+    pub fn compare(&self, a: &SpikeTrain, b: &SpikeTrain) -> Result<SpikeTrainComparison> {
+        self.validate()?;
+        let correlogram = a.cross_correlogram(b, self.max_lag, self.bin_width)?;
+        Ok(SpikeTrainComparison {
+            node_a: a.node_id,
+            node_b: b.node_id,
+            victor_purpura: a.victor_purpura(b, self.shift_cost),
+            van_rossum: a.van_rossum(b, self.tau),
+            synchrony: a.synchrony(b, self.tau),
+            peak_lag: correlogram.peak_lag(),
+            correlogram,
+        })
+    }
+}
+
+/// Reject analysis parameters that aren't positive and finite
+fn positive(name: &str, value: f64) -> Result<()> {
+    if value > 0.0 && value.is_finite() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("Analysis {} must be positive and finite, got {}", name, value))
+    }
+}
+
+/// Number of bins for a computed count, refusing more than `MAX_BINS`
+fn bin_count(count: f64) -> Result<usize> {
+    if count > MAX_BINS as f64 {
+        return Err(anyhow::anyhow!("More than {} bins requested, use a wider bin or step", MAX_BINS));
+    }
+    Ok(count.max(0.0) as usize)
+}
+
+fn millis(duration: chrono::Duration) -> f64 {
+    duration.num_microseconds().unwrap_or(i64::MAX) as f64 / 1000.0
+}
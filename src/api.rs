@@ -1,13 +1,22 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::net::TcpListener;
-use tokio::io::{AsyncBufReadExt, BufReader};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use chrono::{DateTime, Utc};
 use anyhow::Result;
 
 use crate::core::Database;
+use crate::hypergraph::{CascadePolicy, EdgeDelay, EdgeType, EdgeUpdate, NodeMetadata, NodeUpdate, DEFAULT_NODE_TYPE};
+use crate::index::NodeFilter;
+use crate::network::{CapacitatedMatchingConfig, CentralityConfig, MatchingConfig, PreferenceSource, ProposedEdge, TeamConfig, TeamRole};
+use crate::plasticity::{OutcomeTarget, RewardUpdate};
+use crate::export::{ExportFormat, RasterQuery};
 use crate::frontend::StaticFileServer;
 
+/// Size at which streamed downloads send a chunk
+const EXPORT_CHUNK_SIZE: usize = 64 * 1024;
+
 /// HTTP API server for Neurographite
 pub struct Server {
     db: Arc<Database>,
@@ -19,6 +28,19 @@ pub struct AddNodeRequest {
     pub data: serde_json::Value,
     pub node_type: Option<String>,
     pub tags: Option<Vec<String>>,
+    pub properties: Option<HashMap<String, serde_json::Value>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateNodeRequest {
+    pub data: Option<serde_json::Value>,
+    pub node_type: Option<String>,
+    /// Replaces the whole tag list
+    pub tags: Option<Vec<String>>,
+    pub add_tags: Option<Vec<String>>,
+    pub remove_tags: Option<Vec<String>>,
+    /// Merged into the existing properties; a `null` value removes the key
+    pub properties: Option<HashMap<String, serde_json::Value>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -32,6 +54,19 @@ pub struct ConnectNodesRequest {
     pub node_ids: Vec<Uuid>,
     pub relationship: String,
     pub strength: f64,
+    #[serde(default)]
+    pub edge_type: EdgeType,
+    #[serde(default)]
+    pub delay: EdgeDelay,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateEdgeRequest {
+    pub relationship: Option<String>,
+    pub strength: Option<f64>,
+    /// Merged into the existing properties; a `null` value removes the key
+    pub properties: Option<HashMap<String, serde_json::Value>>,
+    pub delay: Option<EdgeDelay>,
 }
 
 #[derive(Debug, Serialize)]
@@ -77,6 +112,147 @@ pub struct NetworkEffectResponse {
 pub struct AffectedNode {
     pub node_id: Uuid,
     pub effect_strength: f64,
+    /// When the first spike arrived (ms after the source fired)
+    pub arrival_time: f64,
+    /// Nodes the first spike passed through, from the source
+    pub path: Vec<Uuid>,
+    /// Hyperedges it crossed
+    pub edges: Vec<Uuid>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MatchingSide {
+    pub node_types: Vec<String>,
+    pub tags: Vec<String>,
+}
+
+impl From<MatchingSide> for NodeFilter {
+    fn from(side: MatchingSide) -> Self {
+        NodeFilter {
+            node_types: side.node_types,
+            tags: side.tags,
+            properties: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StableMatchingRequest {
+    pub proposers: MatchingSide,
+    pub acceptors: MatchingSide,
+    #[serde(default)]
+    pub source: PreferenceSource,
+    /// Pairs scoring below this are unacceptable to both sides
+    pub min_score: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CapacitatedMatchingRequest {
+    pub proposers: MatchingSide,
+    pub acceptors: MatchingSide,
+    #[serde(default)]
+    pub source: PreferenceSource,
+    pub min_score: Option<f64>,
+    /// Node property with each acceptor's capacity, `capacity` by default
+    pub capacity_property: Option<String>,
+    pub lower_quota_property: Option<String>,
+    /// Relationship of the proposed hyperedges, `matched` by default
+    pub relationship: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FormTeamsRequest {
+    #[serde(default)]
+    pub candidates: MatchingSide,
+    pub size: Option<usize>,
+    #[serde(default)]
+    pub roles: Vec<TeamRole>,
+    pub alignment_weight: Option<f64>,
+    pub complementarity_weight: Option<f64>,
+    pub beam_width: Option<usize>,
+    pub max_teams: Option<usize>,
+    /// Relationship of the proposed hyperedges, `team` by default
+    pub relationship: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommitProposalsRequest {
+    pub proposals: Vec<ProposedEdge>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommitProposalsResponse {
+    pub edge_ids: Vec<Uuid>,
+    pub success: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StableRoommatesRequest {
+    #[serde(default)]
+    pub participants: MatchingSide,
+    /// Pairs aligning below this are never matched
+    pub min_score: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReportOutcomeRequest {
+    /// Hyperedge the outcome is about
+    pub edge_id: Option<Uuid>,
+    /// Or the two nodes it is about
+    pub node_ids: Option<Vec<Uuid>>,
+    /// Positive for success, negative for failure
+    pub reward: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReportOutcomeResponse {
+    pub updates: Vec<RewardUpdate>,
+    pub success: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeleteResponse {
+    pub id: Uuid,
+    pub success: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BackupResponse {
+    pub name: String,
+    pub size_bytes: u64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListBackupsResponse {
+    pub backups: Vec<BackupResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RestoreBackupResponse {
+    pub restored: String,
+    pub success: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BackupRetentionRequest {
+    pub keep: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BackupRetentionResponse {
+    pub removed: usize,
+    pub kept: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StorageStatsResponse {
+    pub graph_file_size: u64,
+    pub neural_file_size: u64,
+    pub wal_file_size: u64,
+    pub backup_count: usize,
+    pub total_backup_size: u64,
 }
 
 impl Server {
@@ -94,7 +270,7 @@ impl Server {
         loop {
             let (stream, addr) = listener.accept().await?;
             let db = Arc::clone(&self.db);
-            let static_server = StaticFileServer::new("./frontend");
+            let static_server = self.static_server.clone();
             
             tokio::spawn(async move {
                 if let Err(e) = Self::handle_connection(db, static_server, stream).await {
@@ -141,7 +317,54 @@ impl Server {
             ("GET", path) if path.starts_with("/nodes/") && path.ends_with("/network-effect") => {
                 Self::handle_network_effect(db, stream, path).await
             }
+            ("GET", path) if Self::spike_train_path(path).is_some() => {
+                Self::handle_spike_train(db, stream, path).await
+            }
+            ("GET", path) if path.split('?').next() == Some("/centrality") => {
+                Self::handle_centrality(db, stream, path).await
+            }
             ("GET", "/relationships") => Self::handle_discover_relationships(db, stream).await,
+            ("GET", path) if Self::resource_id(path, "export") == Some("spikes") => {
+                Self::handle_export(db, stream, path, false).await
+            }
+            ("GET", path) if Self::resource_id(path, "export") == Some("membranes") => {
+                Self::handle_export(db, stream, path, true).await
+            }
+            ("POST", "/matching") => Self::handle_stable_matching(db, stream, &request).await,
+            ("POST", "/matching/capacitated") => Self::handle_capacitated_matching(db, stream, &request).await,
+            ("POST", "/teams") => Self::handle_form_teams(db, stream, &request).await,
+            ("POST", "/proposals") => Self::handle_commit_proposals(db, stream, &request).await,
+            ("POST", "/roommates") => Self::handle_stable_roommates(db, stream, &request).await,
+            ("POST", "/outcomes") => Self::handle_report_outcome(db, stream, &request).await,
+            ("GET", path) if Self::resource_id(path, "nodes").is_some() => {
+                Self::handle_get_node(db, stream, path).await
+            }
+            ("PATCH", path) if Self::resource_id(path, "nodes").is_some() => {
+                Self::handle_update_node(db, stream, path, &request).await
+            }
+            ("DELETE", path) if Self::resource_id(path, "nodes").is_some() => {
+                Self::handle_delete_node(db, stream, path).await
+            }
+            ("GET", path) if Self::resource_id(path, "edges").is_some() => {
+                Self::handle_get_edge(db, stream, path).await
+            }
+            ("PATCH", path) if Self::resource_id(path, "edges").is_some() => {
+                Self::handle_update_edge(db, stream, path, &request).await
+            }
+            ("DELETE", path) if Self::resource_id(path, "edges").is_some() => {
+                Self::handle_delete_edge(db, stream, path).await
+            }
+            
+            // Admin routes
+            ("GET", "/admin/storage") => Self::handle_storage_stats(db, stream).await,
+            ("GET", "/admin/backups") => Self::handle_list_backups(db, stream).await,
+            ("POST", "/admin/backups") => Self::handle_create_backup(db, stream).await,
+            ("POST", "/admin/backups/retention") => {
+                Self::handle_backup_retention(db, stream, &request).await
+            }
+            ("POST", path) if path.starts_with("/admin/backups/") && path.ends_with("/restore") => {
+                Self::handle_restore_backup(db, stream, path).await
+            }
             
             // Handle CORS preflight
             ("OPTIONS", _) => Self::handle_cors_preflight(stream).await,
@@ -189,7 +412,13 @@ impl Server {
         
         let request: AddNodeRequest = serde_json::from_str(body)?;
         
-        match db.add_node(request.data).await {
+        let metadata = NodeMetadata {
+            node_type: request.node_type.unwrap_or_else(|| DEFAULT_NODE_TYPE.to_string()),
+            tags: request.tags.unwrap_or_default(),
+            properties: request.properties.unwrap_or_default(),
+        };
+        
+        match db.add_node_with_metadata(request.data, metadata).await {
             Ok(node_id) => {
                 let response = AddNodeResponse {
                     node_id,
@@ -220,7 +449,17 @@ impl Server {
         
         let request: ConnectNodesRequest = serde_json::from_str(body)?;
         
-        match db.connect_nodes(request.node_ids, request.relationship, request.strength).await {
+        let connected = match db
+            .connect_nodes_with_type(request.node_ids, request.relationship, request.strength, request.edge_type)
+            .await
+        {
+            Ok(edge_id) if request.delay != EdgeDelay::Default => {
+                db.set_edge_delay(edge_id, request.delay).await.map(|()| edge_id)
+            }
+            connected => connected,
+        };
+        
+        match connected {
             Ok(edge_id) => {
                 let response = ConnectNodesResponse {
                     edge_id,
@@ -289,15 +528,18 @@ impl Server {
         match db.simulate_network_effect(node_id, 1.0).await {
             Ok(effects) => {
                 let response = NetworkEffectResponse {
+                    total_effect: effects.iter().map(|effect| effect.effect).sum(),
+                    cascade_depth: effects.iter().map(|effect| effect.edges.len()).max().unwrap_or(0),
                     affected_nodes: effects
                         .into_iter()
-                        .map(|(id, strength)| AffectedNode {
-                            node_id: id,
-                            effect_strength: strength,
+                        .map(|effect| AffectedNode {
+                            node_id: effect.node_id,
+                            effect_strength: effect.effect,
+                            arrival_time: effect.arrival_time,
+                            path: effect.path,
+                            edges: effect.edges,
                         })
                         .collect(),
-                    total_effect: 0.0, // TODO: Calculate total
-                    cascade_depth: 0,  // TODO: Calculate depth
                 };
                 let json = serde_json::to_string(&response)?;
                 Self::send_json_response(stream, 200, &json).await
@@ -309,6 +551,139 @@ impl Server {
         }
     }
     
+    /// `/nodes/{id}/spike-train` analyzes a node's spike train and
+    /// `/nodes/{id}/spike-train/{other}` compares it with another node's, over
+    /// the last `?window=` seconds (10 minutes by default)
+    async fn handle_spike_train(
+        db: Arc<Database>,
+        stream: tokio::net::TcpStream,
+        path: &str,
+    ) -> Result<()> {
+        let Some((node_id, other)) = Self::spike_train_path(path) else {
+            return Self::send_error_response(stream, 400, "Invalid path").await;
+        };
+        let (Ok(node_id), Ok(other)) = (Uuid::parse_str(node_id), other.map(Uuid::parse_str).transpose()) else {
+            return Self::send_error_response(stream, 400, "Invalid UUID").await;
+        };
+        let window = match Self::query_param(path, "window").map(str::parse::<u32>) {
+            None => chrono::Duration::minutes(10),
+            Some(Ok(seconds)) if seconds > 0 => chrono::Duration::seconds(seconds.into()),
+            Some(_) => return Self::send_error_response(stream, 400, "Invalid window").await,
+        };
+        
+        for node_id in std::iter::once(node_id).chain(other) {
+            if db.get_node(node_id).await.is_none() {
+                return Self::send_error_response(stream, 404, "Node not found").await;
+            }
+        }
+        
+        let json = match other {
+            None => db.analyze_spike_train(node_id, window).await.map(|summary| serde_json::to_string(&summary)),
+            Some(other) => db.compare_spike_trains(node_id, other, window).await.map(|comparison| serde_json::to_string(&comparison)),
+        };
+        match json {
+            Ok(json) => Self::send_json_response(stream, 200, &json?).await,
+            Err(e) => {
+                tracing::error!("Failed to analyze spike train of {}: {}", node_id, e);
+                Self::send_error_response(stream, 500, &e.to_string()).await
+            }
+        }
+    }
+    
+    /// Stream spikes, or membrane traces, of `?nodes=` (comma-separated, all
+    /// by default) over the last `?window=` seconds as `?format=` csv, ndjson
+    /// or npy; membrane traces are sampled every `?interval=` ms
+    async fn handle_export(
+        db: Arc<Database>,
+        stream: tokio::net::TcpStream,
+        path: &str,
+        membranes: bool,
+    ) -> Result<()> {
+        use tokio::io::AsyncWriteExt;
+        
+        let Ok(format) = Self::query_param(path, "format").unwrap_or("csv").parse::<ExportFormat>() else {
+            return Self::send_error_response(stream, 400, "Invalid format").await;
+        };
+        let node_ids: Result<Vec<Uuid>, _> = Self::query_param(path, "nodes")
+            .map(|nodes| nodes.split(',').map(Uuid::parse_str).collect())
+            .unwrap_or(Ok(Vec::new()));
+        let Ok(node_ids) = node_ids else {
+            return Self::send_error_response(stream, 400, "Invalid UUID").await;
+        };
+        let window = match Self::query_param(path, "window").map(str::parse::<u32>) {
+            None => chrono::Duration::minutes(10),
+            Some(Ok(seconds)) if seconds > 0 => chrono::Duration::seconds(seconds.into()),
+            Some(_) => return Self::send_error_response(stream, 400, "Invalid window").await,
+        };
+        let membrane_interval = match (membranes, Self::query_param(path, "interval").map(str::parse::<f64>)) {
+            (false, _) => None,
+            (true, None) => Some(10.0),
+            (true, Some(Ok(interval))) => Some(interval),
+            (true, Some(Err(_))) => return Self::send_error_response(stream, 400, "Invalid interval").await,
+        };
+        
+        let to = db.now();
+        let query = RasterQuery { node_ids, from: to - window, to, membrane_interval };
+        let raster = match db.raster(&query).await {
+            Ok(raster) => raster,
+            Err(e) => return Self::send_error_response(stream, 400, &e.to_string()).await,
+        };
+        
+        // Encode on a blocking thread and forward each chunk as it fills up
+        let (sender, mut chunks) = tokio::sync::mpsc::channel(4);
+        let writer = tokio::task::spawn_blocking(move || {
+            let out = ChunkWriter { buffer: Vec::new(), chunks: sender };
+            if membranes {
+                raster.write_membranes(format, out)
+            } else {
+                raster.write_spikes(format, out)
+            }
+        });
+        
+        let mut stream = stream;
+        let name = if membranes { "membranes" } else { "spikes" };
+        let header = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Disposition: attachment; filename=\"{}.{}\"\r\nTransfer-Encoding: chunked\r\nAccess-Control-Allow-Origin: *\r\n\r\n",
+            format.content_type(), name, format.extension()
+        );
+        stream.write_all(header.as_bytes()).await?;
+        while let Some(chunk) = chunks.recv().await {
+            stream.write_all(format!("{:x}\r\n", chunk.len()).as_bytes()).await?;
+            stream.write_all(&chunk).await?;
+            stream.write_all(b"\r\n").await?;
+        }
+        
+        // Without the final chunk the client sees the download as cut short
+        writer.await??;
+        stream.write_all(b"0\r\n\r\n").await?;
+        stream.flush().await?;
+        Ok(())
+    }
+    
+    async fn handle_centrality(
+        db: Arc<Database>,
+        stream: tokio::net::TcpStream,
+        path: &str,
+    ) -> Result<()> {
+        let weighted = match Self::query_param(path, "weighted").map(str::parse::<bool>) {
+            None => false,
+            Some(Ok(weighted)) => weighted,
+            Some(Err(_)) => return Self::send_error_response(stream, 400, "Invalid weighted flag").await,
+        };
+        let samples = match Self::query_param(path, "samples").map(str::parse::<usize>) {
+            None => None,
+            Some(Ok(samples)) if samples > 0 => Some(samples),
+            Some(_) => return Self::send_error_response(stream, 400, "Invalid sample count").await,
+        };
+        let Ok(seed) = Self::query_param(path, "seed").unwrap_or("0").parse::<u64>() else {
+            return Self::send_error_response(stream, 400, "Invalid seed").await;
+        };
+        
+        let measures = db.centrality(&CentralityConfig { weighted, samples, seed }).await;
+        let json = serde_json::to_string(&measures)?;
+        Self::send_json_response(stream, 200, &json).await
+    }
+    
     async fn handle_discover_relationships(
         db: Arc<Database>,
         stream: tokio::net::TcpStream,
@@ -325,6 +700,575 @@ impl Server {
         }
     }
     
+    async fn handle_stable_matching(
+        db: Arc<Database>,
+        stream: tokio::net::TcpStream,
+        request: &str,
+    ) -> Result<()> {
+        let request: StableMatchingRequest = match Self::request_body(request).map(serde_json::from_str) {
+            Some(Ok(request)) => request,
+            _ => return Self::send_error_response(stream, 400, "Invalid matching request").await,
+        };
+        
+        let config = MatchingConfig {
+            proposers: request.proposers.into(),
+            acceptors: request.acceptors.into(),
+            source: request.source,
+            min_score: request.min_score.unwrap_or_default(),
+        };
+        
+        match db.stable_matching(&config).await {
+            Ok(matching) => {
+                let json = serde_json::to_string(&matching)?;
+                Self::send_json_response(stream, 200, &json).await
+            }
+            Err(e) => {
+                tracing::error!("Failed to compute stable matching: {}", e);
+                Self::send_error_response(stream, 500, "Internal Server Error").await
+            }
+        }
+    }
+    
+    async fn handle_capacitated_matching(
+        db: Arc<Database>,
+        stream: tokio::net::TcpStream,
+        request: &str,
+    ) -> Result<()> {
+        let request: CapacitatedMatchingRequest = match Self::request_body(request).map(serde_json::from_str) {
+            Some(Ok(request)) => request,
+            _ => return Self::send_error_response(stream, 400, "Invalid matching request").await,
+        };
+        
+        let defaults = CapacitatedMatchingConfig::default();
+        let config = CapacitatedMatchingConfig {
+            matching: MatchingConfig {
+                proposers: request.proposers.into(),
+                acceptors: request.acceptors.into(),
+                source: request.source,
+                min_score: request.min_score.unwrap_or_default(),
+            },
+            capacity_property: request.capacity_property.unwrap_or(defaults.capacity_property),
+            lower_quota_property: request.lower_quota_property,
+            relationship: request.relationship.unwrap_or(defaults.relationship),
+        };
+        
+        match db.capacitated_matching(&config).await {
+            Ok(matching) => {
+                let json = serde_json::to_string(&matching)?;
+                Self::send_json_response(stream, 200, &json).await
+            }
+            Err(e) => {
+                tracing::error!("Failed to compute capacitated matching: {}", e);
+                Self::send_error_response(stream, 500, "Internal Server Error").await
+            }
+        }
+    }
+    
+    async fn handle_form_teams(
+        db: Arc<Database>,
+        stream: tokio::net::TcpStream,
+        request: &str,
+    ) -> Result<()> {
+        let request: FormTeamsRequest = match Self::request_body(request).map(serde_json::from_str) {
+            Some(Ok(request)) => request,
+            _ => return Self::send_error_response(stream, 400, "Invalid team request").await,
+        };
+        
+        let defaults = TeamConfig::default();
+        let config = TeamConfig {
+            candidates: request.candidates.into(),
+            size: request.size.unwrap_or(defaults.size),
+            roles: request.roles,
+            alignment_weight: request.alignment_weight.unwrap_or(defaults.alignment_weight),
+            complementarity_weight: request.complementarity_weight.unwrap_or(defaults.complementarity_weight),
+            beam_width: request.beam_width.unwrap_or(defaults.beam_width),
+            max_teams: request.max_teams.unwrap_or(defaults.max_teams),
+            relationship: request.relationship.unwrap_or(defaults.relationship),
+        };
+        
+        match db.form_teams(&config).await {
+            Ok(teams) => {
+                let json = serde_json::to_string(&teams)?;
+                Self::send_json_response(stream, 200, &json).await
+            }
+            Err(e) => Self::send_error_response(stream, 400, &e.to_string()).await,
+        }
+    }
+    
+    async fn handle_commit_proposals(
+        db: Arc<Database>,
+        stream: tokio::net::TcpStream,
+        request: &str,
+    ) -> Result<()> {
+        let request: CommitProposalsRequest = match Self::request_body(request).map(serde_json::from_str) {
+            Some(Ok(request)) => request,
+            _ => return Self::send_error_response(stream, 400, "Invalid proposals").await,
+        };
+        
+        for node_id in request.proposals.iter().flat_map(|proposal| &proposal.node_ids) {
+            if db.get_node(*node_id).await.is_none() {
+                return Self::send_error_response(stream, 404, "Node not found").await;
+            }
+        }
+        
+        match db.commit_proposals(&request.proposals).await {
+            Ok(edge_ids) => {
+                let response = CommitProposalsResponse {
+                    edge_ids,
+                    success: true,
+                };
+                let json = serde_json::to_string(&response)?;
+                Self::send_json_response(stream, 200, &json).await
+            }
+            Err(e) => {
+                tracing::error!("Failed to commit proposals: {}", e);
+                Self::send_error_response(stream, 500, "Internal Server Error").await
+            }
+        }
+    }
+    
+    async fn handle_stable_roommates(
+        db: Arc<Database>,
+        stream: tokio::net::TcpStream,
+        request: &str,
+    ) -> Result<()> {
+        let request: StableRoommatesRequest = match Self::request_body(request).map(serde_json::from_str) {
+            Some(Ok(request)) => request,
+            _ => return Self::send_error_response(stream, 400, "Invalid matching request").await,
+        };
+        
+        let filter = request.participants.into();
+        match db.stable_roommates(&filter, request.min_score.unwrap_or_default()).await {
+            Ok(matching) => {
+                let json = serde_json::to_string(&matching)?;
+                Self::send_json_response(stream, 200, &json).await
+            }
+            Err(e) => {
+                tracing::error!("Failed to compute stable roommates matching: {}", e);
+                Self::send_error_response(stream, 500, "Internal Server Error").await
+            }
+        }
+    }
+    
+    async fn handle_report_outcome(
+        db: Arc<Database>,
+        stream: tokio::net::TcpStream,
+        request: &str,
+    ) -> Result<()> {
+        let request: ReportOutcomeRequest = match Self::request_body(request).map(serde_json::from_str) {
+            Some(Ok(request)) => request,
+            _ => return Self::send_error_response(stream, 400, "Invalid outcome").await,
+        };
+        
+        let target = match (request.edge_id, request.node_ids.as_deref()) {
+            (Some(edge_id), None) => OutcomeTarget::Edge(edge_id),
+            (None, Some(&[a, b])) => OutcomeTarget::Pair(a, b),
+            _ => {
+                return Self::send_error_response(stream, 400, "Expected edge_id or two node_ids").await;
+            }
+        };
+        
+        let exists = match target {
+            OutcomeTarget::Edge(edge_id) => db.get_hyperedge(edge_id).await.is_some(),
+            OutcomeTarget::Pair(a, b) => db.get_node(a).await.is_some() && db.get_node(b).await.is_some(),
+        };
+        if !exists {
+            return Self::send_error_response(stream, 404, "Not found").await;
+        }
+        
+        match db.report_outcome(target, request.reward).await {
+            Ok(updates) => {
+                let response = ReportOutcomeResponse {
+                    updates,
+                    success: true,
+                };
+                let json = serde_json::to_string(&response)?;
+                Self::send_json_response(stream, 200, &json).await
+            }
+            Err(e) => {
+                tracing::error!("Failed to report outcome: {}", e);
+                Self::send_error_response(stream, 500, "Internal Server Error").await
+            }
+        }
+    }
+    
+    async fn handle_get_node(
+        db: Arc<Database>,
+        stream: tokio::net::TcpStream,
+        path: &str,
+    ) -> Result<()> {
+        let Some(node_id) = Self::resource_uuid(path, "nodes") else {
+            return Self::send_error_response(stream, 400, "Invalid UUID").await;
+        };
+        
+        match db.get_node(node_id).await {
+            Some(node) => {
+                let json = serde_json::to_string(&node)?;
+                Self::send_json_response(stream, 200, &json).await
+            }
+            None => Self::send_error_response(stream, 404, "Node not found").await,
+        }
+    }
+    
+    async fn handle_update_node(
+        db: Arc<Database>,
+        stream: tokio::net::TcpStream,
+        path: &str,
+        request: &str,
+    ) -> Result<()> {
+        let Some(node_id) = Self::resource_uuid(path, "nodes") else {
+            return Self::send_error_response(stream, 400, "Invalid UUID").await;
+        };
+        
+        let request: UpdateNodeRequest = match Self::request_body(request).map(serde_json::from_str) {
+            Some(Ok(request)) => request,
+            _ => return Self::send_error_response(stream, 400, "Invalid update").await,
+        };
+        
+        if db.get_node(node_id).await.is_none() {
+            return Self::send_error_response(stream, 404, "Node not found").await;
+        }
+        
+        match Self::apply_node_update(&db, node_id, request).await {
+            Ok(()) => match db.get_node(node_id).await {
+                Some(node) => {
+                    let json = serde_json::to_string(&node)?;
+                    Self::send_json_response(stream, 200, &json).await
+                }
+                None => Self::send_error_response(stream, 404, "Node not found").await,
+            },
+            Err(e) => {
+                tracing::error!("Failed to update node: {}", e);
+                Self::send_error_response(stream, 500, "Internal Server Error").await
+            }
+        }
+    }
+    
+    async fn apply_node_update(db: &Database, node_id: Uuid, request: UpdateNodeRequest) -> Result<()> {
+        db.update_node(node_id, NodeUpdate {
+            data: request.data,
+            tags: request.tags,
+            properties: request.properties,
+        }).await?;
+        
+        if let Some(node_type) = request.node_type {
+            db.set_node_type(node_id, node_type).await?;
+        }
+        
+        if request.add_tags.is_some() || request.remove_tags.is_some() {
+            db.edit_node_tags(
+                node_id,
+                request.add_tags.unwrap_or_default(),
+                request.remove_tags.unwrap_or_default(),
+            ).await?;
+        }
+        
+        Ok(())
+    }
+    
+    async fn handle_delete_node(
+        db: Arc<Database>,
+        stream: tokio::net::TcpStream,
+        path: &str,
+    ) -> Result<()> {
+        let Some(node_id) = Self::resource_uuid(path, "nodes") else {
+            return Self::send_error_response(stream, 400, "Invalid UUID").await;
+        };
+        
+        // "?cascade=shrink" keeps hyperedges that still have two or more members
+        let policy = match Self::query_param(path, "cascade") {
+            None | Some("drop") => CascadePolicy::DropEdges,
+            Some("shrink") => CascadePolicy::ShrinkEdges,
+            Some(_) => return Self::send_error_response(stream, 400, "Invalid cascade policy").await,
+        };
+        
+        if db.get_node(node_id).await.is_none() {
+            return Self::send_error_response(stream, 404, "Node not found").await;
+        }
+        
+        match db.remove_node(node_id, policy).await {
+            Ok(()) => {
+                let response = DeleteResponse {
+                    id: node_id,
+                    success: true,
+                };
+                let json = serde_json::to_string(&response)?;
+                Self::send_json_response(stream, 200, &json).await
+            }
+            Err(e) => {
+                tracing::error!("Failed to remove node: {}", e);
+                Self::send_error_response(stream, 500, "Internal Server Error").await
+            }
+        }
+    }
+    
+    async fn handle_get_edge(
+        db: Arc<Database>,
+        stream: tokio::net::TcpStream,
+        path: &str,
+    ) -> Result<()> {
+        let Some(edge_id) = Self::resource_uuid(path, "edges") else {
+            return Self::send_error_response(stream, 400, "Invalid UUID").await;
+        };
+        
+        match db.get_hyperedge(edge_id).await {
+            Some(edge) => {
+                let json = serde_json::to_string(&edge)?;
+                Self::send_json_response(stream, 200, &json).await
+            }
+            None => Self::send_error_response(stream, 404, "Edge not found").await,
+        }
+    }
+    
+    async fn handle_update_edge(
+        db: Arc<Database>,
+        stream: tokio::net::TcpStream,
+        path: &str,
+        request: &str,
+    ) -> Result<()> {
+        let Some(edge_id) = Self::resource_uuid(path, "edges") else {
+            return Self::send_error_response(stream, 400, "Invalid UUID").await;
+        };
+        
+        let request: UpdateEdgeRequest = match Self::request_body(request).map(serde_json::from_str) {
+            Some(Ok(request)) => request,
+            _ => return Self::send_error_response(stream, 400, "Invalid update").await,
+        };
+        
+        if db.get_hyperedge(edge_id).await.is_none() {
+            return Self::send_error_response(stream, 404, "Edge not found").await;
+        }
+        
+        match Self::apply_edge_update(&db, edge_id, request).await {
+            Ok(()) => match db.get_hyperedge(edge_id).await {
+                Some(edge) => {
+                    let json = serde_json::to_string(&edge)?;
+                    Self::send_json_response(stream, 200, &json).await
+                }
+                None => Self::send_error_response(stream, 404, "Edge not found").await,
+            },
+            Err(e) => {
+                tracing::error!("Failed to update edge: {}", e);
+                Self::send_error_response(stream, 500, "Internal Server Error").await
+            }
+        }
+    }
+    
+    async fn apply_edge_update(db: &Database, edge_id: Uuid, request: UpdateEdgeRequest) -> Result<()> {
+        db.update_hyperedge(edge_id, EdgeUpdate {
+            relationship: request.relationship,
+            strength: request.strength,
+            properties: request.properties,
+        }).await?;
+        
+        if let Some(delay) = request.delay {
+            db.set_edge_delay(edge_id, delay).await?;
+        }
+        
+        Ok(())
+    }
+    
+    async fn handle_delete_edge(
+        db: Arc<Database>,
+        stream: tokio::net::TcpStream,
+        path: &str,
+    ) -> Result<()> {
+        let Some(edge_id) = Self::resource_uuid(path, "edges") else {
+            return Self::send_error_response(stream, 400, "Invalid UUID").await;
+        };
+        
+        if db.get_hyperedge(edge_id).await.is_none() {
+            return Self::send_error_response(stream, 404, "Edge not found").await;
+        }
+        
+        match db.remove_hyperedge(edge_id).await {
+            Ok(()) => {
+                let response = DeleteResponse {
+                    id: edge_id,
+                    success: true,
+                };
+                let json = serde_json::to_string(&response)?;
+                Self::send_json_response(stream, 200, &json).await
+            }
+            Err(e) => {
+                tracing::error!("Failed to remove edge: {}", e);
+                Self::send_error_response(stream, 500, "Internal Server Error").await
+            }
+        }
+    }
+    
+    async fn handle_storage_stats(
+        db: Arc<Database>,
+        stream: tokio::net::TcpStream,
+    ) -> Result<()> {
+        match db.storage_stats().await {
+            Ok(stats) => {
+                let response = StorageStatsResponse {
+                    graph_file_size: stats.graph_file_size,
+                    neural_file_size: stats.neural_file_size,
+                    wal_file_size: stats.wal_file_size,
+                    backup_count: stats.backup_count,
+                    total_backup_size: stats.total_backup_size,
+                };
+                let json = serde_json::to_string(&response)?;
+                Self::send_json_response(stream, 200, &json).await
+            }
+            Err(e) => {
+                tracing::error!("Failed to read storage stats: {}", e);
+                Self::send_error_response(stream, 500, "Internal Server Error").await
+            }
+        }
+    }
+    
+    async fn handle_list_backups(
+        db: Arc<Database>,
+        stream: tokio::net::TcpStream,
+    ) -> Result<()> {
+        match db.list_backups().await {
+            Ok(backups) => {
+                let response = ListBackupsResponse {
+                    backups: backups
+                        .into_iter()
+                        .map(|backup| BackupResponse {
+                            name: backup.name,
+                            size_bytes: backup.size,
+                            created_at: backup.created_at,
+                        })
+                        .collect(),
+                };
+                let json = serde_json::to_string(&response)?;
+                Self::send_json_response(stream, 200, &json).await
+            }
+            Err(e) => {
+                tracing::error!("Failed to list backups: {}", e);
+                Self::send_error_response(stream, 500, "Internal Server Error").await
+            }
+        }
+    }
+    
+    async fn handle_create_backup(
+        db: Arc<Database>,
+        stream: tokio::net::TcpStream,
+    ) -> Result<()> {
+        match db.backup().await {
+            Ok(backup) => {
+                let response = BackupResponse {
+                    name: backup.name,
+                    size_bytes: backup.size,
+                    created_at: backup.created_at,
+                };
+                let json = serde_json::to_string(&response)?;
+                Self::send_json_response(stream, 201, &json).await
+            }
+            Err(e) => {
+                tracing::error!("Failed to create backup: {}", e);
+                Self::send_error_response(stream, 500, "Internal Server Error").await
+            }
+        }
+    }
+    
+    async fn handle_restore_backup(
+        db: Arc<Database>,
+        stream: tokio::net::TcpStream,
+        path: &str,
+    ) -> Result<()> {
+        // Extract backup name from path like "/admin/backups/{name}/restore"
+        let parts: Vec<&str> = path.split('/').collect();
+        if parts.len() != 5 {
+            return Self::send_error_response(stream, 400, "Invalid path").await;
+        }
+        
+        let name = parts[3];
+        let known = db.list_backups().await?.iter().any(|backup| backup.name == name);
+        if !known {
+            return Self::send_error_response(stream, 404, "Backup not found").await;
+        }
+        
+        match db.restore_backup(name).await {
+            Ok(()) => {
+                let response = RestoreBackupResponse {
+                    restored: name.to_string(),
+                    success: true,
+                };
+                let json = serde_json::to_string(&response)?;
+                Self::send_json_response(stream, 200, &json).await
+            }
+            Err(e) => {
+                tracing::error!("Failed to restore backup {}: {}", name, e);
+                Self::send_error_response(stream, 500, "Internal Server Error").await
+            }
+        }
+    }
+    
+    async fn handle_backup_retention(
+        db: Arc<Database>,
+        stream: tokio::net::TcpStream,
+        request: &str,
+    ) -> Result<()> {
+        let request: BackupRetentionRequest = match Self::request_body(request) {
+            Some(body) if !body.trim().is_empty() => serde_json::from_str(body)?,
+            _ => BackupRetentionRequest { keep: None },
+        };
+        let keep = request.keep.unwrap_or(db.config().backup_retention);
+        
+        match db.apply_backup_retention(keep).await {
+            Ok(removed) => {
+                let response = BackupRetentionResponse { removed, kept: keep };
+                let json = serde_json::to_string(&response)?;
+                Self::send_json_response(stream, 200, &json).await
+            }
+            Err(e) => {
+                tracing::error!("Failed to apply backup retention: {}", e);
+                Self::send_error_response(stream, 500, "Internal Server Error").await
+            }
+        }
+    }
+    
+    /// Match `/{collection}/{id}` (ignoring any query string) and return the id segment
+    fn resource_id<'a>(path: &'a str, collection: &str) -> Option<&'a str> {
+        let path = path.split('?').next().unwrap_or(path);
+        let id = path
+            .strip_prefix('/')?
+            .strip_prefix(collection)?
+            .strip_prefix('/')?;
+        
+        (!id.is_empty() && !id.contains('/')).then_some(id)
+    }
+    
+    /// Node ids in `/nodes/{id}/spike-train[/{other}]`
+    fn spike_train_path(path: &str) -> Option<(&str, Option<&str>)> {
+        let path = path.split('?').next().unwrap_or(path);
+        let rest = path.strip_prefix("/nodes/")?;
+        let (node_id, rest) = rest.split_once("/spike-train")?;
+        match rest {
+            "" => Some((node_id, None)),
+            other => other.strip_prefix('/').filter(|other| !other.contains('/')).map(|other| (node_id, Some(other))),
+        }
+    }
+    
+    fn resource_uuid(path: &str, collection: &str) -> Option<Uuid> {
+        Self::resource_id(path, collection).and_then(|id| Uuid::parse_str(id).ok())
+    }
+    
+    /// Look up a query string parameter
+    fn query_param<'a>(path: &'a str, key: &str) -> Option<&'a str> {
+        let (_, query) = path.split_once('?')?;
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| value)
+    }
+    
+    /// Extract the body of a raw HTTP request, if there is one
+    fn request_body(request: &str) -> Option<&str> {
+        if let Some(body_start) = request.find("\r\n\r\n") {
+            Some(&request[body_start + 4..])
+        } else {
+            request.find("\n\n").map(|body_start| &request[body_start + 2..])
+        }
+    }
+    
     async fn send_json_response(
         stream: tokio::net::TcpStream,
         status_code: u16,
@@ -342,7 +1286,7 @@ impl Server {
         };
         
         let response = format!(
-            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nAccess-Control-Allow-Origin: *\r\nAccess-Control-Allow-Methods: GET, POST, OPTIONS\r\nAccess-Control-Allow-Headers: Content-Type\r\nContent-Length: {}\r\n\r\n{}",
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nAccess-Control-Allow-Origin: *\r\nAccess-Control-Allow-Methods: GET, POST, PATCH, DELETE, OPTIONS\r\nAccess-Control-Allow-Headers: Content-Type\r\nContent-Length: {}\r\n\r\n{}",
             status_code, status_text, body.len(), body
         );
         
@@ -364,10 +1308,35 @@ impl Server {
         use tokio::io::AsyncWriteExt;
         let mut stream = stream;
         
-        let response = "HTTP/1.1 200 OK\r\nAccess-Control-Allow-Origin: *\r\nAccess-Control-Allow-Methods: GET, POST, OPTIONS\r\nAccess-Control-Allow-Headers: Content-Type\r\nContent-Length: 0\r\n\r\n";
+        let response = "HTTP/1.1 200 OK\r\nAccess-Control-Allow-Origin: *\r\nAccess-Control-Allow-Methods: GET, POST, PATCH, DELETE, OPTIONS\r\nAccess-Control-Allow-Headers: Content-Type\r\nContent-Length: 0\r\n\r\n";
         
         stream.write_all(response.as_bytes()).await?;
         stream.flush().await?;
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Writing end of a streamed download, handing full chunks to the connection
+struct ChunkWriter {
+    buffer: Vec<u8>,
+    chunks: tokio::sync::mpsc::Sender<Vec<u8>>,
+}
+
+impl std::io::Write for ChunkWriter {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(data);
+        if self.buffer.len() >= EXPORT_CHUNK_SIZE {
+            self.flush()?;
+        }
+        Ok(data.len())
+    }
+    
+    fn flush(&mut self) -> std::io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        self.chunks
+            .blocking_send(std::mem::take(&mut self.buffer))
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::BrokenPipe, "download was closed"))
+    }
+}
@@ -1,12 +1,31 @@
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::{watch, RwLock};
+use tokio::task::JoinHandle;
 use uuid::Uuid;
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 
-use crate::hypergraph::HyperGraph;
-use crate::neural::SpikeProcessor;
-use crate::storage::StorageEngine;
+use crate::analysis::{AnalysisConfig, SpikeTrainComparison, SpikeTrainSummary};
+use crate::export::{ExportFormat, Raster, RasterQuery};
+use crate::hypergraph::{
+    CascadePolicy, EdgeDelay, EdgeType, EdgeUpdate, HyperEdge, HyperGraph, HyperNode, NodeMetadata, NodeUpdate,
+    DEFAULT_SPIKE_THRESHOLD,
+};
+use crate::history::SpikeRetention;
+use crate::index::{NodeFilter, PropertyQuery};
+use crate::maintenance::{Maintenance, MaintenanceConfig, MaintenanceReport};
+use crate::network::{
+    CapacitatedMatching, CapacitatedMatchingConfig, CentralityConfig, CentralityMeasures, MatchingConfig, NetworkAnalyzer, ProposedEdge, RoommatesMatching,
+    StableMatching, TeamConfig, TeamProposal,
+};
+use crate::neural::{SpikeEvent, SpikeProcessor};
+use crate::neuron::{LifParameters, MembraneState, NeuronModel};
+use crate::plasticity::{ConductanceUpdate, OutcomeTarget, RewardConfig, RewardUpdate, StdpConfig};
+use crate::simulation::{CascadeArrival, Clock, FiringRule, SimulationResult, SystemClock};
+use crate::storage::{BackupInfo, StorageEngine, StorageStats, WalEntry};
 
 /// Main database instance for Neurographite
 /// 
@@ -25,6 +44,15 @@ pub struct Database {
     
     /// Database configuration
     config: DatabaseConfig,
+    
+    /// Scheduled backup task, if `backup_interval` is configured
+    backup_task: Option<JoinHandle<()>>,
+    
+    /// Background maintenance task, if `maintenance` is configured
+    maintenance_task: std::sync::Mutex<Option<JoinHandle<()>>>,
+    
+    /// Tells the maintenance task to stop
+    shutdown: watch::Sender<bool>,
 }
 
 #[derive(Clone, Debug)]
@@ -34,18 +62,38 @@ pub struct DatabaseConfig {
     pub decay_rate: f64,
     pub refractory_period: u64, // milliseconds
     pub max_cascade_depth: usize,
-    pub sync_interval: u64, // seconds
+    pub sync_interval: u64, // seconds between background checkpoints, 0 disables them
+    pub checkpoint_interval: usize, // WAL records between snapshots
+    pub backup_interval: Option<u64>, // seconds, None disables scheduled backups
+    pub backup_retention: usize, // number of backups to keep
+    pub neuron_models: HashMap<String, Arc<dyn NeuronModel>>, // by node type, others are LIF
+    pub stdp: Option<StdpConfig>, // None (the default) disables spike-timing-dependent plasticity
+    pub reward: Option<RewardConfig>, // None (the default) disables reward-modulated learning
+    pub firing_rules: HashMap<String, FiringRule>, // by relationship, others relay every spike
+    pub maintenance: Option<MaintenanceConfig>, // None (the default) disables background decay, pruning and checkpoints
+    pub spike_retention: SpikeRetention, // spikes kept per node and for how long
+    pub spike_analysis: AnalysisConfig, // bins, lags and time constants of spike train analysis
 }
 
 impl Default for DatabaseConfig {
     fn default() -> Self {
         Self {
             data_dir: "./data".to_string(),
-            spike_threshold: 0.7,
+            spike_threshold: DEFAULT_SPIKE_THRESHOLD,
             decay_rate: 0.99,
             refractory_period: 100,
             max_cascade_depth: 10,
             sync_interval: 60,
+            checkpoint_interval: 1000,
+            backup_interval: None,
+            backup_retention: 10,
+            neuron_models: HashMap::new(),
+            stdp: None,
+            reward: None,
+            firing_rules: HashMap::new(),
+            maintenance: None,
+            spike_retention: SpikeRetention::default(),
+            spike_analysis: AnalysisConfig::default(),
         }
     }
 }
@@ -58,106 +106,640 @@ impl Database {
             ..Default::default()
         };
         
-        let storage = Arc::new(StorageEngine::new(&config.data_dir).await?);
-        let graph = Arc::new(RwLock::new(HyperGraph::new()));
-        let neural = Arc::new(SpikeProcessor::new(config.clone()));
-        
-        let db = Self {
-            graph,
-            neural,
-            storage,
-            config,
-        };
-        
-        // Load existing data if available
-        db.load_from_storage().await?;
-        
-        Ok(db)
+        Self::with_config(config).await
     }
     
     /// Create a new database with custom configuration
     pub async fn with_config(config: DatabaseConfig) -> Result<Self> {
+        Self::with_clock(config, Arc::new(SystemClock)).await
+    }
+    
+    /// Create a database whose neural processing runs on the given clock
+    pub async fn with_clock(config: DatabaseConfig, clock: Arc<dyn Clock>) -> Result<Self> {
+        for rule in config.firing_rules.values() {
+            rule.validate()?;
+        }
+        if let Some(stdp) = &config.stdp {
+            stdp.validate()?;
+        }
+        if let Some(reward) = &config.reward {
+            reward.validate()?;
+        }
+        config.spike_analysis.validate()?;
+        for model in config.neuron_models.values() {
+            model.validate()?;
+        }
+        config.spike_retention.validate()?;
+        
         let storage = Arc::new(StorageEngine::new(&config.data_dir).await?);
         let graph = Arc::new(RwLock::new(HyperGraph::new()));
-        let neural = Arc::new(SpikeProcessor::new(config.clone()));
+        let neural = Arc::new(SpikeProcessor::with_clock(config.clone(), clock));
         
-        let db = Self {
+        let mut db = Self {
             graph,
             neural,
             storage,
             config,
+            backup_task: None,
+            maintenance_task: std::sync::Mutex::new(None),
+            shutdown: watch::channel(false).0,
         };
         
+        // Load existing data if available
         db.load_from_storage().await?;
+        
+        db.backup_task = db.spawn_backup_scheduler();
+        if db.config.maintenance.is_some() {
+            let task = db.maintenance().spawn(db.shutdown.subscribe());
+            *db.maintenance_task.lock().unwrap() = Some(task);
+        }
+        
         Ok(db)
     }
     
+    /// Get the database configuration
+    pub fn config(&self) -> &DatabaseConfig {
+        &self.config
+    }
+    
+    /// Current time on the clock neural processing runs on
+    pub fn now(&self) -> DateTime<Utc> {
+        self.neural.now()
+    }
+    
     /// Add a new node to the hypergraph
     pub async fn add_node(&self, data: serde_json::Value) -> Result<Uuid> {
+        self.add_node_with_metadata(data, NodeMetadata::default()).await
+    }
+    
+    /// Add a new node with its type, tags and properties
+    pub async fn add_node_with_metadata(&self, data: serde_json::Value, metadata: NodeMetadata) -> Result<Uuid> {
         let node_id = Uuid::new_v4();
         
-        {
-            let mut graph = self.graph.write().await;
-            graph.add_node(node_id, data)?;
-        }
+        self.apply_mutation(WalEntry::AddNodeWithMetadata { id: node_id, data, metadata, at: self.now() }).await?;
         
         // Trigger neural processing
         self.neural.process_new_node(node_id).await?;
         
-        // Persist changes
-        self.sync_to_storage().await?;
-        
         Ok(node_id)
     }
     
     /// Create a hyperedge connecting multiple nodes
     pub async fn connect_nodes(&self, node_ids: Vec<Uuid>, relationship: String, strength: f64) -> Result<Uuid> {
+        self.connect_nodes_with_type(node_ids, relationship, strength, EdgeType::Symmetric).await
+    }
+    
+    /// Create a hyperedge with the given propagation semantics
+    pub async fn connect_nodes_with_type(
+        &self,
+        node_ids: Vec<Uuid>,
+        relationship: String,
+        strength: f64,
+        edge_type: EdgeType,
+    ) -> Result<Uuid> {
         let edge_id = Uuid::new_v4();
         
-        {
-            let mut graph = self.graph.write().await;
-            graph.add_hyperedge(edge_id, node_ids.clone(), relationship, strength)?;
-        }
+        let entry = match edge_type {
+            EdgeType::Symmetric => WalEntry::AddHyperedge {
+                id: edge_id,
+                node_ids: node_ids.clone(),
+                relationship,
+                strength,
+                at: self.now(),
+            },
+            edge_type => WalEntry::AddTypedHyperedge {
+                id: edge_id,
+                node_ids: node_ids.clone(),
+                relationship,
+                strength,
+                edge_type,
+                at: self.now(),
+            },
+        };
+        self.apply_mutation(entry).await?;
         
         // Trigger spike propagation through the new connection
-        self.neural.propagate_spike(node_ids, strength).await?;
-        
-        self.sync_to_storage().await?;
+        self.propagate(node_ids, strength).await?;
         
         Ok(edge_id)
     }
     
+    /// Remove a node and clean up its neural state
+    pub async fn remove_node(&self, node_id: Uuid, policy: CascadePolicy) -> Result<()> {
+        self.apply_mutation(WalEntry::RemoveNode { id: node_id, policy, at: self.now() }).await?;
+        self.neural.forget_node(node_id).await;
+        Ok(())
+    }
+    
+    /// Remove a hyperedge
+    pub async fn remove_hyperedge(&self, edge_id: Uuid) -> Result<()> {
+        self.apply_mutation(WalEntry::RemoveHyperedge { id: edge_id }).await
+    }
+    
+    /// Apply a partial update to a node
+    pub async fn update_node(&self, node_id: Uuid, update: NodeUpdate) -> Result<()> {
+        self.apply_mutation(WalEntry::UpdateNode { id: node_id, update, at: self.now() }).await
+    }
+    
+    /// Change the type of a node
+    pub async fn set_node_type(&self, node_id: Uuid, node_type: String) -> Result<()> {
+        self.apply_mutation(WalEntry::SetNodeType { id: node_id, node_type, at: self.now() }).await
+    }
+    
+    /// Add and remove tags on a node
+    pub async fn edit_node_tags(&self, node_id: Uuid, add: Vec<String>, remove: Vec<String>) -> Result<()> {
+        self.apply_mutation(WalEntry::EditNodeTags { id: node_id, add, remove, at: self.now() }).await
+    }
+    
+    /// Set or remove (with a `null` value) node properties
+    pub async fn set_node_properties(
+        &self,
+        node_id: Uuid,
+        properties: HashMap<String, serde_json::Value>,
+    ) -> Result<()> {
+        self.update_node(node_id, NodeUpdate {
+            properties: Some(properties),
+            ..Default::default()
+        }).await
+    }
+    
+    /// Apply a partial update to a hyperedge
+    pub async fn update_hyperedge(&self, edge_id: Uuid, update: EdgeUpdate) -> Result<()> {
+        self.apply_mutation(WalEntry::UpdateHyperedge { id: edge_id, update, at: self.now() }).await
+    }
+    
+    /// Set how long spikes take to cross a hyperedge
+    pub async fn set_edge_delay(&self, edge_id: Uuid, delay: EdgeDelay) -> Result<()> {
+        self.apply_mutation(WalEntry::SetEdgeDelay { id: edge_id, delay, at: self.now() }).await
+    }
+    
+    /// Get a copy of a node
+    pub async fn get_node(&self, node_id: Uuid) -> Option<HyperNode> {
+        self.graph.read().await.get_node(&node_id).cloned()
+    }
+    
+    /// Get a copy of a hyperedge
+    pub async fn get_hyperedge(&self, edge_id: Uuid) -> Option<HyperEdge> {
+        self.graph.read().await.get_edge(&edge_id).cloned()
+    }
+    
+    /// Declare a secondary index on a node property key
+    pub async fn create_property_index(&self, key: &str) -> Result<()> {
+        self.apply_mutation(WalEntry::CreatePropertyIndex { key: key.to_string() }).await
+    }
+    
+    /// Remove a declared property index
+    pub async fn drop_property_index(&self, key: &str) -> Result<()> {
+        self.apply_mutation(WalEntry::DropPropertyIndex { key: key.to_string() }).await
+    }
+    
+    /// Find nodes whose property matches a query
+    pub async fn query_nodes(&self, key: &str, query: &PropertyQuery) -> Vec<HyperNode> {
+        let graph = self.graph.read().await;
+        graph.query_nodes(key, query).into_iter().cloned().collect()
+    }
+    
+    /// Find nodes matching every condition of a filter
+    pub async fn filter_nodes(&self, filter: &NodeFilter) -> Vec<HyperNode> {
+        let graph = self.graph.read().await;
+        graph.filter_nodes(filter).into_iter().cloned().collect()
+    }
+    
     /// Find similar nodes using neural activation patterns
     pub async fn find_similar(&self, node_id: Uuid, threshold: f64) -> Result<Vec<(Uuid, f64)>> {
         let graph = self.graph.read().await;
-        self.neural.find_similar_nodes(&*graph, node_id, threshold).await
+        self.neural.find_similar_nodes(&graph, node_id, threshold).await
     }
     
-    /// Discover potential relationships using stable matching algorithm
+    /// Discover potential relationships between active nodes with complementary
+    /// activation levels and network positions
     pub async fn discover_relationships(&self, max_results: usize) -> Result<Vec<(Uuid, Uuid, f64)>> {
         let graph = self.graph.read().await;
-        self.neural.stable_matching(&*graph, max_results).await
+        self.neural.complementary_pairs(&graph, max_results).await
+    }
+    
+    /// Degree, betweenness, closeness and eigenvector centrality of every node
+    pub async fn centrality(&self, config: &CentralityConfig) -> HashMap<Uuid, CentralityMeasures> {
+        let graph = self.graph.read().await;
+        NetworkAnalyzer::calculate_centrality_measures_with_config(&graph, config)
+    }
+    
+    /// Many-to-one stable matching; matched groups come back as proposed
+    /// hyperedges for `commit_proposals`
+    pub async fn capacitated_matching(&self, config: &CapacitatedMatchingConfig) -> Result<CapacitatedMatching> {
+        let graph = self.graph.read().await;
+        self.neural.capacitated_matching(&graph, config).await
+    }
+    
+    /// Propose teams of `config.size` members, best first; accept one with
+    /// `accept_proposal(&team.edge)`
+    pub async fn form_teams(&self, config: &TeamConfig) -> Result<Vec<TeamProposal>> {
+        let graph = self.graph.read().await;
+        self.neural.form_teams(&graph, config).await
+    }
+    
+    /// Create the hyperedge of a single proposal
+    pub async fn accept_proposal(&self, proposal: &ProposedEdge) -> Result<Uuid> {
+        let edge_ids = self.commit_proposals(std::slice::from_ref(proposal)).await?;
+        Ok(edge_ids[0])
+    }
+    
+    /// Create the hyperedges of a set of proposals, returning their ids
+    ///
+    /// Every proposal is checked before any edge is created, under the same
+    /// write lock that creates them, so either all edges are added or none.
+    pub async fn commit_proposals(&self, proposals: &[ProposedEdge]) -> Result<Vec<Uuid>> {
+        let edge_ids: Vec<Uuid> = proposals.iter().map(|_| Uuid::new_v4()).collect();
+        let entries = proposals
+            .iter()
+            .zip(&edge_ids)
+            .map(|(proposal, &id)| match &proposal.edge_type {
+                EdgeType::Symmetric => WalEntry::AddHyperedge {
+                    id,
+                    node_ids: proposal.node_ids.clone(),
+                    relationship: proposal.relationship.clone(),
+                    strength: proposal.strength,
+                    at: self.now(),
+                },
+                edge_type => WalEntry::AddTypedHyperedge {
+                    id,
+                    node_ids: proposal.node_ids.clone(),
+                    relationship: proposal.relationship.clone(),
+                    strength: proposal.strength,
+                    edge_type: edge_type.clone(),
+                    at: self.now(),
+                },
+            })
+            .collect();
+        log_mutations(&self.graph, &self.neural, &self.storage, self.config.checkpoint_interval, entries).await?;
+        
+        // Trigger spike propagation through the new connections
+        for proposal in proposals {
+            self.propagate(proposal.node_ids.clone(), proposal.strength).await?;
+        }
+        Ok(edge_ids)
+    }
+    
+    /// Stable roommates matching of the nodes matching `filter`, by goal
+    /// alignment; pairs scoring below `min_score` are never matched
+    ///
+    /// The matching itself runs on a blocking thread after the graph lock is
+    /// released, since instances without a stable matching fall back to a
+    /// costly local search.
+    pub async fn stable_roommates(&self, filter: &NodeFilter, min_score: f64) -> Result<RoommatesMatching> {
+        let (node_ids, preferences) = {
+            let graph = self.graph.read().await;
+            let mut node_ids: Vec<Uuid> = graph.filter_nodes(filter).iter().map(|node| node.id).collect();
+            node_ids.sort();
+            
+            let preferences = NetworkAnalyzer::alignment_preferences(&graph, &node_ids, min_score)?;
+            (node_ids, preferences)
+        };
+        
+        let matching = tokio::task::spawn_blocking(move || NetworkAnalyzer::stable_roommates(&node_ids, &preferences)).await?;
+        Ok(matching)
+    }
+    
+    /// Two-sided stable matching between the nodes picked by the config's filters
+    pub async fn stable_matching(&self, config: &MatchingConfig) -> Result<StableMatching> {
+        let graph = self.graph.read().await;
+        self.neural.stable_matching(&graph, config).await
+    }
+    
+    /// Fire the given nodes and let the spikes propagate through the network
+    pub async fn stimulate(&self, node_ids: Vec<Uuid>, strength: f64) -> Result<SimulationResult> {
+        self.propagate(node_ids, strength).await
+    }
+    
+    /// Report the outcome of a relationship as a reward signal
+    ///
+    /// A positive reward strengthens, and a negative one weakens, the edges
+    /// that were recently active in causal order, in proportion to their
+    /// eligibility traces.
+    pub async fn report_outcome(&self, target: OutcomeTarget, reward: f64) -> Result<Vec<RewardUpdate>> {
+        let Some(config) = &self.config.reward else {
+            return Err(anyhow::anyhow!("Reward-modulated learning is disabled"));
+        };
+        if !reward.is_finite() {
+            return Err(anyhow::anyhow!("Reward must be finite"));
+        }
+        
+        let mut updates = {
+            let graph = self.graph.read().await;
+            let edge_ids = target.edges(&graph)?;
+            self.neural.reward_updates(&graph, &edge_ids, reward).await
+        };
+        if updates.is_empty() {
+            return Ok(updates);
+        }
+        
+        self.apply_mutation(WalEntry::AdjustConductances {
+            changes: updates.iter().map(|update| (update.edge_id, update.change)).collect(),
+            min: config.min_conductance,
+            max: config.max_conductance,
+            at: self.now(),
+        }).await?;
+        
+        let graph = self.graph.read().await;
+        for update in &mut updates {
+            if let Some(edge) = graph.get_edge(&update.edge_id) {
+                update.conductance = edge.conductance;
+            }
+        }
+        
+        Ok(updates)
+    }
+    
+    /// Current eligibility trace of an edge for reward-modulated learning
+    pub async fn eligibility(&self, edge_id: Uuid) -> f64 {
+        self.neural.eligibility(edge_id).await
+    }
+    
+    /// Recorded plasticity updates, oldest first, optionally for a single edge
+    pub async fn conductance_updates(&self, edge_id: Option<Uuid>) -> Vec<ConductanceUpdate> {
+        self.neural.conductance_updates(edge_id).await
+    }
+    
+    /// Set the neuron parameters for nodes of a type and persist them
+    pub async fn set_neuron_parameters(&self, node_type: &str, parameters: LifParameters) -> Result<()> {
+        parameters.validate()?;
+        self.neural.set_neuron_parameters(node_type, parameters).await;
+        self.checkpoint().await
+    }
+    
+    /// Neuron parameters in effect for nodes of a type
+    pub async fn neuron_parameters(&self, node_type: &str) -> LifParameters {
+        self.neural.neuron_parameters(node_type).await
+    }
+    
+    /// Set the coincidence rule for hyperedges of a relationship type and persist it
+    pub async fn set_firing_rule(&self, relationship: &str, rule: FiringRule) -> Result<()> {
+        rule.validate()?;
+        self.neural.set_firing_rule(relationship, rule).await;
+        self.checkpoint().await
+    }
+    
+    /// Coincidence rule in effect for hyperedges of a relationship type
+    pub async fn firing_rule(&self, relationship: &str) -> Option<FiringRule> {
+        self.neural.firing_rule(relationship).await
+    }
+    
+    /// Spikes of a node within `window` of now, oldest first
+    pub async fn recent_spikes(&self, node_id: Uuid, window: chrono::Duration) -> Vec<SpikeEvent> {
+        self.neural.recent_spikes(node_id, window).await
+    }
+    
+    /// All recorded spikes between two times (inclusive), in time order
+    pub async fn spikes_between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<SpikeEvent> {
+        self.neural.spikes_between(from, to).await
+    }
+    
+    /// ISI distribution, firing rates and bursts of a node over the last `window`
+    pub async fn analyze_spike_train(&self, node_id: Uuid, window: chrono::Duration) -> Result<SpikeTrainSummary> {
+        if self.graph.read().await.get_node(&node_id).is_none() {
+            return Err(anyhow::anyhow!("Node {} not found", node_id));
+        }
+        self.neural.analyze_spike_train(node_id, window).await
+    }
+    
+    /// Distances, synchrony and cross-correlogram of two nodes' spike trains
+    /// over the last `window`
+    pub async fn compare_spike_trains(&self, a: Uuid, b: Uuid, window: chrono::Duration) -> Result<SpikeTrainComparison> {
+        let graph = self.graph.read().await;
+        if let Some(missing) = [a, b].into_iter().find(|id| graph.get_node(id).is_none()) {
+            return Err(anyhow::anyhow!("Node {} not found", missing));
+        }
+        drop(graph);
+        self.neural.compare_spike_trains(a, b, window).await
+    }
+    
+    /// Spikes and, if asked for, membrane traces of a set of nodes over a window
+    pub async fn raster(&self, query: &RasterQuery) -> Result<Raster> {
+        let graph = self.graph.read().await;
+        if let Some(missing) = query.node_ids.iter().find(|id| graph.get_node(id).is_none()) {
+            return Err(anyhow::anyhow!("Node {} not found", missing));
+        }
+        self.neural.raster(&graph, query).await
+    }
+    
+    /// Write the spikes of a raster query as CSV, NDJSON or `.npy`
+    pub async fn export_spikes(&self, query: &RasterQuery, format: ExportFormat, out: impl std::io::Write) -> Result<()> {
+        self.raster(query).await?.write_spikes(format, out)?;
+        Ok(())
+    }
+    
+    /// Write the membrane traces of a raster query as CSV, NDJSON or `.npy`
+    pub async fn export_membranes(&self, query: &RasterQuery, format: ExportFormat, out: impl std::io::Write) -> Result<()> {
+        if query.membrane_interval.is_none() {
+            return Err(anyhow::anyhow!("No membrane sampling interval given"));
+        }
+        self.raster(query).await?.write_membranes(format, out)?;
+        Ok(())
+    }
+    
+    /// Current membrane potential of a node
+    pub async fn membrane_potential(&self, node_id: Uuid) -> Option<f64> {
+        let graph = self.graph.read().await;
+        self.neural.membrane_potential(&graph, node_id).await
+    }
+    
+    /// Current membrane state of a node, including its recovery variable
+    pub async fn membrane_state(&self, node_id: Uuid) -> Option<MembraneState> {
+        let graph = self.graph.read().await;
+        self.neural.membrane_state(&graph, node_id).await
     }
     
     /// Get network effects from a node activation
-    pub async fn simulate_network_effect(&self, node_id: Uuid, activation_strength: f64) -> Result<Vec<(Uuid, f64)>> {
+    pub async fn simulate_network_effect(&self, node_id: Uuid, activation_strength: f64) -> Result<Vec<CascadeArrival>> {
         let graph = self.graph.read().await;
-        self.neural.simulate_cascade(&*graph, node_id, activation_strength).await
+        self.neural.simulate_cascade(&graph, node_id, activation_strength).await
+    }
+    
+    /// Write a snapshot of the graph and truncate the write-ahead log
+    pub async fn checkpoint(&self) -> Result<()> {
+        checkpoint_state(&self.graph, &self.neural, &self.storage).await
+    }
+    
+    /// Checkpoint and copy the resulting graph and neural state into the
+    /// backup directory
+    pub async fn backup(&self) -> Result<BackupInfo> {
+        create_backup(&self.graph, &self.neural, &self.storage).await
+    }
+    
+    /// List available backups, oldest first
+    pub async fn list_backups(&self) -> Result<Vec<BackupInfo>> {
+        let mut backups = Vec::new();
+        for path in self.storage.list_backups().await? {
+            backups.push(self.storage.backup_info(&path).await?);
+        }
+        
+        backups.sort_by_key(|backup| backup.created_at);
+        Ok(backups)
+    }
+    
+    /// Replace the live graph and neural state with the contents of a backup
+    ///
+    /// The backup is decoded and written out as the new snapshot before the
+    /// in-memory state is swapped, all under the graph write lock, so readers
+    /// see either the old or the restored state and never a mix. Backups
+    /// holding only a graph keep the current neural state.
+    pub async fn restore_backup(&self, name: &str) -> Result<()> {
+        let backup_path = self.storage.backup_path(name)?;
+        let (restored, restored_neural) = self.storage.restore_from_backup(&backup_path).await?;
+        
+        let mut graph = self.graph.write().await;
+        
+        match restored_neural {
+            Some(neural) => {
+                self.storage.checkpoint(&restored, &neural).await?;
+                self.neural.restore_state(neural).await;
+            }
+            None => {
+                let neural = self.neural.state().await;
+                self.storage.checkpoint(&restored, &neural).await?;
+            }
+        }
+        
+        self.neural.sync_with_graph(&restored).await;
+        *graph = restored;
+        tracing::info!("Restored graph from backup {}", name);
+        
+        Ok(())
+    }
+    
+    /// Delete all but the newest `keep_count` backups
+    pub async fn apply_backup_retention(&self, keep_count: usize) -> Result<usize> {
+        self.storage.cleanup_backups(keep_count).await
+    }
+    
+    /// Get storage statistics (file and backup sizes)
+    pub async fn storage_stats(&self) -> Result<StorageStats> {
+        self.storage.stats().await
     }
     
     /// Load database state from storage
+    ///
+    /// Reads the last snapshot, then replays any mutations logged since.
+    /// Neural state is restored as of the last checkpoint.
     async fn load_from_storage(&self) -> Result<()> {
-        if let Ok(graph_data) = self.storage.load_graph().await {
-            let mut graph = self.graph.write().await;
-            *graph = graph_data;
+        let mut graph = self.storage.load_graph().await?;
+        
+        if let Some(neural_state) = self.storage.load_neural_state().await? {
+            self.neural.restore_state(neural_state).await;
         }
+        
+        let entries = self.storage.replay_wal().await?;
+        for entry in &entries {
+            entry.apply(&mut graph)?;
+        }
+        
+        self.neural.sync_with_graph(&graph).await;
+        
+        *self.graph.write().await = graph;
+        
+        if !entries.is_empty() {
+            tracing::info!("Replayed {} write-ahead log entries", entries.len());
+            self.checkpoint().await?;
+        }
+        
         Ok(())
     }
     
-    /// Sync current state to storage
-    async fn sync_to_storage(&self) -> Result<()> {
-        let graph = self.graph.read().await;
-        self.storage.save_graph(&*graph).await
+    /// Run a spike cascade and apply the conductance changes it caused
+    async fn propagate(&self, node_ids: Vec<Uuid>, strength: f64) -> Result<SimulationResult> {
+        let mut result = {
+            let graph = self.graph.read().await;
+            self.neural.propagate_spike(&graph, node_ids, strength).await?
+        };
+        
+        if let (Some(stdp), false) = (&self.config.stdp, result.conductance_updates.is_empty()) {
+            let changes = result
+                .conductance_updates
+                .iter()
+                .map(|update| (update.edge_id, update.change()))
+                .collect();
+            self.apply_mutation(WalEntry::AdjustConductances {
+                changes,
+                min: stdp.min_conductance,
+                max: stdp.max_conductance,
+                at: self.now(),
+            }).await?;
+            
+            // Record what the edges ended up at, in case they moved meanwhile
+            let graph = self.graph.read().await;
+            for update in &mut result.conductance_updates {
+                if let Some(edge) = graph.get_edge(&update.edge_id) {
+                    update.conductance = edge.conductance;
+                }
+            }
+            drop(graph);
+            self.neural.record_conductance_updates(&result.conductance_updates).await;
+        }
+        
+        Ok(result)
+    }
+    
+    /// Record a mutation in the write-ahead log and apply it to the graph
+    async fn apply_mutation(&self, entry: WalEntry) -> Result<()> {
+        log_mutation(&self.graph, &self.neural, &self.storage, self.config.checkpoint_interval, entry).await
+    }
+    
+    /// Run decay, pruning and a checkpoint right away, whether or not the
+    /// background task is enabled
+    pub async fn run_maintenance(&self) -> Result<MaintenanceReport> {
+        self.maintenance().run_once().await
+    }
+    
+    /// Stop the background maintenance task and write a final checkpoint
+    pub async fn shutdown(&self) -> Result<()> {
+        self.shutdown.send_replace(true);
+        let task = self.maintenance_task.lock().unwrap().take();
+        if let Some(task) = task {
+            task.await?;
+        }
+        self.checkpoint().await
+    }
+    
+    fn maintenance(&self) -> Maintenance {
+        Maintenance {
+            graph: Arc::clone(&self.graph),
+            neural: Arc::clone(&self.neural),
+            storage: Arc::clone(&self.storage),
+            config: self.config.maintenance.clone().unwrap_or_default(),
+            decay_rate: self.config.decay_rate,
+            checkpoint_interval: self.config.checkpoint_interval,
+            sync_interval: self.config.sync_interval,
+        }
+    }
+    
+    /// Start the scheduled backup task if `backup_interval` is configured
+    fn spawn_backup_scheduler(&self) -> Option<JoinHandle<()>> {
+        let interval = self.config.backup_interval?;
+        let retention = self.config.backup_retention;
+        let graph = Arc::clone(&self.graph);
+        let neural = Arc::clone(&self.neural);
+        let storage = Arc::clone(&self.storage);
+        
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(interval.max(1)));
+            ticker.tick().await; // The first tick completes immediately
+            
+            loop {
+                ticker.tick().await;
+                
+                match create_backup(&graph, &neural, &storage).await {
+                    Ok(backup) => tracing::info!("Scheduled backup written to {}", backup.name),
+                    Err(e) => {
+                        tracing::error!("Scheduled backup failed: {}", e);
+                        continue;
+                    }
+                }
+                
+                if let Err(e) = storage.cleanup_backups(retention).await {
+                    tracing::error!("Failed to apply backup retention: {}", e);
+                }
+            }
+        }))
     }
     
     /// Get database statistics
@@ -175,6 +757,102 @@ impl Database {
     }
 }
 
+impl Drop for Database {
+    fn drop(&mut self) {
+        if let Some(task) = self.backup_task.take() {
+            task.abort();
+        }
+        if let Some(task) = self.maintenance_task.get_mut().unwrap().take() {
+            task.abort();
+        }
+    }
+}
+
+/// Record a mutation in the write-ahead log and apply it to the graph,
+/// checkpointing once `checkpoint_interval` records have built up
+///
+/// The entry is validated first and only applied once it is durable, so a
+/// failed append leaves the graph untouched. The log append happens under
+/// the graph write lock so that the log order always matches the order in
+/// which mutations were applied.
+pub(crate) async fn log_mutation(
+    graph: &RwLock<HyperGraph>,
+    neural: &SpikeProcessor,
+    storage: &StorageEngine,
+    checkpoint_interval: usize,
+    entry: WalEntry,
+) -> Result<()> {
+    let mut graph = graph.write().await;
+    entry.validate(&graph)?;
+    
+    let pending = storage.append_wal(&entry).await?;
+    entry.apply(&mut graph)?;
+    if pending >= checkpoint_interval {
+        let neural = neural.state().await;
+        storage.checkpoint(&graph, &neural).await?;
+    }
+    
+    Ok(())
+}
+
+/// Validate, log and apply independent mutations under one write lock
+///
+/// Every entry is validated before any is logged, so a rejected entry
+/// leaves the graph untouched. The entries must not depend on one another.
+pub(crate) async fn log_mutations(
+    graph: &RwLock<HyperGraph>,
+    neural: &SpikeProcessor,
+    storage: &StorageEngine,
+    checkpoint_interval: usize,
+    entries: Vec<WalEntry>,
+) -> Result<()> {
+    let mut graph = graph.write().await;
+    for entry in &entries {
+        entry.validate(&graph)?;
+    }
+    
+    let mut pending = 0;
+    for entry in &entries {
+        pending = storage.append_wal(entry).await?;
+        entry.apply(&mut graph)?;
+    }
+    if pending >= checkpoint_interval {
+        let neural = neural.state().await;
+        storage.checkpoint(&graph, &neural).await?;
+    }
+    
+    Ok(())
+}
+
+/// Snapshot the graph and neural state, then truncate the write-ahead log
+pub(crate) async fn checkpoint_state(
+    graph: &RwLock<HyperGraph>,
+    neural: &SpikeProcessor,
+    storage: &StorageEngine,
+) -> Result<()> {
+    // Mutations are logged under the graph write lock, so holding the
+    // read lock keeps the snapshot and the log truncation consistent
+    let graph = graph.read().await;
+    let neural = neural.state().await;
+    storage.checkpoint(&graph, &neural).await
+}
+
+/// Checkpoint, then copy the fresh snapshot into the backup directory
+async fn create_backup(
+    graph: &RwLock<HyperGraph>,
+    neural: &SpikeProcessor,
+    storage: &StorageEngine,
+) -> Result<BackupInfo> {
+    // As in `checkpoint_state`, the read lock keeps mutations out until the
+    // snapshot has been copied
+    let backup_path = {
+        let graph = graph.read().await;
+        let neural = neural.state().await;
+        storage.checkpoint_and_backup(&graph, &neural).await?
+    };
+    storage.backup_info(&backup_path).await
+}
+
 #[derive(Debug, Clone)]
 pub struct DatabaseStats {
     pub node_count: usize,
@@ -0,0 +1,244 @@
+use std::io::{self, Write};
+use std::str::FromStr;
+use uuid::Uuid;
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use anyhow::{anyhow, Result};
+
+use crate::neural::SpikeEvent;
+
+/// Most membrane samples taken per node in one export
+pub const MAX_TRACE_SAMPLES: usize = 1_000_000;
+
+/// File formats spike rasters and membrane traces can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Csv,
+    /// One JSON object per line
+    Ndjson,
+    /// NumPy array of 64-bit floats
+    Npy,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv",
+            ExportFormat::Ndjson => "application/x-ndjson",
+            ExportFormat::Npy => "application/octet-stream",
+        }
+    }
+    
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Npy => "npy",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = anyhow::Error;
+    
+    fn from_str(format: &str) -> Result<Self> {
+        match format {
+            "csv" => Ok(ExportFormat::Csv),
+            "ndjson" => Ok(ExportFormat::Ndjson),
+            "npy" => Ok(ExportFormat::Npy),
+            _ => Err(anyhow!("Unknown export format {}", format)),
+        }
+    }
+}
+
+/// Which spikes and membrane traces to export
+#[derive(Debug, Clone, PartialEq)]
+pub struct RasterQuery {
+    /// Nodes to export, in row order; empty means every node
+    pub node_ids: Vec<Uuid>,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    /// Sample membrane potentials every this many ms, if set
+    pub membrane_interval: Option<f64>,
+}
+
+/// Spikes of a set of nodes over a time window, with optional membrane traces
+///
+/// Times are in ms since `from`. Nodes are numbered by their position in
+/// `node_ids`, which is how they appear in `.npy` output.
+#[derive(Debug, Clone)]
+pub struct Raster {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub node_ids: Vec<Uuid>,
+    /// Spikes of the nodes in time order
+    pub spikes: Vec<SpikeEvent>,
+    pub traces: Option<MembraneTraces>,
+}
+
+/// Membrane potentials sampled on a common time grid
+#[derive(Debug, Clone, PartialEq)]
+pub struct MembraneTraces {
+    /// Time between samples (ms)
+    pub interval: f64,
+    /// One row of samples per node, in the order of `Raster::node_ids`
+    pub potentials: Vec<Vec<f64>>,
+}
+
+#[derive(Serialize)]
+struct SpikeRow {
+    node_id: Uuid,
+    node_index: usize,
+    time: f64,
+    timestamp: DateTime<Utc>,
+    intensity: f64,
+    propagation_depth: usize,
+}
+
+#[derive(Serialize)]
+struct SampleRow {
+    node_id: Uuid,
+    node_index: usize,
+    time: f64,
+    potential: f64,
+}
+
+impl RasterQuery {
+    /// Number of membrane samples per node, or an error for an unusable interval
+    pub fn samples(&self) -> Result<Option<usize>> {
+        let Some(interval) = self.membrane_interval else {
+            return Ok(None);
+        };
+        if interval.is_nan() || interval <= 0.0 {
+            return Err(anyhow!("Membrane sampling interval must be positive"));
+        }
+        
+        let duration = millis(self.to - self.from).max(0.0);
+        let samples = (duration / interval).floor() + 1.0;
+        if samples > MAX_TRACE_SAMPLES as f64 {
+            return Err(anyhow!("More than {} membrane samples per node requested", MAX_TRACE_SAMPLES));
+        }
+        Ok(Some(samples as usize))
+    }
+}
+
+impl Raster {
+    /// Write the spikes: one row per spike, or a `(spikes, 2)` array of node
+    /// index and time for `.npy`
+    pub fn write_spikes(&self, format: ExportFormat, mut out: impl Write) -> io::Result<()> {
+        let rows: Vec<SpikeRow> = self.spike_rows().collect();
+        match format {
+            ExportFormat::Csv => {
+                writeln!(out, "node_id,node_index,time,timestamp,intensity,propagation_depth")?;
+                for row in rows {
+                    writeln!(
+                        out,
+                        "{},{},{},{},{},{}",
+                        row.node_id,
+                        row.node_index,
+                        row.time,
+                        row.timestamp.to_rfc3339(),
+                        row.intensity,
+                        row.propagation_depth,
+                    )?;
+                }
+            }
+            ExportFormat::Ndjson => {
+                for row in rows {
+                    serde_json::to_writer(&mut out, &row)?;
+                    writeln!(out)?;
+                }
+            }
+            ExportFormat::Npy => {
+                write_npy_header(&mut out, &[rows.len(), 2])?;
+                for row in rows {
+                    out.write_all(&(row.node_index as f64).to_le_bytes())?;
+                    out.write_all(&row.time.to_le_bytes())?;
+                }
+            }
+        }
+        out.flush()
+    }
+    
+    /// Write the membrane traces: one row per sample, or a `(nodes, samples)`
+    /// array of potentials for `.npy`
+    pub fn write_membranes(&self, format: ExportFormat, mut out: impl Write) -> io::Result<()> {
+        let Some(traces) = &self.traces else {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "no membrane traces were sampled"));
+        };
+        let samples = || {
+            self.node_ids.iter().zip(&traces.potentials).enumerate().flat_map(|(node_index, (node_id, row))| {
+                row.iter().enumerate().map(move |(sample, potential)| SampleRow {
+                    node_id: *node_id,
+                    node_index,
+                    time: sample as f64 * traces.interval,
+                    potential: *potential,
+                })
+            })
+        };
+        
+        match format {
+            ExportFormat::Csv => {
+                writeln!(out, "node_id,node_index,time,potential")?;
+                for row in samples() {
+                    writeln!(out, "{},{},{},{}", row.node_id, row.node_index, row.time, row.potential)?;
+                }
+            }
+            ExportFormat::Ndjson => {
+                for row in samples() {
+                    serde_json::to_writer(&mut out, &row)?;
+                    writeln!(out)?;
+                }
+            }
+            ExportFormat::Npy => {
+                let columns = traces.potentials.first().map_or(0, Vec::len);
+                write_npy_header(&mut out, &[self.node_ids.len(), columns])?;
+                for row in samples() {
+                    out.write_all(&row.potential.to_le_bytes())?;
+                }
+            }
+        }
+        out.flush()
+    }
+    
+    fn spike_rows(&self) -> impl Iterator<Item = SpikeRow> + '_ {
+        let index: std::collections::HashMap<Uuid, usize> =
+            self.node_ids.iter().enumerate().map(|(i, node_id)| (*node_id, i)).collect();
+        self.spikes.iter().filter_map(move |spike| {
+            Some(SpikeRow {
+                node_id: spike.node_id,
+                node_index: *index.get(&spike.node_id)?,
+                time: millis(spike.timestamp - self.from),
+                timestamp: spike.timestamp,
+                intensity: spike.intensity,
+                propagation_depth: spike.propagation_depth,
+            })
+        })
+    }
+}
+
+/// Header of a version 1.0 `.npy` file holding a C-ordered array of
+/// little-endian 64-bit floats
+fn write_npy_header(out: &mut impl Write, shape: &[usize]) -> io::Result<()> {
+    let dims: Vec<String> = shape.iter().map(usize::to_string).collect();
+    let shape = match dims.as_slice() {
+        [single] => format!("({},)", single),
+        dims => format!("({})", dims.join(", ")),
+    };
+    let mut header = format!("{{'descr': '<f8', 'fortran_order': False, 'shape': {}, }}", shape);
+    
+    // Magic, version and length take 10 bytes; the whole header is padded
+    // to a multiple of 64 and ends in a newline
+    let unpadded = 10 + header.len() + 1;
+    header.push_str(&" ".repeat(unpadded.next_multiple_of(64) - unpadded));
+    header.push('\n');
+    
+    out.write_all(b"\x93NUMPY\x01\x00")?;
+    out.write_all(&(header.len() as u16).to_le_bytes())?;
+    out.write_all(header.as_bytes())
+}
+
+fn millis(duration: chrono::Duration) -> f64 {
+    duration.num_microseconds().unwrap_or(i64::MAX) as f64 / 1000.0
+}
@@ -5,6 +5,7 @@ use tokio::net::TcpStream;
 use anyhow::Result;
 
 /// Simple static file server for the frontend
+#[derive(Clone)]
 pub struct StaticFileServer {
     frontend_dir: String,
 }
@@ -0,0 +1,311 @@
+use std::collections::{HashMap, VecDeque};
+use uuid::Uuid;
+use serde::{Deserialize, Serialize};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+
+use crate::neural::SpikeEvent;
+use crate::neuron::{MembraneState, NeuronModel};
+
+/// How much spike history is kept
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpikeRetention {
+    /// Spikes kept per node; older ones are evicted as new ones arrive
+    pub max_per_node: usize,
+    /// Spikes older than this many seconds are pruned, if set
+    pub max_age: Option<u64>,
+}
+
+impl Default for SpikeRetention {
+    fn default() -> Self {
+        Self {
+            max_per_node: 1000,
+            max_age: Some(3600),
+        }
+    }
+}
+
+impl SpikeRetention {
+    /// `max_age` as a duration, if set
+    pub fn max_age(&self) -> Result<Option<Duration>> {
+        self.max_age
+            .map(|seconds| {
+                i64::try_from(seconds)
+                    .ok()
+                    .and_then(Duration::try_seconds)
+                    .ok_or_else(|| anyhow::anyhow!("Spike retention max_age of {} seconds is out of range", seconds))
+            })
+            .transpose()
+    }
+    
+    /// Check that `max_age` fits in a duration
+    pub fn validate(&self) -> Result<()> {
+        self.max_age().map(|_| ())
+    }
+}
+
+/// Spike history indexed by node
+///
+/// Each node's spikes are kept in time order in a ring buffer bounded by
+/// `capacity`, so windowed queries are binary searches instead of scans.
+/// On disk it is a flat list of spikes, as it was before it was indexed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "Vec<SpikeEvent>", into = "Vec<SpikeEvent>")]
+pub struct SpikeHistory {
+    by_node: HashMap<Uuid, VecDeque<SpikeEvent>>,
+    capacity: usize,
+    len: usize,
+}
+
+impl Default for SpikeHistory {
+    fn default() -> Self {
+        Self::new(SpikeRetention::default().max_per_node)
+    }
+}
+
+impl SpikeHistory {
+    /// Empty history keeping up to `capacity` spikes per node
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            by_node: HashMap::new(),
+            capacity,
+            len: 0,
+        }
+    }
+    
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    
+    /// Change the per-node capacity, evicting the oldest spikes past it
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        for spikes in self.by_node.values_mut() {
+            let excess = spikes.len().saturating_sub(capacity);
+            spikes.drain(..excess);
+            self.len -= excess;
+        }
+        self.by_node.retain(|_, spikes| !spikes.is_empty());
+    }
+    
+    /// Record a spike, evicting the node's oldest one if its buffer is full
+    pub fn push(&mut self, spike: SpikeEvent) {
+        if self.capacity == 0 {
+            return;
+        }
+        
+        let spikes = self.by_node.entry(spike.node_id).or_default();
+        // Spikes nearly always arrive in order; others are slotted in
+        let index = spikes.partition_point(|earlier| earlier.timestamp <= spike.timestamp);
+        spikes.insert(index, spike);
+        self.len += 1;
+        
+        if spikes.len() > self.capacity {
+            spikes.pop_front();
+            self.len -= 1;
+        }
+    }
+    
+    /// Every spike, grouped by node
+    pub fn iter(&self) -> impl Iterator<Item = &SpikeEvent> {
+        self.by_node.values().flatten()
+    }
+    
+    /// Spikes of a node, oldest first
+    pub fn node(&self, node_id: &Uuid) -> impl DoubleEndedIterator<Item = &SpikeEvent> {
+        self.by_node.get(node_id).into_iter().flatten()
+    }
+    
+    /// Most recent spike of a node
+    pub fn latest(&self, node_id: &Uuid) -> Option<&SpikeEvent> {
+        self.by_node.get(node_id)?.back()
+    }
+    
+    /// Spikes of a node between `from` and `to` (inclusive), oldest first
+    pub fn node_window(
+        &self,
+        node_id: &Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> impl DoubleEndedIterator<Item = &SpikeEvent> {
+        let spikes = self.by_node.get(node_id);
+        let range = spikes.map_or(0..0, |spikes| Self::range(spikes, from, to));
+        spikes.into_iter().flat_map(move |spikes| spikes.range(range.clone()))
+    }
+    
+    /// Number of spikes of a node at or after `since`
+    pub fn count_since(&self, node_id: &Uuid, since: DateTime<Utc>) -> usize {
+        self.by_node.get(node_id).map_or(0, |spikes| {
+            spikes.len() - spikes.partition_point(|spike| spike.timestamp < since)
+        })
+    }
+    
+    /// All spikes between `from` and `to` (inclusive), in time order
+    pub fn between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<SpikeEvent> {
+        let mut spikes: Vec<SpikeEvent> = self
+            .by_node
+            .values()
+            .flat_map(|spikes| spikes.range(Self::range(spikes, from, to)))
+            .cloned()
+            .collect();
+        spikes.sort_by_key(|spike| spike.timestamp);
+        spikes
+    }
+    
+    /// Drop spikes at or before `cutoff`, returning how many were dropped
+    pub fn prune(&mut self, cutoff: DateTime<Utc>) -> usize {
+        let mut pruned = 0;
+        for spikes in self.by_node.values_mut() {
+            let expired = spikes.partition_point(|spike| spike.timestamp <= cutoff);
+            spikes.drain(..expired);
+            pruned += expired;
+        }
+        self.by_node.retain(|_, spikes| !spikes.is_empty());
+        self.len -= pruned;
+        pruned
+    }
+    
+    /// Drop the history of a node
+    pub fn remove_node(&mut self, node_id: &Uuid) {
+        if let Some(spikes) = self.by_node.remove(node_id) {
+            self.len -= spikes.len();
+        }
+    }
+    
+    /// Keep only the history of nodes for which `keep` returns true
+    pub fn retain_nodes(&mut self, mut keep: impl FnMut(&Uuid) -> bool) {
+        let len = &mut self.len;
+        self.by_node.retain(|node_id, spikes| {
+            let kept = keep(node_id);
+            if !kept {
+                *len -= spikes.len();
+            }
+            kept
+        });
+    }
+    
+    fn range(spikes: &VecDeque<SpikeEvent>, from: DateTime<Utc>, to: DateTime<Utc>) -> std::ops::Range<usize> {
+        let start = spikes.partition_point(|spike| spike.timestamp < from);
+        let end = spikes.partition_point(|spike| spike.timestamp <= to);
+        start..end.max(start)
+    }
+}
+
+impl From<Vec<SpikeEvent>> for SpikeHistory {
+    /// Unbounded until a capacity is set
+    fn from(spikes: Vec<SpikeEvent>) -> Self {
+        let mut history = Self::new(usize::MAX);
+        for spike in spikes {
+            history.push(spike);
+        }
+        history
+    }
+}
+
+impl From<SpikeHistory> for Vec<SpikeEvent> {
+    fn from(history: SpikeHistory) -> Self {
+        let mut spikes: Vec<SpikeEvent> = history.by_node.into_values().flatten().collect();
+        spikes.sort_by_key(|spike| spike.timestamp);
+        spikes
+    }
+}
+
+/// Membrane states recorded after each input and reset, by node
+///
+/// Between records a membrane evolves freely under its model, so the state
+/// at any time follows from the latest record before it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MembraneHistory {
+    by_node: HashMap<Uuid, VecDeque<MembraneState>>,
+    /// Records kept per node, unbounded until set
+    #[serde(skip)]
+    capacity: Option<usize>,
+}
+
+impl MembraneHistory {
+    /// Change the per-node capacity, evicting the oldest records past it
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = Some(capacity);
+        for states in self.by_node.values_mut() {
+            let excess = states.len().saturating_sub(capacity);
+            states.drain(..excess);
+        }
+        self.by_node.retain(|_, states| !states.is_empty());
+    }
+    
+    /// Record a node's membrane as of its `updated_at`
+    pub fn push(&mut self, node_id: Uuid, membrane: MembraneState) {
+        let capacity = self.capacity.unwrap_or(usize::MAX);
+        if capacity == 0 {
+            return;
+        }
+        
+        let states = self.by_node.entry(node_id).or_default();
+        let index = states.partition_point(|earlier| earlier.updated_at <= membrane.updated_at);
+        states.insert(index, membrane);
+        if states.len() > capacity {
+            states.pop_front();
+        }
+    }
+    
+    /// Potentials of a node sampled every `interval` ms from `from`
+    ///
+    /// Before its first record a node is taken to be at rest.
+    pub fn trace(
+        &self,
+        node_id: &Uuid,
+        model: &dyn NeuronModel,
+        from: DateTime<Utc>,
+        interval: f64,
+        samples: usize,
+    ) -> Vec<f64> {
+        let empty = VecDeque::new();
+        let states = self.by_node.get(node_id).unwrap_or(&empty);
+        let mut next = states.partition_point(|state| state.updated_at <= from);
+        let mut current = match next {
+            0 => model.resting_state(from),
+            _ => states[next - 1],
+        };
+        
+        (0..samples)
+            .map(|sample| {
+                let at = from + chrono::Duration::microseconds((sample as f64 * interval * 1000.0) as i64);
+                while next < states.len() && states[next].updated_at <= at {
+                    current = states[next];
+                    next += 1;
+                }
+                let elapsed = (at - current.updated_at).num_microseconds().unwrap_or(0) as f64 / 1000.0;
+                model.advance(&mut current, elapsed);
+                current.updated_at = at;
+                current.potential
+            })
+            .collect()
+    }
+    
+    /// Drop records at or before `cutoff`, keeping each node's latest one as
+    /// the starting point of its trace
+    pub fn prune(&mut self, cutoff: DateTime<Utc>) {
+        for states in self.by_node.values_mut() {
+            let expired = states.partition_point(|state| state.updated_at <= cutoff);
+            states.drain(..expired.saturating_sub(1));
+        }
+    }
+    
+    /// Drop the history of a node
+    pub fn remove_node(&mut self, node_id: &Uuid) {
+        self.by_node.remove(node_id);
+    }
+    
+    /// Keep only the history of nodes for which `keep` returns true
+    pub fn retain_nodes(&mut self, mut keep: impl FnMut(&Uuid) -> bool) {
+        self.by_node.retain(|node_id, _| keep(node_id));
+    }
+}
@@ -1,9 +1,11 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use uuid::Uuid;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use anyhow::{Result, anyhow};
 
+use crate::index::{NodeFilter, NodeIndexes, PropertyQuery};
+
 /// A hypergraph where nodes can be connected by hyperedges
 /// that link multiple nodes simultaneously (not just pairs)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,12 +16,20 @@ pub struct HyperGraph {
     // Adjacency information for efficient traversal
     node_to_edges: HashMap<Uuid, HashSet<Uuid>>,
     edge_to_nodes: HashMap<Uuid, Vec<Uuid>>,
+    
+    // Property keys with a declared secondary index
+    indexed_properties: BTreeSet<String>,
+    
+    // Secondary indexes, rebuilt from the nodes on load
+    #[serde(skip)]
+    indexes: NodeIndexes,
 }
 
 /// A node in the hypergraph representing an entity (person, goal, resource)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HyperNode {
     pub id: Uuid,
+    #[serde(with = "json_compat")]
     pub data: serde_json::Value,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -32,10 +42,19 @@ pub struct HyperNode {
     // Metadata
     pub node_type: String,
     pub tags: Vec<String>,
+    #[serde(with = "json_compat")]
     pub properties: HashMap<String, serde_json::Value>,
 }
 
 /// A hyperedge connecting multiple nodes with a relationship
+///
+/// The sign of the conductance is the edge's polarity. An edge created with
+/// a negative strength starts out with a negative conductance and is
+/// inhibitory: spikes travelling along it lower the potential of their
+/// targets instead of raising it. Learning and decay only scale the
+/// magnitude, so the polarity is fixed at creation, even once the magnitude
+/// reaches zero (stored as `-0.0`); updating `strength` later doesn't
+/// change it.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HyperEdge {
     pub id: Uuid,
@@ -49,25 +68,264 @@ pub struct HyperEdge {
     pub conductance: f64,
     pub last_activation: Option<DateTime<Utc>>,
     pub activation_count: u64,
+    #[serde(default)]
+    pub delay: EdgeDelay,
     
     // Metadata
     pub edge_type: EdgeType,
     pub weight_decay: f64,
+    #[serde(with = "json_compat")]
     pub properties: HashMap<String, serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum EdgeType {
     /// Bidirectional relationship (mutual benefit)
+    #[default]
     Symmetric,
     /// Directional relationship (one-way influence)
     Directional { from: Uuid, to: Vec<Uuid> },
     /// Hub relationship (one central node, many peripheral)
     Hub { center: Uuid, periphery: Vec<Uuid> },
-    /// Chain relationship (sequential dependencies)
+    /// Chain relationship (sequential dependencies, in `node_ids` order)
     Chain,
 }
 
+/// How long a spike takes to cross a hyperedge
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeDelay {
+    /// The default synaptic delay of the simulation
+    #[default]
+    Default,
+    /// A fixed delay (ms)
+    Fixed(f64),
+    /// A numeric edge property times `scale` (ms); the default delay while
+    /// the property is missing
+    Property { key: String, scale: f64 },
+    /// `base + per_day × age in days`, kept within `[min, max]` (ms); a
+    /// negative `per_day` lets established relationships react faster
+    Age { base: f64, per_day: f64, min: f64, max: f64 },
+}
+
+impl EdgeDelay {
+    pub(crate) fn validate(&self) -> Result<()> {
+        let valid = match self {
+            EdgeDelay::Default => true,
+            EdgeDelay::Fixed(delay) => delay.is_finite() && *delay >= 0.0,
+            EdgeDelay::Property { scale, .. } => scale.is_finite() && *scale >= 0.0,
+            EdgeDelay::Age { base, per_day, min, max } => {
+                base.is_finite() && per_day.is_finite() && *min >= 0.0 && min <= max
+            }
+        };
+        if valid { Ok(()) } else { Err(anyhow!("Invalid edge delay {:?}", self)) }
+    }
+}
+
+impl EdgeType {
+    /// Check that the anchors of the edge type are members of the edge
+    pub(crate) fn validate(&self, node_ids: &[Uuid]) -> Result<()> {
+        let (anchor, others) = match self {
+            EdgeType::Symmetric | EdgeType::Chain => return Ok(()),
+            EdgeType::Directional { from, to } => (from, to),
+            EdgeType::Hub { center, periphery } => (center, periphery),
+        };
+        
+        if others.is_empty() {
+            return Err(anyhow!("Edge type needs at least one target"));
+        }
+        if others.contains(anchor) {
+            return Err(anyhow!("Node {} can't be both anchor and target", anchor));
+        }
+        for node_id in std::iter::once(anchor).chain(others) {
+            if !node_ids.contains(node_id) {
+                return Err(anyhow!("Node {} is not a member of the edge", node_id));
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+impl HyperEdge {
+    /// Whether spikes along this edge suppress their targets
+    pub fn is_inhibitory(&self) -> bool {
+        self.conductance.is_sign_negative()
+    }
+    
+    /// Conductance after a learned change to its magnitude, within bounds
+    ///
+    /// Learning strengthens or weakens an edge without flipping its sign, so
+    /// an inhibitory edge stays inhibitory, down to a magnitude of zero. Like
+    /// `f64::clamp`, this panics unless `min <= max`.
+    pub fn adjusted_conductance(&self, change: f64, min: f64, max: f64) -> f64 {
+        (self.conductance.abs() + change).clamp(min, max).copysign(self.conductance)
+    }
+    
+    /// Transmission delay at a given time (ms), `default` unless the edge
+    /// has its own
+    pub fn delay_at(&self, now: DateTime<Utc>, default: f64) -> f64 {
+        let delay = match &self.delay {
+            EdgeDelay::Default => default,
+            EdgeDelay::Fixed(delay) => *delay,
+            EdgeDelay::Property { key, scale } => self
+                .properties
+                .get(key)
+                .and_then(|value| value.as_f64())
+                .map_or(default, |value| value * scale),
+            EdgeDelay::Age { base, per_day, min, max } => {
+                let days = (now - self.created_at).num_seconds().max(0) as f64 / 86_400.0;
+                (base + per_day * days).clamp(*min, *max)
+            }
+        };
+        if delay.is_finite() { delay.max(0.0) } else { default }
+    }
+    
+    /// Nodes a signal at `node_id` can reach through this edge
+    ///
+    /// Symmetric edges reach every other member, directional edges only
+    /// flow from `from` to `to`, hub edges route through the center, and
+    /// chain edges reach the next member in sequence.
+    pub fn targets_from(&self, node_id: &Uuid) -> Vec<Uuid> {
+        match &self.edge_type {
+            EdgeType::Symmetric => self
+                .node_ids
+                .iter()
+                .filter(|id| *id != node_id)
+                .copied()
+                .collect(),
+            EdgeType::Directional { from, to } => {
+                if from == node_id { to.clone() } else { Vec::new() }
+            }
+            EdgeType::Hub { center, periphery } => {
+                if center == node_id {
+                    periphery.clone()
+                } else if periphery.contains(node_id) {
+                    vec![*center]
+                } else {
+                    Vec::new()
+                }
+            }
+            EdgeType::Chain => self
+                .node_ids
+                .iter()
+                .position(|id| id == node_id)
+                .and_then(|index| self.node_ids.get(index + 1))
+                .copied()
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+/// Node type assigned when none is given
+pub const DEFAULT_NODE_TYPE: &str = "generic";
+
+/// Transmission delay of edges without their own (ms)
+pub const DEFAULT_SYNAPTIC_DELAY: f64 = 1.0;
+
+/// Activation at which a node counts as spiking unless configured otherwise
+pub const DEFAULT_SPIKE_THRESHOLD: f64 = 0.7;
+
+/// Type, tags and properties for a new node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeMetadata {
+    pub node_type: String,
+    pub tags: Vec<String>,
+    #[serde(with = "json_compat")]
+    pub properties: HashMap<String, serde_json::Value>,
+}
+
+impl Default for NodeMetadata {
+    fn default() -> Self {
+        Self {
+            node_type: DEFAULT_NODE_TYPE.to_string(),
+            tags: Vec::new(),
+            properties: HashMap::new(),
+        }
+    }
+}
+
+/// What happens to incident hyperedges when a node is removed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CascadePolicy {
+    /// Remove every hyperedge the node belongs to
+    #[default]
+    DropEdges,
+    /// Remove the node from its hyperedges, dropping those left with fewer than two members
+    ShrinkEdges,
+}
+
+/// Result of removing a node from the hypergraph
+#[derive(Debug, Clone)]
+pub struct NodeRemoval {
+    pub node: HyperNode,
+    pub removed_edges: Vec<Uuid>,
+    pub shrunk_edges: Vec<Uuid>,
+}
+
+/// Partial update for a node; `None` fields are left unchanged
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NodeUpdate {
+    #[serde(default, with = "json_compat")]
+    pub data: Option<serde_json::Value>,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    /// Merged into the existing properties; a `null` value removes the key
+    #[serde(default, with = "json_compat")]
+    pub properties: Option<HashMap<String, serde_json::Value>>,
+}
+
+/// Partial update for a hyperedge; `None` fields are left unchanged
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EdgeUpdate {
+    #[serde(default)]
+    pub relationship: Option<String>,
+    /// Declared strength; the learned conductance is left untouched
+    #[serde(default)]
+    pub strength: Option<f64>,
+    /// Merged into the existing properties; a `null` value removes the key
+    #[serde(default, with = "json_compat")]
+    pub properties: Option<HashMap<String, serde_json::Value>>,
+}
+
+/// Serde adapter for JSON-typed fields
+///
+/// `serde_json::Value` can only be deserialized through `deserialize_any`,
+/// which compact binary formats like bincode don't support. For those
+/// formats the value is stored as its JSON text instead; human-readable
+/// formats keep the native representation.
+pub(crate) mod json_compat {
+    use serde::de::{DeserializeOwned, Error};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    
+    pub fn serialize<T: Serialize, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            value.serialize(serializer)
+        } else {
+            let text = serde_json::to_string(value).map_err(serde::ser::Error::custom)?;
+            serializer.serialize_str(&text)
+        }
+    }
+    
+    pub fn deserialize<'de, T: DeserializeOwned, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        if deserializer.is_human_readable() {
+            T::deserialize(deserializer)
+        } else {
+            let text = String::deserialize(deserializer)?;
+            serde_json::from_str(&text).map_err(D::Error::custom)
+        }
+    }
+}
+
+impl Default for HyperGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl HyperGraph {
     pub fn new() -> Self {
         Self {
@@ -75,13 +333,34 @@ impl HyperGraph {
             edges: HashMap::new(),
             node_to_edges: HashMap::new(),
             edge_to_nodes: HashMap::new(),
+            indexed_properties: BTreeSet::new(),
+            indexes: NodeIndexes::default(),
         }
     }
     
     /// Add a new node to the hypergraph
     pub fn add_node(&mut self, id: Uuid, data: serde_json::Value) -> Result<()> {
-        let now = Utc::now();
-        
+        self.add_node_with_metadata(id, data, NodeMetadata::default())
+    }
+    
+    /// Add a new node with its type, tags and properties
+    pub fn add_node_with_metadata(
+        &mut self,
+        id: Uuid,
+        data: serde_json::Value,
+        metadata: NodeMetadata,
+    ) -> Result<()> {
+        self.add_node_with_metadata_at(id, data, metadata, Utc::now())
+    }
+    
+    /// Add a new node created at a given time
+    pub fn add_node_with_metadata_at(
+        &mut self,
+        id: Uuid,
+        data: serde_json::Value,
+        metadata: NodeMetadata,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
         let node = HyperNode {
             id,
             data,
@@ -90,24 +369,54 @@ impl HyperGraph {
             activation_level: 0.0,
             last_spike_time: None,
             spike_count: 0,
-            node_type: "generic".to_string(),
-            tags: Vec::new(),
-            properties: HashMap::new(),
+            node_type: metadata.node_type,
+            tags: metadata.tags,
+            properties: metadata.properties,
         };
         
+        if let Some(previous) = self.nodes.get(&id) {
+            self.indexes.remove(previous);
+        }
+        self.indexes.insert(&node);
+        
         self.nodes.insert(id, node);
         self.node_to_edges.insert(id, HashSet::new());
         
         Ok(())
     }
     
-    /// Add a hyperedge connecting multiple nodes
+    /// Add a symmetric hyperedge connecting multiple nodes
     pub fn add_hyperedge(
         &mut self,
         id: Uuid,
         node_ids: Vec<Uuid>,
         relationship: String,
         strength: f64,
+    ) -> Result<()> {
+        self.add_typed_hyperedge(id, node_ids, relationship, strength, EdgeType::Symmetric)
+    }
+    
+    /// Add a hyperedge with the given propagation semantics
+    pub fn add_typed_hyperedge(
+        &mut self,
+        id: Uuid,
+        node_ids: Vec<Uuid>,
+        relationship: String,
+        strength: f64,
+        edge_type: EdgeType,
+    ) -> Result<()> {
+        self.add_typed_hyperedge_at(id, node_ids, relationship, strength, edge_type, Utc::now())
+    }
+    
+    /// Add a typed hyperedge created at a given time
+    pub fn add_typed_hyperedge_at(
+        &mut self,
+        id: Uuid,
+        node_ids: Vec<Uuid>,
+        relationship: String,
+        strength: f64,
+        edge_type: EdgeType,
+        now: DateTime<Utc>,
     ) -> Result<()> {
         // Validate all nodes exist
         for node_id in &node_ids {
@@ -115,8 +424,7 @@ impl HyperGraph {
                 return Err(anyhow!("Node {} not found", node_id));
             }
         }
-        
-        let now = Utc::now();
+        edge_type.validate(&node_ids)?;
         
         let edge = HyperEdge {
             id,
@@ -128,7 +436,8 @@ impl HyperGraph {
             conductance: strength,
             last_activation: None,
             activation_count: 0,
-            edge_type: EdgeType::Symmetric,
+            delay: EdgeDelay::Default,
+            edge_type,
             weight_decay: 0.99,
             properties: HashMap::new(),
         };
@@ -140,19 +449,153 @@ impl HyperGraph {
         for node_id in node_ids {
             self.node_to_edges
                 .entry(node_id)
-                .or_insert_with(HashSet::new)
+                .or_default()
                 .insert(id);
         }
         
         Ok(())
     }
     
+    /// Remove a node, handling its hyperedges according to `policy`
+    pub fn remove_node(&mut self, id: &Uuid, policy: CascadePolicy) -> Result<NodeRemoval> {
+        self.remove_node_at(id, policy, Utc::now())
+    }
+    
+    /// Remove a node at a given time, which shrunk edges record as their update
+    pub fn remove_node_at(&mut self, id: &Uuid, policy: CascadePolicy, now: DateTime<Utc>) -> Result<NodeRemoval> {
+        let node = self.nodes.remove(id).ok_or_else(|| anyhow!("Node {} not found", id))?;
+        self.indexes.remove(&node);
+        let edge_ids = self.node_to_edges.remove(id).unwrap_or_default();
+        
+        let mut removed_edges = Vec::new();
+        let mut shrunk_edges = Vec::new();
+        
+        for edge_id in edge_ids {
+            let keep = match policy {
+                CascadePolicy::DropEdges => false,
+                CascadePolicy::ShrinkEdges => self.shrink_edge(&edge_id, id, now),
+            };
+            
+            if keep {
+                shrunk_edges.push(edge_id);
+            } else {
+                self.detach_edge(&edge_id);
+                removed_edges.push(edge_id);
+            }
+        }
+        
+        Ok(NodeRemoval {
+            node,
+            removed_edges,
+            shrunk_edges,
+        })
+    }
+    
+    /// Remove a hyperedge
+    pub fn remove_hyperedge(&mut self, id: &Uuid) -> Result<HyperEdge> {
+        self.detach_edge(id).ok_or_else(|| anyhow!("Edge {} not found", id))
+    }
+    
+    /// Apply a partial update to a node
+    pub fn update_node(&mut self, id: &Uuid, update: NodeUpdate) -> Result<()> {
+        self.update_node_at(id, update, Utc::now())
+    }
+    
+    /// Apply a partial update to a node at a given time
+    pub fn update_node_at(&mut self, id: &Uuid, update: NodeUpdate, now: DateTime<Utc>) -> Result<()> {
+        let node = self.nodes.get_mut(id).ok_or_else(|| anyhow!("Node {} not found", id))?;
+        self.indexes.remove(node);
+        
+        if let Some(data) = update.data {
+            node.data = data;
+        }
+        if let Some(tags) = update.tags {
+            node.tags = tags;
+        }
+        if let Some(properties) = update.properties {
+            merge_properties(&mut node.properties, properties);
+        }
+        
+        node.updated_at = now;
+        self.indexes.insert(node);
+        Ok(())
+    }
+    
+    /// Change the type of a node
+    pub fn set_node_type(&mut self, id: &Uuid, node_type: String) -> Result<()> {
+        self.set_node_type_at(id, node_type, Utc::now())
+    }
+    
+    /// Change the type of a node at a given time
+    pub fn set_node_type_at(&mut self, id: &Uuid, node_type: String, now: DateTime<Utc>) -> Result<()> {
+        let node = self.nodes.get_mut(id).ok_or_else(|| anyhow!("Node {} not found", id))?;
+        self.indexes.remove(node);
+        node.node_type = node_type;
+        node.updated_at = now;
+        self.indexes.insert(node);
+        Ok(())
+    }
+    
+    /// Add and remove tags on a node, keeping the order of existing tags
+    pub fn edit_node_tags(&mut self, id: &Uuid, add: &[String], remove: &[String]) -> Result<()> {
+        self.edit_node_tags_at(id, add, remove, Utc::now())
+    }
+    
+    /// Add and remove tags on a node at a given time
+    pub fn edit_node_tags_at(
+        &mut self,
+        id: &Uuid,
+        add: &[String],
+        remove: &[String],
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        let node = self.nodes.get_mut(id).ok_or_else(|| anyhow!("Node {} not found", id))?;
+        self.indexes.remove(node);
+        
+        node.tags.retain(|tag| !remove.contains(tag));
+        for tag in add {
+            if !node.tags.contains(tag) {
+                node.tags.push(tag.clone());
+            }
+        }
+        
+        node.updated_at = now;
+        self.indexes.insert(node);
+        Ok(())
+    }
+    
+    /// Apply a partial update to a hyperedge
+    pub fn update_hyperedge(&mut self, id: &Uuid, update: EdgeUpdate) -> Result<()> {
+        self.update_hyperedge_at(id, update, Utc::now())
+    }
+    
+    /// Apply a partial update to a hyperedge at a given time
+    pub fn update_hyperedge_at(&mut self, id: &Uuid, update: EdgeUpdate, now: DateTime<Utc>) -> Result<()> {
+        let edge = self.edges.get_mut(id).ok_or_else(|| anyhow!("Edge {} not found", id))?;
+        
+        if let Some(relationship) = update.relationship {
+            edge.relationship = relationship;
+        }
+        if let Some(strength) = update.strength {
+            edge.strength = strength;
+        }
+        if let Some(properties) = update.properties {
+            merge_properties(&mut edge.properties, properties);
+        }
+        
+        edge.updated_at = now;
+        Ok(())
+    }
+    
     /// Get a node by ID
     pub fn get_node(&self, id: &Uuid) -> Option<&HyperNode> {
         self.nodes.get(id)
     }
     
     /// Get a mutable node by ID
+    ///
+    /// Changes made here to `node_type`, `tags` or `properties` bypass the
+    /// secondary indexes; call `rebuild_indexes` afterwards.
     pub fn get_node_mut(&mut self, id: &Uuid) -> Option<&mut HyperNode> {
         self.nodes.get_mut(id)
     }
@@ -179,46 +622,115 @@ impl HyperGraph {
         }
     }
     
-    /// Get all nodes connected to a node through any edge
+    /// Get all nodes a node can reach in one hop, respecting edge types
     pub fn get_neighbors(&self, node_id: &Uuid) -> Vec<&HyperNode> {
-        let mut neighbors = HashSet::new();
+        self.get_outgoing(node_id)
+            .into_keys()
+            .filter_map(|id| self.nodes.get(&id))
+            .collect()
+    }
+    
+    /// Nodes a node can reach in one hop, each with the edges leading there
+    pub fn get_outgoing(&self, node_id: &Uuid) -> HashMap<Uuid, Vec<&HyperEdge>> {
+        let mut outgoing: HashMap<Uuid, Vec<&HyperEdge>> = HashMap::new();
         
-        if let Some(edge_ids) = self.node_to_edges.get(node_id) {
-            for edge_id in edge_ids {
-                if let Some(connected_nodes) = self.edge_to_nodes.get(edge_id) {
-                    for connected_id in connected_nodes {
-                        if connected_id != node_id {
-                            neighbors.insert(connected_id);
-                        }
-                    }
-                }
+        for edge in self.get_node_edges(node_id) {
+            for target in edge.targets_from(node_id) {
+                outgoing.entry(target).or_default().push(edge);
             }
         }
         
-        neighbors
-            .into_iter()
-            .filter_map(|id| self.nodes.get(id))
-            .collect()
+        outgoing
     }
     
     /// Find nodes by properties
     pub fn find_nodes_by_property(&self, key: &str, value: &serde_json::Value) -> Vec<&HyperNode> {
-        self.nodes
-            .values()
-            .filter(|node| {
-                node.properties.get(key).map_or(false, |v| v == value)
-            })
+        self.query_nodes(key, &PropertyQuery::Equals(value.clone()))
+    }
+    
+    /// Find nodes whose property matches a query (uses the index if one is declared)
+    pub fn query_nodes(&self, key: &str, query: &PropertyQuery) -> Vec<&HyperNode> {
+        match self.indexes.query_property(key, query) {
+            Some(ids) => ids.iter().filter_map(|id| self.nodes.get(id)).collect(),
+            None => self
+                .nodes
+                .values()
+                .filter(|node| node.properties.get(key).is_some_and(|value| query.matches(value)))
+                .collect(),
+        }
+    }
+    
+    /// Find nodes of a given type
+    pub fn find_nodes_by_type(&self, node_type: &str) -> Vec<&HyperNode> {
+        self.indexes
+            .nodes_of_type(node_type)
+            .iter()
+            .filter_map(|id| self.nodes.get(id))
             .collect()
     }
     
-    /// Update node activation level
+    /// Find nodes carrying a given tag
+    pub fn find_nodes_by_tag(&self, tag: &str) -> Vec<&HyperNode> {
+        self.indexes
+            .nodes_with_tag(tag)
+            .iter()
+            .filter_map(|id| self.nodes.get(id))
+            .collect()
+    }
+    
+    /// Find nodes matching every condition of a filter
+    pub fn filter_nodes(&self, filter: &NodeFilter) -> Vec<&HyperNode> {
+        match self.indexes.candidates(filter) {
+            Some(ids) => ids
+                .iter()
+                .filter_map(|id| self.nodes.get(id))
+                .filter(|node| filter.matches(node))
+                .collect(),
+            None => self.nodes.values().filter(|node| filter.matches(node)).collect(),
+        }
+    }
+    
+    /// Declare a secondary index on a property key
+    pub fn create_property_index(&mut self, key: &str) {
+        if self.indexed_properties.insert(key.to_string()) {
+            self.indexes.add_property_index(key, self.nodes.values());
+        }
+    }
+    
+    /// Remove a declared property index
+    pub fn drop_property_index(&mut self, key: &str) {
+        self.indexed_properties.remove(key);
+        self.indexes.drop_property_index(key);
+    }
+    
+    /// Property keys with a declared index
+    pub fn indexed_properties(&self) -> &BTreeSet<String> {
+        &self.indexed_properties
+    }
+    
+    /// Rebuild all secondary indexes from the current nodes
+    pub fn rebuild_indexes(&mut self) {
+        self.indexes = NodeIndexes::build(self.nodes.values(), &self.indexed_properties);
+    }
+    
+    /// Update node activation level, counting a spike above the default threshold
     pub fn update_node_activation(&mut self, node_id: &Uuid, activation: f64) -> Result<()> {
+        self.update_node_activation_with_threshold(node_id, activation, DEFAULT_SPIKE_THRESHOLD)
+    }
+    
+    /// Update node activation level, counting a spike above `threshold`
+    pub fn update_node_activation_with_threshold(
+        &mut self,
+        node_id: &Uuid,
+        activation: f64,
+        threshold: f64,
+    ) -> Result<()> {
         if let Some(node) = self.nodes.get_mut(node_id) {
             node.activation_level = activation;
             node.updated_at = Utc::now();
             
             // Record spike if above threshold
-            if activation > 0.7 {
+            if activation > threshold {
                 node.last_spike_time = Some(Utc::now());
                 node.spike_count += 1;
             }
@@ -242,6 +754,43 @@ impl HyperGraph {
         }
     }
     
+    /// Shift the magnitude of an edge's conductance by a learned change,
+    /// keeping it within bounds
+    ///
+    /// Returns the new conductance.
+    pub fn adjust_conductance(&mut self, edge_id: &Uuid, change: f64, min: f64, max: f64) -> Result<f64> {
+        self.adjust_conductance_at(edge_id, change, min, max, Utc::now())
+    }
+    
+    /// Shift an edge's conductance at a given time
+    pub fn adjust_conductance_at(
+        &mut self,
+        edge_id: &Uuid,
+        change: f64,
+        min: f64,
+        max: f64,
+        now: DateTime<Utc>,
+    ) -> Result<f64> {
+        let edge = self.edges.get_mut(edge_id).ok_or_else(|| anyhow!("Edge {} not found", edge_id))?;
+        edge.conductance = edge.adjusted_conductance(change, min, max);
+        edge.updated_at = now;
+        Ok(edge.conductance)
+    }
+    
+    /// Set how long spikes take to cross a hyperedge
+    pub fn set_edge_delay(&mut self, edge_id: &Uuid, delay: EdgeDelay) -> Result<()> {
+        self.set_edge_delay_at(edge_id, delay, Utc::now())
+    }
+    
+    /// Set the delay of a hyperedge at a given time
+    pub fn set_edge_delay_at(&mut self, edge_id: &Uuid, delay: EdgeDelay, now: DateTime<Utc>) -> Result<()> {
+        delay.validate()?;
+        let edge = self.edges.get_mut(edge_id).ok_or_else(|| anyhow!("Edge {} not found", edge_id))?;
+        edge.delay = delay;
+        edge.updated_at = now;
+        Ok(())
+    }
+    
     /// Apply decay to all node activations and edge conductances
     pub fn apply_decay(&mut self, decay_rate: f64) {
         for node in self.nodes.values_mut() {
@@ -280,4 +829,73 @@ impl HyperGraph {
     pub fn edges(&self) -> &HashMap<Uuid, HyperEdge> {
         &self.edges
     }
+    
+    // Helper methods
+    
+    /// Remove an edge and all adjacency entries pointing at it
+    fn detach_edge(&mut self, edge_id: &Uuid) -> Option<HyperEdge> {
+        let edge = self.edges.remove(edge_id)?;
+        self.edge_to_nodes.remove(edge_id);
+        
+        for node_id in &edge.node_ids {
+            if let Some(edges) = self.node_to_edges.get_mut(node_id) {
+                edges.remove(edge_id);
+            }
+        }
+        
+        Some(edge)
+    }
+    
+    /// Take a node out of an edge; returns false if the edge should be dropped instead
+    fn shrink_edge(&mut self, edge_id: &Uuid, node_id: &Uuid, now: DateTime<Utc>) -> bool {
+        let Some(edge) = self.edges.get_mut(edge_id) else {
+            return false;
+        };
+        
+        edge.node_ids.retain(|id| id != node_id);
+        if edge.node_ids.len() < 2 {
+            return false;
+        }
+        
+        // Directional and hub edges lose their meaning without their anchor
+        match &mut edge.edge_type {
+            EdgeType::Symmetric | EdgeType::Chain => {}
+            EdgeType::Directional { from, to } => {
+                if from == node_id {
+                    return false;
+                }
+                to.retain(|id| id != node_id);
+                if to.is_empty() {
+                    return false;
+                }
+            }
+            EdgeType::Hub { center, periphery } => {
+                if center == node_id {
+                    return false;
+                }
+                periphery.retain(|id| id != node_id);
+                if periphery.is_empty() {
+                    return false;
+                }
+            }
+        }
+        
+        edge.updated_at = now;
+        self.edge_to_nodes.insert(*edge_id, edge.node_ids.clone());
+        true
+    }
+}
+
+/// Merge a property patch into a property map; `null` values remove keys
+fn merge_properties(
+    properties: &mut HashMap<String, serde_json::Value>,
+    patch: HashMap<String, serde_json::Value>,
+) {
+    for (key, value) in patch {
+        if value.is_null() {
+            properties.remove(&key);
+        } else {
+            properties.insert(key, value);
+        }
+    }
 }
\ No newline at end of file
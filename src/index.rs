@@ -0,0 +1,294 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::ops::Bound;
+use uuid::Uuid;
+
+use crate::hypergraph::HyperNode;
+
+/// Secondary indexes over node metadata
+///
+/// `node_type` and tags are always indexed. Property values are indexed
+/// only for keys that have been declared with `HyperGraph::create_property_index`.
+#[derive(Debug, Clone, Default)]
+pub struct NodeIndexes {
+    by_type: HashMap<String, HashSet<Uuid>>,
+    by_tag: HashMap<String, HashSet<Uuid>>,
+    properties: HashMap<String, PropertyIndex>,
+}
+
+/// Index over the values of a single property key
+#[derive(Debug, Clone, Default)]
+struct PropertyIndex {
+    /// Exact matches, keyed by the canonical JSON text of the value
+    by_value: HashMap<String, HashSet<Uuid>>,
+    /// Numeric values in order, for range lookups
+    by_number: BTreeMap<NumericKey, HashSet<Uuid>>,
+}
+
+/// Total ordering over `f64` so numbers can key a `BTreeMap`
+#[derive(Debug, Clone, Copy)]
+struct NumericKey(f64);
+
+impl PartialEq for NumericKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for NumericKey {}
+
+impl PartialOrd for NumericKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NumericKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl NumericKey {
+    /// Key for a number, with `-0.0` folded into `0.0` so the index agrees
+    /// with IEEE comparisons, which treat them as equal
+    fn new(number: f64) -> Self {
+        NumericKey(if number == 0.0 { 0.0 } else { number })
+    }
+}
+
+/// A lookup against a property value
+#[derive(Debug, Clone)]
+pub enum PropertyQuery {
+    /// Value equals the given one
+    Equals(serde_json::Value),
+    /// Value is one of the given ones
+    In(Vec<serde_json::Value>),
+    /// Numeric value within the inclusive bounds (`None` is unbounded)
+    Range { min: Option<f64>, max: Option<f64> },
+}
+
+impl PropertyQuery {
+    /// Check a single value against the query
+    pub fn matches(&self, value: &serde_json::Value) -> bool {
+        match self {
+            PropertyQuery::Equals(expected) => value == expected,
+            PropertyQuery::In(candidates) => candidates.contains(value),
+            PropertyQuery::Range { min, max } => value.as_f64().is_some_and(|number| {
+                min.is_none_or(|min| number >= min) && max.is_none_or(|max| number <= max)
+            }),
+        }
+    }
+}
+
+/// Conjunctive filter over node metadata
+#[derive(Debug, Clone, Default)]
+pub struct NodeFilter {
+    /// Node type must be one of these (empty means any type)
+    pub node_types: Vec<String>,
+    /// Node must carry all of these tags
+    pub tags: Vec<String>,
+    /// Every property condition must hold
+    pub properties: Vec<(String, PropertyQuery)>,
+}
+
+impl NodeFilter {
+    /// Check a single node against the filter
+    pub fn matches(&self, node: &HyperNode) -> bool {
+        (self.node_types.is_empty() || self.node_types.contains(&node.node_type))
+            && self.tags.iter().all(|tag| node.tags.contains(tag))
+            && self.properties.iter().all(|(key, query)| {
+                node.properties.get(key).is_some_and(|value| query.matches(value))
+            })
+    }
+}
+
+impl NodeIndexes {
+    /// Build indexes for a set of nodes and declared property keys
+    pub fn build<'a>(
+        nodes: impl IntoIterator<Item = &'a HyperNode>,
+        property_keys: &BTreeSet<String>,
+    ) -> Self {
+        let mut indexes = Self {
+            properties: property_keys
+                .iter()
+                .map(|key| (key.clone(), PropertyIndex::default()))
+                .collect(),
+            ..Default::default()
+        };
+        
+        for node in nodes {
+            indexes.insert(node);
+        }
+        
+        indexes
+    }
+    
+    /// Add a node's current metadata to the indexes
+    pub fn insert(&mut self, node: &HyperNode) {
+        self.by_type.entry(node.node_type.clone()).or_default().insert(node.id);
+        
+        for tag in &node.tags {
+            self.by_tag.entry(tag.clone()).or_default().insert(node.id);
+        }
+        
+        for (key, index) in self.properties.iter_mut() {
+            if let Some(value) = node.properties.get(key) {
+                index.insert(node.id, value);
+            }
+        }
+    }
+    
+    /// Remove a node's current metadata from the indexes
+    pub fn remove(&mut self, node: &HyperNode) {
+        remove_from(&mut self.by_type, &node.node_type, &node.id);
+        
+        for tag in &node.tags {
+            remove_from(&mut self.by_tag, tag, &node.id);
+        }
+        
+        for (key, index) in self.properties.iter_mut() {
+            if let Some(value) = node.properties.get(key) {
+                index.remove(node.id, value);
+            }
+        }
+    }
+    
+    /// Start indexing a property key, populating it from the given nodes
+    pub fn add_property_index<'a>(&mut self, key: &str, nodes: impl IntoIterator<Item = &'a HyperNode>) {
+        let mut index = PropertyIndex::default();
+        for node in nodes {
+            if let Some(value) = node.properties.get(key) {
+                index.insert(node.id, value);
+            }
+        }
+        self.properties.insert(key.to_string(), index);
+    }
+    
+    /// Stop indexing a property key
+    pub fn drop_property_index(&mut self, key: &str) {
+        self.properties.remove(key);
+    }
+    
+    /// Whether a property key is indexed
+    pub fn has_property_index(&self, key: &str) -> bool {
+        self.properties.contains_key(key)
+    }
+    
+    /// Nodes of a given type
+    pub fn nodes_of_type(&self, node_type: &str) -> HashSet<Uuid> {
+        self.by_type.get(node_type).cloned().unwrap_or_default()
+    }
+    
+    /// Nodes carrying a given tag
+    pub fn nodes_with_tag(&self, tag: &str) -> HashSet<Uuid> {
+        self.by_tag.get(tag).cloned().unwrap_or_default()
+    }
+    
+    /// Nodes whose indexed property matches the query, or `None` if the key isn't indexed
+    pub fn query_property(&self, key: &str, query: &PropertyQuery) -> Option<HashSet<Uuid>> {
+        let index = self.properties.get(key)?;
+        
+        let ids = match query {
+            PropertyQuery::Equals(value) => index.lookup(value),
+            PropertyQuery::In(values) => values.iter().flat_map(|value| index.lookup(value)).collect(),
+            PropertyQuery::Range { min, max } => {
+                // A NaN bound matches nothing, as in `PropertyQuery::matches`
+                if min.is_some_and(f64::is_nan) || max.is_some_and(f64::is_nan) {
+                    return Some(HashSet::new());
+                }
+                
+                let min = min.map(NumericKey::new);
+                let max = max.map(NumericKey::new);
+                
+                // An inverted range would make BTreeMap::range panic
+                if matches!((min, max), (Some(min), Some(max)) if min > max) {
+                    return Some(HashSet::new());
+                }
+                
+                let lower = min.map_or(Bound::Unbounded, Bound::Included);
+                let upper = max.map_or(Bound::Unbounded, Bound::Included);
+                
+                index
+                    .by_number
+                    .range((lower, upper))
+                    .flat_map(|(_, ids)| ids.iter().copied())
+                    .collect()
+            }
+        };
+        
+        Some(ids)
+    }
+    
+    /// Candidate nodes for a filter using the indexes, or `None` if no
+    /// condition could be answered from an index
+    ///
+    /// The result may be a superset of the matches; callers re-check each node.
+    pub fn candidates(&self, filter: &NodeFilter) -> Option<HashSet<Uuid>> {
+        let mut sets = Vec::new();
+        
+        if !filter.node_types.is_empty() {
+            let ids = filter
+                .node_types
+                .iter()
+                .flat_map(|node_type| self.nodes_of_type(node_type))
+                .collect();
+            sets.push(ids);
+        }
+        
+        for tag in &filter.tags {
+            sets.push(self.nodes_with_tag(tag));
+        }
+        
+        for (key, query) in &filter.properties {
+            if let Some(ids) = self.query_property(key, query) {
+                sets.push(ids);
+            }
+        }
+        
+        // Intersect starting from the most selective set
+        sets.sort_by_key(|ids| ids.len());
+        let mut sets = sets.into_iter();
+        let first = sets.next()?;
+        
+        Some(sets.fold(first, |acc, ids| acc.intersection(&ids).copied().collect()))
+    }
+}
+
+impl PropertyIndex {
+    fn insert(&mut self, node_id: Uuid, value: &serde_json::Value) {
+        self.by_value.entry(value.to_string()).or_default().insert(node_id);
+        
+        if let Some(number) = value.as_f64() {
+            self.by_number.entry(NumericKey::new(number)).or_default().insert(node_id);
+        }
+    }
+    
+    fn remove(&mut self, node_id: Uuid, value: &serde_json::Value) {
+        remove_from(&mut self.by_value, &value.to_string(), &node_id);
+        
+        if let Some(number) = value.as_f64() {
+            let key = NumericKey::new(number);
+            if let Some(ids) = self.by_number.get_mut(&key) {
+                ids.remove(&node_id);
+                if ids.is_empty() {
+                    self.by_number.remove(&key);
+                }
+            }
+        }
+    }
+    
+    fn lookup(&self, value: &serde_json::Value) -> HashSet<Uuid> {
+        self.by_value.get(&value.to_string()).cloned().unwrap_or_default()
+    }
+}
+
+/// Remove an id from a keyed set, dropping the key once it's empty
+fn remove_from(map: &mut HashMap<String, HashSet<Uuid>>, key: &str, id: &Uuid) {
+    if let Some(ids) = map.get_mut(key) {
+        ids.remove(id);
+        if ids.is_empty() {
+            map.remove(key);
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! Neurographite - Neuromorphic Spiking Hypergraph Database
+//!
+//! Combines hypergraph data structures with spiking neural network
+//! dynamics so that relationship strengths adapt over time.
+
+pub mod analysis;
+pub mod core;
+pub mod export;
+pub mod history;
+pub mod hypergraph;
+pub mod index;
+pub mod maintenance;
+pub mod neural;
+pub mod neuron;
+pub mod plasticity;
+pub mod simulation;
+pub mod storage;
+pub mod network;
+pub mod api;
+pub mod frontend;
+
+pub use crate::analysis::{
+    AnalysisConfig, BurstConfig, SpikeTrain, IsiDistribution, RatePoint, Correlogram, Burst, FiringPattern,
+    SpikeTrainSummary, SpikeTrainComparison,
+};
+pub use crate::core::{Database, DatabaseConfig, DatabaseStats};
+pub use crate::hypergraph::{
+    HyperNode, HyperEdge, HyperGraph, EdgeType, CascadePolicy, NodeRemoval, NodeUpdate, EdgeUpdate,
+    NodeMetadata, EdgeDelay,
+};
+pub use crate::export::{ExportFormat, RasterQuery, Raster, MembraneTraces};
+pub use crate::history::{SpikeHistory, SpikeRetention, MembraneHistory};
+pub use crate::index::{NodeFilter, PropertyQuery};
+pub use crate::maintenance::{MaintenanceConfig, MaintenanceReport};
+pub use crate::neural::{SpikeProcessor, NeuralState, SpikeEvent, NeuralStats};
+pub use crate::neuron::{
+    NeuronModel, LifParameters, IzhikevichParameters, AdExParameters, RateParameters, MembraneState, Projection,
+};
+pub use crate::plasticity::{
+    StdpConfig, ConductanceUpdate, RewardConfig, EligibilityTrace, OutcomeTarget, RewardUpdate,
+};
+pub use crate::simulation::{
+    Clock, SystemClock, VirtualClock, Simulator, SimulationConfig, SimulationResult, CascadeArrival, ArrivalPath,
+    FiringRule,
+};
+pub use crate::network::{
+    NetworkEffect, GoalAlignment, NetworkAnalyzer, MatchingConfig, PreferenceSource, Preferences, MatchedPair,
+    StableMatching, RoommatesOutcome, RoommatePair, RoommatesMatching, CapacitatedMatchingConfig, Capacity,
+    ProposedEdge, CapacitatedMatching, TeamRole, TeamConfig, TeamProposal,
+    CentralityConfig, CentralityMeasures,
+};
+pub use crate::storage::{StorageEngine, StorageStats};
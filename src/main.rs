@@ -1,14 +1,7 @@
-mod core;
-mod hypergraph;
-mod neural;
-mod storage;
-mod network;
-mod api;
-mod frontend;
-
 use std::error::Error;
 use tracing::{info, Level};
-use tracing_subscriber;
+
+use neurographite::api;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
@@ -19,8 +12,13 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     info!("🧠 Starting Neurographite - Neuromorphic Hypergraph Database");
     
-    // Initialize the database
-    let db = neurographite::Database::new("./data").await?;
+    // Initialize the database; a long-running server opts in to background maintenance
+    let config = neurographite::DatabaseConfig {
+        data_dir: "./data".to_string(),
+        maintenance: Some(neurographite::MaintenanceConfig::default()),
+        ..Default::default()
+    };
+    let db = neurographite::Database::with_config(config).await?;
     
     info!("🚀 Neurographite database initialized");
     
@@ -30,10 +28,3 @@ async fn main() -> Result<(), Box<dyn Error>> {
     
     Ok(())
 }
-
-pub mod neurographite {
-    pub use crate::core::Database;
-    pub use crate::hypergraph::{HyperNode, HyperEdge, HyperGraph};
-    pub use crate::neural::{SpikeProcessor, NeuralState};
-    pub use crate::network::{NetworkEffect, GoalAlignment};
-}
\ No newline at end of file
@@ -0,0 +1,143 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{watch, RwLock};
+use tokio::task::JoinHandle;
+use tokio::time::{Interval, MissedTickBehavior};
+use anyhow::Result;
+
+use crate::core::{checkpoint_state, log_mutation};
+use crate::hypergraph::HyperGraph;
+use crate::neural::SpikeProcessor;
+use crate::storage::{StorageEngine, WalEntry};
+
+/// Intervals of the background maintenance task
+///
+/// Checkpoints run every `DatabaseConfig::sync_interval` seconds, and
+/// pruning follows `DatabaseConfig::spike_retention`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaintenanceConfig {
+    /// Seconds between decays of activations and edge conductances
+    pub decay_interval: u64,
+    /// Seconds between prunings of spike history and refractory periods
+    pub prune_interval: u64,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            decay_interval: 60,
+            prune_interval: 300,
+        }
+    }
+}
+
+/// What a maintenance run did
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MaintenanceReport {
+    pub decayed: bool,
+    /// Spikes dropped from the history
+    pub pruned_spikes: usize,
+    /// Refractory periods that had run out
+    pub expired_refractory: usize,
+    pub checkpointed: bool,
+}
+
+/// Periodic upkeep of the graph and neural state
+#[derive(Clone)]
+pub(crate) struct Maintenance {
+    pub graph: Arc<RwLock<HyperGraph>>,
+    pub neural: Arc<SpikeProcessor>,
+    pub storage: Arc<StorageEngine>,
+    pub config: MaintenanceConfig,
+    pub decay_rate: f64,
+    pub checkpoint_interval: usize,
+    /// Seconds between checkpoints, 0 disables them
+    pub sync_interval: u64,
+}
+
+impl Maintenance {
+    /// Decay node activations, neural activations and edge conductances
+    ///
+    /// Graph decay goes through the write-ahead log like any other mutation,
+    /// once per call whether or not anything changed; checkpoints fold the
+    /// records into the snapshot.
+    pub async fn decay(&self) -> Result<()> {
+        let entry = WalEntry::ApplyDecay { decay_rate: self.decay_rate };
+        log_mutation(&self.graph, &self.neural, &self.storage, self.checkpoint_interval, entry).await?;
+        self.neural.decay_activations().await;
+        Ok(())
+    }
+    
+    /// Drop spikes past the retention age and expired refractory periods
+    pub async fn prune(&self) -> (usize, usize) {
+        self.neural.prune().await
+    }
+    
+    pub async fn checkpoint(&self) -> Result<()> {
+        checkpoint_state(&self.graph, &self.neural, &self.storage).await
+    }
+    
+    /// Run every task once
+    pub async fn run_once(&self) -> Result<MaintenanceReport> {
+        self.decay().await?;
+        let (pruned_spikes, expired_refractory) = self.prune().await;
+        self.checkpoint().await?;
+        
+        Ok(MaintenanceReport {
+            decayed: true,
+            pruned_spikes,
+            expired_refractory,
+            checkpointed: true,
+        })
+    }
+    
+    /// Run each task on its interval until `shutdown` turns true
+    pub fn spawn(self, mut shutdown: watch::Receiver<bool>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut decay = ticker(self.config.decay_interval);
+            let mut prune = ticker(self.config.prune_interval);
+            let mut checkpoint = ticker(self.sync_interval);
+            
+            loop {
+                tokio::select! {
+                    _ = shutdown.changed() => break,
+                    _ = tick(&mut decay) => {
+                        if let Err(e) = self.decay().await {
+                            tracing::error!("Scheduled decay failed: {}", e);
+                        }
+                    }
+                    _ = tick(&mut prune) => {
+                        let (spikes, refractory) = self.prune().await;
+                        tracing::debug!("Pruned {} spikes and {} refractory periods", spikes, refractory);
+                    }
+                    _ = tick(&mut checkpoint) => {
+                        if let Err(e) = self.checkpoint().await {
+                            tracing::error!("Scheduled checkpoint failed: {}", e);
+                        }
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// Interval for a task, or `None` if it is disabled (0 seconds)
+fn ticker(seconds: u64) -> Option<Interval> {
+    if seconds == 0 {
+        return None;
+    }
+    let period = Duration::from_secs(seconds);
+    let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    Some(ticker)
+}
+
+/// Wait for the next tick, or forever for a disabled task
+async fn tick(ticker: &mut Option<Interval>) {
+    match ticker {
+        Some(ticker) => {
+            ticker.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
@@ -1,15 +1,21 @@
-use std::collections::{HashMap, HashSet};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
 use uuid::Uuid;
 use serde::{Deserialize, Serialize};
+use chrono::Utc;
 use anyhow::Result;
+use rayon::prelude::*;
 
-use crate::hypergraph::{HyperGraph, HyperNode};
+use crate::hypergraph::{EdgeType, HyperEdge, HyperGraph, HyperNode, DEFAULT_SYNAPTIC_DELAY};
+use crate::index::NodeFilter;
+use crate::simulation::CascadeArrival;
 
 /// Network effect analysis for DeepRuin goal alignment
 #[derive(Debug, Clone)]
 pub struct NetworkEffect {
     pub source_node: Uuid,
-    pub affected_nodes: Vec<(Uuid, f64)>, // Node ID and effect strength
+    pub affected_nodes: Vec<(Uuid, f64)>, // Node ID and effect strength (negative when suppressed)
+    pub arrivals: Vec<CascadeArrival>, // When and by which path each affected node was reached
     pub total_effect: f64,
     pub cascade_depth: usize,
     pub effect_type: EffectType,
@@ -53,54 +59,464 @@ pub enum AlignmentType {
     Incompatible,
 }
 
+/// Scores closer than this are treated as ties in preference lists
+const TIE_TOLERANCE: f64 = 1e-9;
+
+/// Most candidate matchings the roommates fallback scores, each costing a
+/// full blocking pair count, before it settles for the best found so far
+const MAX_LOCAL_SEARCH_TRIES: usize = 1_000;
+
+/// Where match preferences come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreferenceSource {
+    /// Goal alignment from `NetworkAnalyzer::analyze_goal_alignment`
+    #[default]
+    Alignment,
+    /// Complementary activation levels and network positions
+    Complementarity,
+    /// Learned conductance of the hyperedges joining the pair
+    Conductance,
+}
+
+/// A two-sided matching problem
+#[derive(Debug, Clone, Default)]
+pub struct MatchingConfig {
+    /// Nodes that propose; a node on both sides only proposes
+    pub proposers: NodeFilter,
+    /// Nodes that accept or reject proposals
+    pub acceptors: NodeFilter,
+    pub source: PreferenceSource,
+    /// Candidates scoring below this are unacceptable
+    pub min_score: f64,
+}
+
+/// Ranked preferences of every participant in a matching
+///
+/// Candidates whose scores are equal share a tier; those a node doesn't
+/// rank at all are unacceptable to it.
+#[derive(Debug, Clone, Default)]
+pub struct Preferences {
+    scores: HashMap<Uuid, HashMap<Uuid, f64>>,
+    ranks: HashMap<Uuid, HashMap<Uuid, usize>>,
+    tiers: HashMap<Uuid, Vec<Vec<Uuid>>>,
+}
+
+impl Preferences {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Rank a node's acceptable candidates by score, best first
+    pub fn insert(&mut self, node_id: Uuid, mut scores: Vec<(Uuid, f64)>) {
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        
+        let mut tiers: Vec<Vec<Uuid>> = Vec::new();
+        let mut ranks = HashMap::new();
+        let mut tier_score = f64::NAN;
+        for (candidate, score) in &scores {
+            if tiers.is_empty() || (tier_score - score).abs() > TIE_TOLERANCE {
+                tiers.push(Vec::new());
+                tier_score = *score;
+            }
+            tiers.last_mut().expect("a tier was just pushed").push(*candidate);
+            ranks.insert(*candidate, tiers.len() - 1);
+        }
+        
+        self.scores.insert(node_id, scores.into_iter().collect());
+        self.ranks.insert(node_id, ranks);
+        self.tiers.insert(node_id, tiers);
+    }
+    
+    /// Acceptable candidates of a node in tiers, best first
+    pub fn tiers(&self, node_id: &Uuid) -> &[Vec<Uuid>] {
+        self.tiers.get(node_id).map_or(&[], Vec::as_slice)
+    }
+    
+    /// Tier of a candidate in a node's list, or `None` if unacceptable
+    pub fn rank(&self, node_id: &Uuid, candidate: &Uuid) -> Option<usize> {
+        self.ranks.get(node_id)?.get(candidate).copied()
+    }
+    
+    pub fn score(&self, node_id: &Uuid, candidate: &Uuid) -> Option<f64> {
+        self.scores.get(node_id)?.get(candidate).copied()
+    }
+    
+    /// Both nodes find each other acceptable
+    pub fn acceptable(&self, a: &Uuid, b: &Uuid) -> bool {
+        self.rank(a, b).is_some() && self.rank(b, a).is_some()
+    }
+    
+    /// The node strictly prefers `candidate` to its current partner (being
+    /// unmatched is worse than any acceptable candidate)
+    pub fn prefers(&self, node_id: &Uuid, candidate: &Uuid, current: Option<&Uuid>) -> bool {
+        let Some(rank) = self.rank(node_id, candidate) else {
+            return false;
+        };
+        current.and_then(|current| self.rank(node_id, current)).is_none_or(|current| rank < current)
+    }
+}
+
+/// A proposer and acceptor matched together
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchedPair {
+    pub proposer: Uuid,
+    pub acceptor: Uuid,
+    /// Score the proposer gave the acceptor
+    pub proposer_score: f64,
+    /// Score the acceptor gave the proposer
+    pub acceptor_score: f64,
+}
+
+/// Outcome of a stable matching
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StableMatching {
+    pub pairs: Vec<MatchedPair>,
+    /// Participants left without a partner
+    pub unmatched: Vec<Uuid>,
+    /// Pairs who would both rather be with each other than with their
+    /// partners; empty when the matching is stable
+    pub blocking_pairs: Vec<(Uuid, Uuid)>,
+}
+
+impl StableMatching {
+    pub fn is_stable(&self) -> bool {
+        self.blocking_pairs.is_empty()
+    }
+    
+    /// Partner of a participant, if matched
+    pub fn partner(&self, node_id: &Uuid) -> Option<Uuid> {
+        self.pairs.iter().find_map(|pair| {
+            if pair.proposer == *node_id {
+                Some(pair.acceptor)
+            } else if pair.acceptor == *node_id {
+                Some(pair.proposer)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// Whether a roommates matching could be made stable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoommatesOutcome {
+    Stable,
+    /// No stable matching exists; the pairs are the matching with the
+    /// fewest blocking pairs the fallback search found
+    NoStableMatching,
+}
+
+/// Two participants of a one-sided matching paired together
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoommatePair {
+    /// The lower id of the two
+    pub first: Uuid,
+    pub second: Uuid,
+    /// Score the first gave the second
+    pub first_score: f64,
+    /// Score the second gave the first
+    pub second_score: f64,
+}
+
+/// Outcome of a stable roommates matching
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoommatesMatching {
+    pub outcome: RoommatesOutcome,
+    pub pairs: Vec<RoommatePair>,
+    /// Participants left without a partner
+    pub unmatched: Vec<Uuid>,
+    /// Pairs who would both rather be with each other than with their
+    /// partners; empty when the matching is stable
+    pub blocking_pairs: Vec<(Uuid, Uuid)>,
+}
+
+impl RoommatesMatching {
+    pub fn is_stable(&self) -> bool {
+        self.outcome == RoommatesOutcome::Stable
+    }
+    
+    /// Partner of a participant, if matched
+    pub fn partner(&self, node_id: &Uuid) -> Option<Uuid> {
+        self.pairs.iter().find_map(|pair| {
+            if pair.first == *node_id {
+                Some(pair.second)
+            } else if pair.second == *node_id {
+                Some(pair.first)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// A many-to-one matching problem, hospitals/residents style
+#[derive(Debug, Clone)]
+pub struct CapacitatedMatchingConfig {
+    /// Sides, preference source and threshold; each proposer gets at most one
+    /// acceptor, and acceptors take several proposers
+    pub matching: MatchingConfig,
+    /// Node property holding an acceptor's capacity (1 when missing)
+    pub capacity_property: String,
+    /// Node property holding the fewest proposers an acceptor must get to be
+    /// matched at all
+    pub lower_quota_property: Option<String>,
+    /// Relationship of the proposed hyperedges
+    pub relationship: String,
+}
+
+impl Default for CapacitatedMatchingConfig {
+    fn default() -> Self {
+        Self {
+            matching: MatchingConfig::default(),
+            capacity_property: "capacity".to_string(),
+            lower_quota_property: None,
+            relationship: "matched".to_string(),
+        }
+    }
+}
+
+/// How many proposers an acceptor takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Capacity {
+    pub upper: usize,
+    /// Fewer than this and the acceptor is left out of the matching
+    pub lower: usize,
+}
+
+/// A hyperedge suggested by an analysis, not yet in the graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProposedEdge {
+    pub node_ids: Vec<Uuid>,
+    pub relationship: String,
+    pub strength: f64,
+    #[serde(default)]
+    pub edge_type: EdgeType,
+}
+
+/// Outcome of a capacitated stable matching
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CapacitatedMatching {
+    /// One hub hyperedge per matched acceptor, centered on the acceptor, its
+    /// members in the acceptor's order of preference
+    pub groups: Vec<ProposedEdge>,
+    /// Participants left without a match
+    pub unmatched: Vec<Uuid>,
+    /// Acceptors that could not reach their lower quota and were left out
+    pub closed: Vec<Uuid>,
+    /// Proposer–acceptor pairs who would both rather be matched together
+    pub blocking_pairs: Vec<(Uuid, Uuid)>,
+}
+
+impl CapacitatedMatching {
+    pub fn is_stable(&self) -> bool {
+        self.blocking_pairs.is_empty()
+    }
+    
+    /// Proposers matched to an acceptor
+    pub fn members(&self, acceptor: &Uuid) -> &[Uuid] {
+        self.groups
+            .iter()
+            .find(|group| group.node_ids.first() == Some(acceptor))
+            .map_or(&[], |group| &group.node_ids[1..])
+    }
+    
+    /// Acceptor a proposer was matched to
+    pub fn acceptor_of(&self, proposer: &Uuid) -> Option<Uuid> {
+        self.groups.iter().find(|group| group.node_ids[1..].contains(proposer)).map(|group| group.node_ids[0])
+    }
+}
+
+/// How many members of a node type a team may have
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TeamRole {
+    pub node_type: String,
+    #[serde(default)]
+    pub min: usize,
+    pub max: usize,
+}
+
+/// A team formation search
+#[derive(Debug, Clone)]
+pub struct TeamConfig {
+    /// Nodes that may join a team
+    pub candidates: NodeFilter,
+    /// Members per team
+    pub size: usize,
+    /// Limits per node type; types without a role are unconstrained
+    pub roles: Vec<TeamRole>,
+    /// Weight of mean pairwise goal alignment in a team's score
+    pub alignment_weight: f64,
+    /// Weight of mean pairwise complementarity in a team's score
+    pub complementarity_weight: f64,
+    /// Partial teams kept at each step of the beam search
+    pub beam_width: usize,
+    /// Most teams proposed
+    pub max_teams: usize,
+    /// Relationship of the proposed hyperedges
+    pub relationship: String,
+}
+
+impl Default for TeamConfig {
+    fn default() -> Self {
+        Self {
+            candidates: NodeFilter::default(),
+            size: 3,
+            roles: Vec::new(),
+            alignment_weight: 1.0,
+            complementarity_weight: 1.0,
+            beam_width: 20,
+            max_teams: 5,
+            relationship: "team".to_string(),
+        }
+    }
+}
+
+/// A proposed team, best-scoring first in search results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamProposal {
+    /// Weighted mean of alignment and complementarity
+    pub score: f64,
+    /// Mean goal alignment over member pairs
+    pub alignment: f64,
+    /// Mean complementarity over member pairs
+    pub complementarity: f64,
+    /// Symmetric hyperedge joining the members, with the score as strength
+    pub edge: ProposedEdge,
+}
+
+impl TeamProposal {
+    pub fn members(&self) -> &[Uuid] {
+        &self.edge.node_ids
+    }
+}
+
 /// Network analyzer for discovering relationships and effects
 pub struct NetworkAnalyzer;
 
+/// An effect on its way to a node during network effect analysis
+struct PendingEffect {
+    /// Accumulated transmission delay (ms)
+    time: f64,
+    seq: u64,
+    node_id: Uuid,
+    strength: f64,
+    depth: usize,
+    /// Node and edge it was sent from
+    from: Option<(Uuid, Uuid)>,
+}
+
+impl PartialEq for PendingEffect {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for PendingEffect {}
+
+impl PartialOrd for PendingEffect {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PendingEffect {
+    // Reversed so that `BinaryHeap` pops the earliest arrival first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .time
+            .total_cmp(&self.time)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
 impl NetworkAnalyzer {
     /// Analyze network effects from a node activation
+    ///
+    /// Effects spread in order of arrival time, so each node is reached by
+    /// the earliest path given the edges' transmission delays.
     pub fn analyze_network_effects(
         graph: &HyperGraph,
         source_node: Uuid,
         activation_strength: f64,
         max_depth: usize,
     ) -> Result<NetworkEffect> {
+        let now = Utc::now();
         let mut affected_nodes = Vec::new();
+        let mut arrivals = Vec::new();
         let mut visited = HashSet::new();
-        let mut queue = vec![(source_node, activation_strength, 0)];
+        // Node and edge each node was first reached from
+        let mut reached_from: HashMap<Uuid, (Uuid, Uuid)> = HashMap::new();
+        let mut queue = BinaryHeap::new();
+        queue.push(PendingEffect {
+            time: 0.0,
+            seq: 0,
+            node_id: source_node,
+            strength: activation_strength,
+            depth: 0,
+            from: None,
+        });
+        let mut seq = 0;
         let mut total_effect = 0.0;
         let mut max_cascade_depth = 0;
         
-        while let Some((current_node, current_strength, depth)) = queue.pop() {
+        while let Some(pending) = queue.pop() {
+            let PendingEffect { time, node_id: current_node, strength: current_strength, depth, from, .. } = pending;
             if depth >= max_depth || visited.contains(&current_node) {
                 continue;
             }
             
             visited.insert(current_node);
             max_cascade_depth = max_cascade_depth.max(depth);
+            if let Some(from) = from {
+                reached_from.insert(current_node, from);
+            }
             
             if current_node != source_node {
                 affected_nodes.push((current_node, current_strength));
                 total_effect += current_strength;
+                
+                let (path, edges) = Self::trace_path(&reached_from, current_node);
+                arrivals.push(CascadeArrival {
+                    node_id: current_node,
+                    effect: current_strength,
+                    arrival_time: time,
+                    path,
+                    edges,
+                });
+            }
+            
+            // Suppressed nodes don't pass anything on
+            if current_strength < 0.0 {
+                continue;
             }
             
-            // Propagate to neighbors
-            let neighbors = graph.get_neighbors(&current_node);
-            for neighbor in neighbors {
-                if !visited.contains(&neighbor.id) {
-                    let edges = graph.get_node_edges(&current_node);
-                    let mut propagated_strength: f64 = 0.0;
+            // Propagate along the edges leading out of this node
+            for (neighbor_id, edges) in graph.get_outgoing(&current_node) {
+                if !visited.contains(&neighbor_id) {
+                    let mut strongest: Option<(f64, &HyperEdge)> = None;
                     
-                    // Calculate propagation strength based on edge properties
+                    // The strongest edge wins, whether it excites or inhibits
                     for edge in edges {
-                        if edge.node_ids.contains(&neighbor.id) {
-                            propagated_strength = propagated_strength.max(
-                                current_strength * edge.conductance * 0.9 // 10% decay per hop
-                            );
+                        let contribution = current_strength * edge.conductance * 0.9; // 10% decay per hop
+                        if strongest.is_none_or(|(strength, _)| contribution.abs() > strength.abs()) {
+                            strongest = Some((contribution, edge));
                         }
                     }
                     
-                    if propagated_strength > 0.01 {
-                        queue.push((neighbor.id, propagated_strength, depth + 1));
+                    if let Some((propagated_strength, edge)) = strongest {
+                        if propagated_strength.abs() > 0.01 {
+                            seq += 1;
+                            queue.push(PendingEffect {
+                                time: time + edge.delay_at(now, DEFAULT_SYNAPTIC_DELAY),
+                                seq,
+                                node_id: neighbor_id,
+                                strength: propagated_strength,
+                                depth: depth + 1,
+                                from: Some((current_node, edge.id)),
+                            });
+                        }
                     }
                 }
             }
@@ -112,12 +528,28 @@ impl NetworkAnalyzer {
         Ok(NetworkEffect {
             source_node,
             affected_nodes,
+            arrivals,
             total_effect,
             cascade_depth: max_cascade_depth,
             effect_type,
         })
     }
     
+    /// Nodes and edges leading to `node_id` from the source of a search
+    fn trace_path(reached_from: &HashMap<Uuid, (Uuid, Uuid)>, node_id: Uuid) -> (Vec<Uuid>, Vec<Uuid>) {
+        let mut path = vec![node_id];
+        let mut edges = Vec::new();
+        let mut current = node_id;
+        while let Some((previous, edge_id)) = reached_from.get(&current) {
+            path.push(*previous);
+            edges.push(*edge_id);
+            current = *previous;
+        }
+        path.reverse();
+        edges.reverse();
+        (path, edges)
+    }
+    
     /// Analyze goal alignment between two nodes
     pub fn analyze_goal_alignment(
         graph: &HyperGraph,
@@ -164,82 +596,659 @@ impl NetworkAnalyzer {
         })
     }
     
-    /// Find optimal pairs using stable matching algorithm
+    /// Best aligned pairs between two groups, from a stable matching by goal
+    /// alignment; pairs aligning below 0.3 are never matched
     pub fn find_optimal_pairs(
         graph: &HyperGraph,
-        candidate_nodes: &[Uuid],
+        proposers: &[Uuid],
+        acceptors: &[Uuid],
         max_pairs: usize,
     ) -> Result<Vec<GoalAlignment>> {
-        let mut pairs = Vec::new();
-        let mut preferences: HashMap<Uuid, Vec<(Uuid, f64)>> = HashMap::new();
+        // A node on both sides only proposes
+        let acceptors: Vec<Uuid> = acceptors.iter().filter(|id| !proposers.contains(id)).copied().collect();
+        
+        let mut proposer_scores: HashMap<Uuid, Vec<(Uuid, f64)>> = HashMap::new();
+        let mut acceptor_scores: HashMap<Uuid, Vec<(Uuid, f64)>> = HashMap::new();
+        for &proposer in proposers {
+            for &acceptor in &acceptors {
+                let score = Self::analyze_goal_alignment(graph, proposer, acceptor)?.alignment_score;
+                if score >= 0.3 {
+                    proposer_scores.entry(proposer).or_default().push((acceptor, score));
+                    acceptor_scores.entry(acceptor).or_default().push((proposer, score));
+                }
+            }
+        }
         
-        // Calculate preferences for each node
-        for &node1 in candidate_nodes {
-            let mut node_preferences = Vec::new();
+        let mut preferences = Preferences::new();
+        for (node_id, scores) in proposer_scores.into_iter().chain(acceptor_scores) {
+            preferences.insert(node_id, scores);
+        }
+        let matching = Self::gale_shapley(proposers, &acceptors, &preferences);
+        
+        let mut pairs = matching
+            .pairs
+            .iter()
+            .map(|pair| Self::analyze_goal_alignment(graph, pair.proposer, pair.acceptor))
+            .collect::<Result<Vec<_>>>()?;
+        
+        // Sort pairs by potential value
+        pairs.sort_by(|a, b| b.potential_value.partial_cmp(&a.potential_value).unwrap_or(std::cmp::Ordering::Equal));
+        pairs.truncate(max_pairs);
+        
+        Ok(pairs)
+    }
+    
+    /// Preferences of each node over the others by goal alignment; pairs
+    /// scoring below `min_score` are unacceptable to both
+    pub fn alignment_preferences(graph: &HyperGraph, nodes: &[Uuid], min_score: f64) -> Result<Preferences> {
+        let mut scores: HashMap<Uuid, Vec<(Uuid, f64)>> = HashMap::new();
+        for (i, &node1) in nodes.iter().enumerate() {
+            for &node2 in &nodes[i + 1..] {
+                let score = Self::analyze_goal_alignment(graph, node1, node2)?.alignment_score;
+                if score >= min_score {
+                    scores.entry(node1).or_default().push((node2, score));
+                    scores.entry(node2).or_default().push((node1, score));
+                }
+            }
+        }
+        
+        let mut preferences = Preferences::new();
+        for node_id in nodes {
+            preferences.insert(*node_id, scores.remove(node_id).unwrap_or_default());
+        }
+        Ok(preferences)
+    }
+    
+    /// Proposer-optimal stable matching by Gale–Shapley deferred acceptance
+    ///
+    /// Ties are broken by node id, which makes the result weakly stable: no
+    /// pair strictly prefers each other to their partners. Blocking pairs are
+    /// still checked and reported.
+    pub fn gale_shapley(proposers: &[Uuid], acceptors: &[Uuid], preferences: &Preferences) -> StableMatching {
+        let is_acceptor: HashSet<Uuid> = acceptors.iter().copied().collect();
+        // Proposal order with ties broken by id (already sorted within tiers)
+        let order: HashMap<Uuid, Vec<Uuid>> = proposers
+            .iter()
+            .map(|proposer| {
+                let candidates = preferences
+                    .tiers(proposer)
+                    .iter()
+                    .flatten()
+                    .filter(|acceptor| is_acceptor.contains(acceptor) && preferences.acceptable(proposer, acceptor))
+                    .copied()
+                    .collect();
+                (*proposer, candidates)
+            })
+            .collect();
+        
+        let mut next: HashMap<Uuid, usize> = HashMap::new();
+        let mut engaged: HashMap<Uuid, Uuid> = HashMap::new();
+        let mut free: VecDeque<Uuid> = proposers.iter().copied().collect();
+        
+        while let Some(proposer) = free.pop_front() {
+            let index = next.entry(proposer).or_default();
+            let Some(&acceptor) = order[&proposer].get(*index) else {
+                continue;
+            };
+            *index += 1;
             
-            for &node2 in candidate_nodes {
-                if node1 != node2 {
-                    match Self::analyze_goal_alignment(graph, node1, node2) {
-                        Ok(alignment) => {
-                            node_preferences.push((node2, alignment.alignment_score));
-                        }
-                        Err(_) => continue,
+            match engaged.get(&acceptor).copied() {
+                None => {
+                    engaged.insert(acceptor, proposer);
+                }
+                Some(current) => {
+                    let key = |node: &Uuid| (preferences.rank(&acceptor, node), *node);
+                    if key(&proposer) < key(&current) {
+                        engaged.insert(acceptor, proposer);
+                        free.push_back(current);
+                    } else {
+                        free.push_back(proposer);
                     }
                 }
             }
-            
-            // Sort by alignment score (highest first)
-            node_preferences.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-            preferences.insert(node1, node_preferences);
         }
         
-        // Simple greedy matching (not fully stable, but efficient)
-        let mut matched = HashSet::new();
+        let mut pairs: Vec<MatchedPair> = engaged
+            .iter()
+            .map(|(acceptor, proposer)| MatchedPair {
+                proposer: *proposer,
+                acceptor: *acceptor,
+                proposer_score: preferences.score(proposer, acceptor).unwrap_or(0.0),
+                acceptor_score: preferences.score(acceptor, proposer).unwrap_or(0.0),
+            })
+            .collect();
+        pairs.sort_by_key(|pair| pair.proposer);
+        
+        let partners: HashMap<Uuid, Uuid> = engaged
+            .iter()
+            .flat_map(|(acceptor, proposer)| [(*acceptor, *proposer), (*proposer, *acceptor)])
+            .collect();
+        let unmatched = proposers
+            .iter()
+            .chain(acceptors)
+            .filter(|node_id| !partners.contains_key(node_id))
+            .copied()
+            .collect();
         
-        for &node1 in candidate_nodes {
-            if matched.contains(&node1) {
+        StableMatching {
+            pairs,
+            unmatched,
+            blocking_pairs: Self::blocking_pairs(proposers, acceptors, preferences, &partners),
+        }
+    }
+    
+    /// Proposer–acceptor pairs who both strictly prefer each other to their
+    /// partners in `partners`
+    pub fn blocking_pairs(
+        proposers: &[Uuid],
+        acceptors: &[Uuid],
+        preferences: &Preferences,
+        partners: &HashMap<Uuid, Uuid>,
+    ) -> Vec<(Uuid, Uuid)> {
+        let mut blocking = Vec::new();
+        for proposer in proposers {
+            for acceptor in acceptors {
+                if partners.get(proposer) == Some(acceptor) {
+                    continue;
+                }
+                if preferences.prefers(proposer, acceptor, partners.get(proposer))
+                    && preferences.prefers(acceptor, proposer, partners.get(acceptor))
+                {
+                    blocking.push((*proposer, *acceptor));
+                }
+            }
+        }
+        blocking
+    }
+    
+    /// Irving's stable roommates algorithm over a single pool of participants
+    ///
+    /// Ties are broken by node id, so with ties a different tie-break could
+    /// occasionally still find a matching where none was reported. Some
+    /// instances have no stable matching; the outcome then says so and the
+    /// pairs come from a local search for the matching with the fewest
+    /// blocking pairs.
+    pub fn stable_roommates(participants: &[Uuid], preferences: &Preferences) -> RoommatesMatching {
+        let mut participants = participants.to_vec();
+        participants.sort();
+        participants.dedup();
+        let n = participants.len();
+        let index: HashMap<Uuid, usize> = participants.iter().enumerate().map(|(i, node_id)| (*node_id, i)).collect();
+        
+        // Strict lists of mutually acceptable participants, best first
+        let mut lists: Vec<Vec<usize>> = participants
+            .iter()
+            .enumerate()
+            .map(|(i, node_id)| {
+                preferences
+                    .tiers(node_id)
+                    .iter()
+                    .flatten()
+                    .filter_map(|candidate| index.get(candidate).copied())
+                    .filter(|&j| j != i && preferences.acceptable(node_id, &participants[j]))
+                    .collect()
+            })
+            .collect();
+        
+        // Phase 1: everyone proposes down their list; whoever is proposed to
+        // holds the proposal and drops everyone they like less
+        let mut holding: Vec<Option<usize>> = vec![None; n];
+        let mut free: VecDeque<usize> = (0..n).collect();
+        while let Some(proposer) = free.pop_front() {
+            let Some(&target) = lists[proposer].first() else {
                 continue;
+            };
+            // The target already dropped everyone worse than its current
+            // proposer, so this proposal is better
+            if let Some(rejected) = holding[target].replace(proposer) {
+                free.push_back(rejected);
             }
-            
-            if let Some(node_prefs) = preferences.get(&node1) {
-                for &(node2, score) in node_prefs {
-                    if !matched.contains(&node2) && score > 0.3 {
-                        // Create the alignment
-                        if let Ok(alignment) = Self::analyze_goal_alignment(graph, node1, node2) {
-                            pairs.push(alignment);
-                            matched.insert(node1);
-                            matched.insert(node2);
-                            break;
-                        }
-                    }
+            Self::truncate_after(&mut lists, target, proposer);
+        }
+        
+        // Participants whose lists emptied in phase 1 are unmatched in every
+        // stable matching; anyone else losing their whole list means there is
+        // no stable matching at all
+        let matchable: Vec<bool> = lists.iter().map(|list| !list.is_empty()).collect();
+        let mut solvable = true;
+        
+        // Phase 2: eliminate exposed rotations until every list is down to one
+        while let Some(start) = (0..n).find(|&i| lists[i].len() > 1) {
+            // Follow each participant's second choice to the last on that
+            // choice's list until the walk cycles; the cycle is a rotation
+            let mut sequence = vec![start];
+            let mut position = HashMap::from([(start, 0)]);
+            let mut current = start;
+            let rotation = loop {
+                let second = lists[current][1];
+                current = *lists[second].last().expect("a second choice still lists its proposer");
+                if let Some(&i) = position.get(&current) {
+                    break sequence.split_off(i);
                 }
+                position.insert(current, sequence.len());
+                sequence.push(current);
+            };
+            
+            // Each member moves on to its second choice, which drops everyone
+            // it likes less than the newcomer
+            let moves: Vec<(usize, usize)> = rotation.iter().map(|&x| (x, lists[x][1])).collect();
+            for (x, y) in moves {
+                Self::truncate_after(&mut lists, y, x);
             }
             
-            if pairs.len() >= max_pairs {
+            if (0..n).any(|i| matchable[i] && lists[i].is_empty()) {
+                solvable = false;
                 break;
             }
         }
         
-        // Sort pairs by potential value
-        pairs.sort_by(|a, b| b.potential_value.partial_cmp(&a.potential_value).unwrap_or(std::cmp::Ordering::Equal));
-        pairs.truncate(max_pairs);
+        let partners: HashMap<Uuid, Uuid> = if solvable {
+            lists
+                .iter()
+                .enumerate()
+                .filter_map(|(i, list)| Some((participants[i], participants[*list.first()?])))
+                .collect()
+        } else {
+            Self::fewest_blocking_matching(&participants, preferences)
+        };
         
-        Ok(pairs)
+        let mut pairs: Vec<RoommatePair> = partners
+            .iter()
+            .filter(|(a, b)| a < b)
+            .map(|(a, b)| RoommatePair {
+                first: *a,
+                second: *b,
+                first_score: preferences.score(a, b).unwrap_or(0.0),
+                second_score: preferences.score(b, a).unwrap_or(0.0),
+            })
+            .collect();
+        pairs.sort_by_key(|pair| pair.first);
+        
+        RoommatesMatching {
+            outcome: if solvable { RoommatesOutcome::Stable } else { RoommatesOutcome::NoStableMatching },
+            pairs,
+            unmatched: participants.iter().filter(|node_id| !partners.contains_key(node_id)).copied().collect(),
+            blocking_pairs: Self::roommate_blocking_pairs(&participants, preferences, &partners),
+        }
+    }
+    
+    /// Pairs of participants who both strictly prefer each other to their
+    /// partners in `partners`
+    pub fn roommate_blocking_pairs(
+        participants: &[Uuid],
+        preferences: &Preferences,
+        partners: &HashMap<Uuid, Uuid>,
+    ) -> Vec<(Uuid, Uuid)> {
+        let mut blocking = Vec::new();
+        for (i, a) in participants.iter().enumerate() {
+            for b in &participants[i + 1..] {
+                if partners.get(a) == Some(b) {
+                    continue;
+                }
+                if preferences.prefers(a, b, partners.get(a)) && preferences.prefers(b, a, partners.get(b)) {
+                    blocking.push((*a, *b));
+                }
+            }
+        }
+        blocking
+    }
+    
+    /// Drop everyone `holder` likes less than `kept` from its list, and
+    /// `holder` from theirs
+    fn truncate_after(lists: &mut [Vec<usize>], holder: usize, kept: usize) {
+        while let Some(&dropped) = lists[holder].last() {
+            if dropped == kept {
+                break;
+            }
+            lists[holder].pop();
+            lists[dropped].retain(|&i| i != holder);
+        }
+    }
+    
+    /// Fallback for roommates instances without a stable matching: start from
+    /// the greedy matching of the best mutual scores and keep satisfying a
+    /// blocking pair while that lowers the number of blocking pairs, giving
+    /// up after `MAX_LOCAL_SEARCH_TRIES` candidates
+    fn fewest_blocking_matching(participants: &[Uuid], preferences: &Preferences) -> HashMap<Uuid, Uuid> {
+        let mut candidates: Vec<(f64, Uuid, Uuid)> = Vec::new();
+        for (i, a) in participants.iter().enumerate() {
+            for b in &participants[i + 1..] {
+                if let (Some(ab), Some(ba)) = (preferences.score(a, b), preferences.score(b, a)) {
+                    candidates.push((ab + ba, *a, *b));
+                }
+            }
+        }
+        candidates.sort_by(|x, y| y.0.total_cmp(&x.0).then((x.1, x.2).cmp(&(y.1, y.2))));
+        
+        let mut partners: HashMap<Uuid, Uuid> = HashMap::new();
+        for (_, a, b) in candidates {
+            if !partners.contains_key(&a) && !partners.contains_key(&b) {
+                partners.insert(a, b);
+                partners.insert(b, a);
+            }
+        }
+        
+        let mut blocking = Self::roommate_blocking_pairs(participants, preferences, &partners);
+        let mut tries = 0;
+        // Every accepted step removes at least one blocking pair, so this
+        // ends, but each try is quadratic so their number is capped as well
+        while let Some((improved, remaining)) = blocking.iter().take_while(|_| {
+            tries += 1;
+            tries <= MAX_LOCAL_SEARCH_TRIES
+        }).find_map(|(a, b)| {
+            let mut candidate = partners.clone();
+            let left = [candidate.remove(a), candidate.remove(b)];
+            for former in left.iter().flatten() {
+                candidate.remove(former);
+            }
+            candidate.insert(*a, *b);
+            candidate.insert(*b, *a);
+            // The two left behind pair up if they can
+            if let [Some(x), Some(y)] = left {
+                if preferences.acceptable(&x, &y) {
+                    candidate.insert(x, y);
+                    candidate.insert(y, x);
+                }
+            }
+            
+            let remaining = Self::roommate_blocking_pairs(participants, preferences, &candidate);
+            (remaining.len() < blocking.len()).then_some((candidate, remaining))
+        }) {
+            partners = improved;
+            blocking = remaining;
+        }
+        partners
+    }
+    
+    /// Proposer-optimal hospitals/residents matching: each proposer gets at
+    /// most one acceptor and each acceptor up to its upper capacity
+    ///
+    /// By the rural hospitals theorem an acceptor short of its lower quota
+    /// gets the same number of proposers in every stable matching, so the
+    /// acceptor furthest below its quota is closed and the matching rerun
+    /// until every open acceptor meets its quota. Groups are proposed as hub
+    /// hyperedges with the given relationship.
+    pub fn hospitals_residents(
+        proposers: &[Uuid],
+        acceptors: &[(Uuid, Capacity)],
+        preferences: &Preferences,
+        relationship: &str,
+    ) -> CapacitatedMatching {
+        let mut open: HashMap<Uuid, Capacity> = acceptors.iter().copied().collect();
+        let mut closed = Vec::new();
+        let assigned = loop {
+            let assigned = Self::capacitated_deferred_acceptance(proposers, &open, preferences);
+            let shortfall = |acceptor: &Uuid| {
+                let held = assigned.get(acceptor).map_or(0, Vec::len);
+                open[acceptor].lower.saturating_sub(held)
+            };
+            let furthest_short = acceptors
+                .iter()
+                .map(|(acceptor, _)| acceptor)
+                .filter(|acceptor| open.contains_key(acceptor) && shortfall(acceptor) > 0)
+                .max_by_key(|acceptor| (shortfall(acceptor), std::cmp::Reverse(**acceptor)));
+            match furthest_short {
+                Some(acceptor) => {
+                    open.remove(acceptor);
+                    closed.push(*acceptor);
+                }
+                None => break assigned,
+            }
+        };
+        
+        let mut groups = Vec::new();
+        let mut matched = HashSet::new();
+        for (acceptor, _) in acceptors {
+            let Some(members) = assigned.get(acceptor).filter(|members| !members.is_empty()) else {
+                continue;
+            };
+            let mut members = members.clone();
+            members.sort_by_key(|member| (preferences.rank(acceptor, member), *member));
+            
+            let scores: Vec<f64> = members
+                .iter()
+                .flat_map(|member| [preferences.score(acceptor, member), preferences.score(member, acceptor)])
+                .map(|score| score.unwrap_or(0.0))
+                .collect();
+            matched.insert(*acceptor);
+            matched.extend(members.iter().copied());
+            groups.push(ProposedEdge {
+                node_ids: std::iter::once(*acceptor).chain(members.iter().copied()).collect(),
+                relationship: relationship.to_string(),
+                strength: scores.iter().sum::<f64>() / scores.len() as f64,
+                edge_type: EdgeType::Hub { center: *acceptor, periphery: members },
+            });
+        }
+        
+        // A proposer and an open acceptor block if the proposer would rather
+        // have it and the acceptor has room or someone it likes less
+        let mut blocking_pairs = Vec::new();
+        let assignment: HashMap<Uuid, Uuid> =
+            assigned.iter().flat_map(|(acceptor, members)| members.iter().map(move |member| (*member, *acceptor))).collect();
+        for proposer in proposers {
+            for (acceptor, capacity) in acceptors.iter().filter(|(acceptor, _)| open.contains_key(acceptor)) {
+                if assignment.get(proposer) == Some(acceptor)
+                    || !preferences.prefers(proposer, acceptor, assignment.get(proposer))
+                {
+                    continue;
+                }
+                let members = assigned.get(acceptor).map_or(&[][..], Vec::as_slice);
+                let has_room = members.len() < capacity.upper && preferences.rank(acceptor, proposer).is_some();
+                if has_room || members.iter().any(|member| preferences.prefers(acceptor, proposer, Some(member))) {
+                    blocking_pairs.push((*proposer, *acceptor));
+                }
+            }
+        }
+        
+        CapacitatedMatching {
+            groups,
+            unmatched: proposers
+                .iter()
+                .chain(acceptors.iter().map(|(acceptor, _)| acceptor))
+                .filter(|node_id| !matched.contains(node_id))
+                .copied()
+                .collect(),
+            closed,
+            blocking_pairs,
+        }
+    }
+    
+    /// Deferred acceptance with capacities; ties are broken by node id
+    fn capacitated_deferred_acceptance(
+        proposers: &[Uuid],
+        acceptors: &HashMap<Uuid, Capacity>,
+        preferences: &Preferences,
+    ) -> HashMap<Uuid, Vec<Uuid>> {
+        let order: HashMap<Uuid, Vec<Uuid>> = proposers
+            .iter()
+            .map(|proposer| {
+                let candidates = preferences
+                    .tiers(proposer)
+                    .iter()
+                    .flatten()
+                    .filter(|acceptor| acceptors.contains_key(acceptor) && preferences.acceptable(proposer, acceptor))
+                    .copied()
+                    .collect();
+                (*proposer, candidates)
+            })
+            .collect();
+        
+        let mut next: HashMap<Uuid, usize> = HashMap::new();
+        let mut assigned: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        let mut free: VecDeque<Uuid> = proposers.iter().copied().collect();
+        
+        while let Some(proposer) = free.pop_front() {
+            let index = next.entry(proposer).or_default();
+            let Some(&acceptor) = order[&proposer].get(*index) else {
+                continue;
+            };
+            *index += 1;
+            
+            let members = assigned.entry(acceptor).or_default();
+            members.push(proposer);
+            if members.len() > acceptors[&acceptor].upper {
+                // Over capacity: the least preferred member is rejected
+                let key = |node: &Uuid| (preferences.rank(&acceptor, node), *node);
+                let worst = (0..members.len()).max_by_key(|&i| key(&members[i])).expect("members is non-empty");
+                free.push_back(members.swap_remove(worst));
+            }
+        }
+        assigned
+    }
+    
+    /// Beam search for the teams with the best combined score, each then
+    /// improved by swapping members while that raises its score
+    ///
+    /// `candidates` pairs each node with its type, and `scores[i][j]` holds
+    /// the alignment and complementarity of candidates `i` and `j`.
+    pub fn form_teams(
+        candidates: &[(Uuid, String)],
+        scores: &[Vec<(f64, f64)>],
+        config: &TeamConfig,
+    ) -> Result<Vec<TeamProposal>> {
+        if config.size < 2 {
+            return Err(anyhow::anyhow!("Teams need at least two members"));
+        }
+        if let Some(role) = config.roles.iter().find(|role| role.min > role.max) {
+            return Err(anyhow::anyhow!("Role {} has a minimum above its maximum", role.node_type));
+        }
+        if config.roles.iter().map(|role| role.min).sum::<usize>() > config.size {
+            return Err(anyhow::anyhow!("Role minimums add up to more than the team size"));
+        }
+        let valid_weight = |weight: f64| weight.is_finite() && weight >= 0.0;
+        let total_weight = config.alignment_weight + config.complementarity_weight;
+        if !valid_weight(config.alignment_weight) || !valid_weight(config.complementarity_weight) || total_weight == 0.0 {
+            return Err(anyhow::anyhow!("Score weights must be non-negative and not both zero"));
+        }
+        
+        let combined = |i: usize, j: usize| {
+            let (alignment, complementarity) = scores[i][j];
+            (config.alignment_weight * alignment + config.complementarity_weight * complementarity) / total_weight
+        };
+        // Every role's counts stay within its maximum, with room left for
+        // the members its minimum still needs
+        let feasible = |team: &[usize]| {
+            let mut missing = 0;
+            for role in &config.roles {
+                let count = team.iter().filter(|&&i| candidates[i].1 == role.node_type).count();
+                if count > role.max {
+                    return false;
+                }
+                missing += role.min.saturating_sub(count);
+            }
+            missing <= config.size - team.len()
+        };
+        let total = |team: &[usize]| -> f64 {
+            team.iter().enumerate().flat_map(|(k, &i)| team[k + 1..].iter().map(move |&j| combined(i, j))).sum()
+        };
+        let ranked = |a: &(f64, Vec<usize>), b: &(f64, Vec<usize>)| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(&b.1));
+        
+        let width = config.beam_width.max(config.max_teams).max(1);
+        let mut beam: Vec<(f64, Vec<usize>)> = vec![(0.0, Vec::new())];
+        for _ in 0..config.size {
+            let mut seen = HashSet::new();
+            let mut next = Vec::new();
+            for (score, team) in &beam {
+                for candidate in 0..candidates.len() {
+                    if team.contains(&candidate) {
+                        continue;
+                    }
+                    let mut grown = team.clone();
+                    grown.push(candidate);
+                    grown.sort_unstable();
+                    if !feasible(&grown) || !seen.insert(grown.clone()) {
+                        continue;
+                    }
+                    let gain: f64 = team.iter().map(|&member| combined(member, candidate)).sum();
+                    next.push((score + gain, grown));
+                }
+            }
+            next.sort_by(ranked);
+            next.truncate(width);
+            beam = next;
+        }
+        
+        // Local search: swap in the outsider that raises the score most,
+        // never turning one proposal into a copy of another
+        let mut taken: HashSet<Vec<usize>> = beam.iter().map(|(_, team)| team.clone()).collect();
+        for (score, team) in &mut beam {
+            loop {
+                let mut best: Option<(f64, Vec<usize>)> = None;
+                for position in 0..team.len() {
+                    for outsider in (0..candidates.len()).filter(|i| !team.contains(i)) {
+                        let mut swapped = team.clone();
+                        swapped[position] = outsider;
+                        swapped.sort_unstable();
+                        if !feasible(&swapped) || taken.contains(&swapped) {
+                            continue;
+                        }
+                        let swapped_score = total(&swapped);
+                        if swapped_score > best.as_ref().map_or(*score, |(best, _)| *best) + TIE_TOLERANCE {
+                            best = Some((swapped_score, swapped));
+                        }
+                    }
+                }
+                match best {
+                    Some((improved, swapped)) => {
+                        taken.remove(team);
+                        taken.insert(swapped.clone());
+                        *score = improved;
+                        *team = swapped;
+                    }
+                    None => break,
+                }
+            }
+        }
+        beam.sort_by(ranked);
+        beam.truncate(config.max_teams);
+        
+        let pairs = (config.size * (config.size - 1) / 2) as f64;
+        Ok(beam
+            .into_iter()
+            .map(|(_, team)| {
+                let mean = |part: fn((f64, f64)) -> f64| {
+                    team.iter()
+                        .enumerate()
+                        .flat_map(|(k, &i)| team[k + 1..].iter().map(move |&j| part(scores[i][j])))
+                        .sum::<f64>()
+                        / pairs
+                };
+                let alignment = mean(|(alignment, _)| alignment);
+                let complementarity = mean(|(_, complementarity)| complementarity);
+                let score = (config.alignment_weight * alignment + config.complementarity_weight * complementarity)
+                    / total_weight;
+                TeamProposal {
+                    score,
+                    alignment,
+                    complementarity,
+                    edge: ProposedEdge {
+                        node_ids: team.iter().map(|&i| candidates[i].0).collect(),
+                        relationship: config.relationship.clone(),
+                        strength: score,
+                        edge_type: EdgeType::Symmetric,
+                    },
+                }
+            })
+            .collect())
     }
     
     /// Calculate centrality measures for nodes
     pub fn calculate_centrality_measures(graph: &HyperGraph) -> HashMap<Uuid, CentralityMeasures> {
-        let mut measures = HashMap::new();
+        Self::calculate_centrality_measures_with_config(graph, &CentralityConfig::default())
+    }
+    
+    /// Calculate centrality measures, with distances and sampling as configured
+    pub fn calculate_centrality_measures_with_config(
+        graph: &HyperGraph,
+        config: &CentralityConfig,
+    ) -> HashMap<Uuid, CentralityMeasures> {
+        let paths = Self::shortest_path_centrality(graph, config);
         
-        for (node_id, _) in graph.nodes() {
+        let mut measures = HashMap::new();
+        for node_id in graph.nodes().keys() {
             let degree = graph.get_node_edges(node_id).len() as f64;
             let neighbors = graph.get_neighbors(node_id);
-            
-            // Betweenness centrality (simplified)
-            let betweenness = Self::calculate_betweenness_centrality(graph, *node_id);
-            
-            // Closeness centrality (simplified)
-            let closeness = Self::calculate_closeness_centrality(graph, *node_id);
+            let (betweenness, closeness) = paths.get(node_id).copied().unwrap_or_default();
             
             // Eigenvector centrality (simplified)
             let eigenvector = Self::calculate_eigenvector_centrality(graph, *node_id, &neighbors);
@@ -255,18 +1264,47 @@ impl NetworkAnalyzer {
         measures
     }
     
+    /// Normalized betweenness of every node
+    pub fn betweenness_centrality(graph: &HyperGraph, config: &CentralityConfig) -> HashMap<Uuid, f64> {
+        Self::shortest_path_centrality(graph, config)
+            .into_iter()
+            .map(|(node_id, (betweenness, _))| (node_id, betweenness))
+            .collect()
+    }
+    
+    /// Closeness of every node, scaled by the share of nodes it reaches
+    pub fn closeness_centrality(graph: &HyperGraph, config: &CentralityConfig) -> HashMap<Uuid, f64> {
+        Self::shortest_path_centrality(graph, config)
+            .into_iter()
+            .map(|(node_id, (_, closeness))| (node_id, closeness))
+            .collect()
+    }
+    
     // Helper methods
     
+    /// Classify a cascade from its signed per-node effects
+    ///
+    /// Effects are competitive when the nodes it suppresses lose at least half
+    /// as much as the others gain.
     fn classify_effect_type(affected_nodes: &[(Uuid, f64)], total_effect: f64) -> EffectType {
-        if total_effect > 0.5 && affected_nodes.len() > 2 {
+        let gains: f64 = affected_nodes.iter().map(|(_, effect)| effect.max(0.0)).sum();
+        let losses: f64 = affected_nodes.iter().map(|(_, effect)| (-effect).max(0.0)).sum();
+        let beneficiaries = affected_nodes.iter().filter(|(_, effect)| *effect > 0.0).count();
+        
+        if losses >= 0.1 && losses >= gains * 0.5 {
+            EffectType::Competitive
+        } else if total_effect > 0.5 && beneficiaries > 2 {
             EffectType::Synergistic
         } else if total_effect < 0.1 {
             EffectType::Neutral
-        } else if let Some(&(primary_node, primary_effect)) = affected_nodes.first() {
-            if primary_effect > total_effect * 0.7 {
+        } else if let Some(&(primary_node, primary_effect)) = affected_nodes
+            .iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+        {
+            if primary_effect > gains * 0.7 {
                 EffectType::Asymmetric { primary_beneficiary: primary_node }
             } else {
-                EffectType::Competitive
+                EffectType::Synergistic
             }
         } else {
             EffectType::Neutral
@@ -381,15 +1419,133 @@ impl NetworkAnalyzer {
         (risks, opportunities)
     }
     
-    // Simplified centrality calculations
-    fn calculate_betweenness_centrality(_graph: &HyperGraph, _node_id: Uuid) -> f64 {
-        // TODO: Implement proper betweenness centrality calculation
-        0.5 // Placeholder
+    /// Betweenness and closeness of every node by Brandes' algorithm over
+    /// the star expansion of the hypergraph
+    ///
+    /// A path steps from node to node through a hyperedge both belong to,
+    /// whatever the edge's propagation direction, so distinct hyperedges
+    /// joining the same nodes are distinct paths. A step costs 1, or the
+    /// inverse of the edge's conductance magnitude when weighted, in which
+    /// case edges without conductance can't be crossed. Sources run in
+    /// parallel; with sampling, only that many random sources run and the
+    /// results are scaled up to estimate the exact values.
+    ///
+    /// Betweenness is normalized by the `(n - 1)(n - 2) / 2` pairs it could
+    /// lie between. Closeness is `(r / (n - 1)) / mean distance` over the `r`
+    /// nodes a node reaches (Wasserman–Faust), so it stays comparable on
+    /// disconnected graphs.
+    fn shortest_path_centrality(graph: &HyperGraph, config: &CentralityConfig) -> HashMap<Uuid, (f64, f64)> {
+        let mut node_ids: Vec<Uuid> = graph.nodes().keys().copied().collect();
+        node_ids.sort();
+        let n = node_ids.len();
+        let index: HashMap<Uuid, usize> = node_ids.iter().enumerate().map(|(i, node_id)| (*node_id, i)).collect();
+        
+        // Nodes take vertices 0..n and hyperedges the ones after; half a
+        // step's cost is paid entering the hyperedge and half leaving it
+        let mut adjacency: Vec<Vec<(usize, f64)>> = vec![Vec::new(); n];
+        let mut edge_ids: Vec<&Uuid> = graph.edges().keys().collect();
+        edge_ids.sort();
+        for edge_id in edge_ids {
+            let edge = &graph.edges()[edge_id];
+            let cost = if config.weighted { 1.0 / edge.conductance.abs() } else { 1.0 };
+            if !cost.is_finite() {
+                continue;
+            }
+            let members: Vec<usize> = edge.node_ids.iter().filter_map(|node_id| index.get(node_id).copied()).collect();
+            let vertex = adjacency.len();
+            adjacency.push(members.iter().map(|&member| (member, cost / 2.0)).collect());
+            for member in members {
+                adjacency[member].push((vertex, cost / 2.0));
+            }
+        }
+        
+        let sources: Vec<usize> = match config.samples {
+            Some(samples) if samples < n => sample_indices(n, samples, config.seed),
+            _ => (0..n).collect(),
+        };
+        let totals = sources
+            .par_iter()
+            .fold(
+                || PathTotals::new(n),
+                |mut totals, &source| {
+                    Self::brandes_from(source, n, &adjacency, &mut totals);
+                    totals
+                },
+            )
+            .reduce(|| PathTotals::new(n), PathTotals::merge);
+        
+        let is_source: HashSet<usize> = sources.iter().copied().collect();
+        let scale = if sources.is_empty() { 0.0 } else { n as f64 / sources.len() as f64 };
+        // Each unordered pair is counted from both ends
+        let pairs = (n.saturating_sub(1) * n.saturating_sub(2)) as f64;
+        node_ids
+            .iter()
+            .enumerate()
+            .map(|(i, node_id)| {
+                let betweenness = if pairs > 0.0 { totals.dependency[i] * scale / pairs } else { 0.0 };
+                // Sources other than the node itself, whose distances to it
+                // stand in for its own distances to everyone
+                let others = sources.len() - usize::from(is_source.contains(&i));
+                let closeness = if totals.reached[i] == 0 || totals.distance[i] <= 0.0 {
+                    0.0
+                } else {
+                    let reach = totals.reached[i] as f64 / others as f64;
+                    reach * totals.reached[i] as f64 / totals.distance[i]
+                };
+                (*node_id, (betweenness, closeness))
+            })
+            .collect()
     }
     
-    fn calculate_closeness_centrality(_graph: &HyperGraph, _node_id: Uuid) -> f64 {
-        // TODO: Implement proper closeness centrality calculation
-        0.5 // Placeholder
+    /// Single-source shortest paths from one node, adding each node's
+    /// dependency and its distance from the source to the totals
+    fn brandes_from(source: usize, n: usize, adjacency: &[Vec<(usize, f64)>], totals: &mut PathTotals) {
+        let vertices = adjacency.len();
+        let mut distance = vec![f64::INFINITY; vertices];
+        let mut paths = vec![0.0; vertices];
+        let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); vertices];
+        let mut settled = Vec::with_capacity(vertices);
+        let mut done = vec![false; vertices];
+        
+        distance[source] = 0.0;
+        paths[source] = 1.0;
+        let mut queue = BinaryHeap::from([PathStep { distance: 0.0, vertex: source }]);
+        while let Some(PathStep { distance: reached, vertex }) = queue.pop() {
+            if done[vertex] {
+                continue;
+            }
+            done[vertex] = true;
+            settled.push(vertex);
+            
+            for &(next, cost) in &adjacency[vertex] {
+                let candidate = reached + cost;
+                let tolerance = TIE_TOLERANCE * candidate.max(1.0);
+                if candidate < distance[next] - tolerance {
+                    distance[next] = candidate;
+                    paths[next] = paths[vertex];
+                    predecessors[next].clear();
+                    predecessors[next].push(vertex);
+                    queue.push(PathStep { distance: candidate, vertex: next });
+                } else if (candidate - distance[next]).abs() <= tolerance && !done[next] {
+                    paths[next] += paths[vertex];
+                    predecessors[next].push(vertex);
+                }
+            }
+        }
+        
+        // Hyperedge vertices pass dependency on but are never endpoints
+        let mut dependency = vec![0.0; vertices];
+        for &vertex in settled.iter().rev() {
+            let endpoint = if vertex < n { 1.0 } else { 0.0 };
+            for &previous in &predecessors[vertex] {
+                dependency[previous] += paths[previous] / paths[vertex] * (endpoint + dependency[vertex]);
+            }
+            if vertex < n && vertex != source {
+                totals.dependency[vertex] += dependency[vertex];
+                totals.distance[vertex] += distance[vertex];
+                totals.reached[vertex] += 1;
+            }
+        }
     }
     
     fn calculate_eigenvector_centrality(_graph: &HyperGraph, _node_id: Uuid, neighbors: &[&HyperNode]) -> f64 {
@@ -402,10 +1558,97 @@ impl NetworkAnalyzer {
     }
 }
 
-#[derive(Debug, Clone)]
+/// How shortest-path centralities are computed
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CentralityConfig {
+    /// Measure a step through a hyperedge as the inverse of its conductance
+    /// instead of one hop
+    pub weighted: bool,
+    /// Only run from this many random source nodes and scale the results,
+    /// trading accuracy for time on large graphs
+    pub samples: Option<usize>,
+    /// Seed for picking the sampled sources
+    pub seed: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CentralityMeasures {
     pub degree: f64,
     pub betweenness: f64,
     pub closeness: f64,
     pub eigenvector: f64,
-}
\ No newline at end of file
+}
+
+/// Per-node sums gathered from single-source shortest path runs
+struct PathTotals {
+    dependency: Vec<f64>,
+    distance: Vec<f64>,
+    reached: Vec<usize>,
+}
+
+impl PathTotals {
+    fn new(n: usize) -> Self {
+        Self {
+            dependency: vec![0.0; n],
+            distance: vec![0.0; n],
+            reached: vec![0; n],
+        }
+    }
+    
+    fn merge(mut self, other: Self) -> Self {
+        for i in 0..self.reached.len() {
+            self.dependency[i] += other.dependency[i];
+            self.distance[i] += other.distance[i];
+            self.reached[i] += other.reached[i];
+        }
+        self
+    }
+}
+
+/// A vertex waiting in the shortest path queue, nearest first
+struct PathStep {
+    distance: f64,
+    vertex: usize,
+}
+
+impl PartialEq for PathStep {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for PathStep {}
+
+impl PartialOrd for PathStep {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PathStep {
+    // Reversed so that `BinaryHeap` pops the nearest vertex first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.distance.total_cmp(&self.distance).then_with(|| other.vertex.cmp(&self.vertex))
+    }
+}
+
+/// `count` distinct indices below `n`, picked by a seeded shuffle
+fn sample_indices(n: usize, count: usize, seed: u64) -> Vec<usize> {
+    // SplitMix64
+    let mut state = seed;
+    let mut next = move || {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    };
+    
+    let mut indices: Vec<usize> = (0..n).collect();
+    for i in 0..count.min(n) {
+        let j = i + (next() % (n - i) as u64) as usize;
+        indices.swap(i, j);
+    }
+    indices.truncate(count);
+    indices
+}
@@ -1,11 +1,24 @@
 use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Arc;
 use uuid::Uuid;
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, RwLockReadGuard};
+use serde::{Deserialize, Serialize};
 use anyhow::Result;
 use chrono::{DateTime, Utc, Duration};
 
 use crate::core::DatabaseConfig;
-use crate::hypergraph::HyperGraph;
+use crate::analysis::{SpikeTrain, SpikeTrainComparison, SpikeTrainSummary};
+use crate::export::{ExportFormat, MembraneTraces, Raster, RasterQuery};
+use crate::history::{MembraneHistory, SpikeHistory};
+use crate::hypergraph::{HyperGraph, DEFAULT_NODE_TYPE};
+use crate::network::{
+    Capacity, CapacitatedMatching, CapacitatedMatchingConfig, MatchingConfig, NetworkAnalyzer, PreferenceSource,
+    Preferences, StableMatching, TeamConfig, TeamProposal,
+};
+use crate::neuron::{LifParameters, MembraneState, NeuronModel};
+use crate::plasticity::{ConductanceUpdate, EligibilityTrace, RewardUpdate, StdpConfig};
+use crate::simulation::{CascadeArrival, Clock, FiringRule, SimulationConfig, SimulationResult, Simulator, SystemClock};
 
 /// Neuromorphic spike processing engine
 /// 
@@ -14,15 +27,16 @@ use crate::hypergraph::HyperGraph;
 pub struct SpikeProcessor {
     config: DatabaseConfig,
     neural_state: RwLock<NeuralState>,
+    clock: Arc<dyn Clock>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NeuralState {
     /// Current activation levels for all nodes
     pub activations: HashMap<Uuid, f64>,
     
     /// Spike history for temporal processing
-    pub spike_history: Vec<SpikeEvent>,
+    pub spike_history: SpikeHistory,
     
     /// Refractory periods for nodes (can't spike again immediately)
     pub refractory_until: HashMap<Uuid, DateTime<Utc>>,
@@ -34,9 +48,51 @@ pub struct NeuralState {
     pub total_spikes: u64,
     pub processing_cycles: u64,
     pub last_update: DateTime<Utc>,
+    
+    /// Neuron parameters per node type (others use the configured threshold)
+    pub neuron_parameters: HashMap<String, LifParameters>,
+    
+    /// Membrane state of nodes that have received input
+    pub membranes: HashMap<Uuid, MembraneState>,
+    
+    /// Recent spike-timing-dependent plasticity updates, oldest first
+    #[serde(default)]
+    pub conductance_updates: Vec<ConductanceUpdate>,
+    
+    /// Eligibility of each hyperedge for reward-modulated learning
+    #[serde(default)]
+    pub eligibility: HashMap<Uuid, EligibilityTrace>,
+    
+    /// Coincidence rules per relationship type, on top of the configured ones
+    #[serde(default)]
+    pub firing_rules: HashMap<String, FiringRule>,
+    
+    /// Membrane states after each input and reset, for membrane traces
+    #[serde(default)]
+    pub membrane_history: MembraneHistory,
 }
 
-#[derive(Debug, Clone)]
+impl Default for NeuralState {
+    fn default() -> Self {
+        Self {
+            activations: HashMap::new(),
+            spike_history: SpikeHistory::default(),
+            refractory_until: HashMap::new(),
+            synaptic_weights: HashMap::new(),
+            total_spikes: 0,
+            processing_cycles: 0,
+            last_update: Utc::now(),
+            neuron_parameters: HashMap::new(),
+            membranes: HashMap::new(),
+            conductance_updates: Vec::new(),
+            eligibility: HashMap::new(),
+            firing_rules: HashMap::new(),
+            membrane_history: MembraneHistory::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpikeEvent {
     pub node_id: Uuid,
     pub timestamp: DateTime<Utc>,
@@ -54,64 +110,353 @@ pub struct NeuralStats {
 
 impl SpikeProcessor {
     pub fn new(config: DatabaseConfig) -> Self {
+        Self::with_clock(config, Arc::new(SystemClock))
+    }
+    
+    /// Create a processor driven by the given clock (e.g. a `VirtualClock` in tests)
+    pub fn with_clock(config: DatabaseConfig, clock: Arc<dyn Clock>) -> Self {
+        let mut state = NeuralState {
+            spike_history: SpikeHistory::new(config.spike_retention.max_per_node),
+            ..Default::default()
+        };
+        state.membrane_history.set_capacity(config.spike_retention.max_per_node);
         Self {
             config,
-            neural_state: RwLock::new(NeuralState {
-                activations: HashMap::new(),
-                spike_history: Vec::new(),
-                refractory_until: HashMap::new(),
-                synaptic_weights: HashMap::new(),
-                total_spikes: 0,
-                processing_cycles: 0,
-                last_update: Utc::now(),
-            }),
+            neural_state: RwLock::new(state),
+            clock,
         }
     }
     
+    /// Current time according to the processor's clock
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+    
+    /// Read access to the current neural state (e.g. for snapshots)
+    pub async fn state(&self) -> RwLockReadGuard<'_, NeuralState> {
+        self.neural_state.read().await
+    }
+    
+    /// Replace the neural state wholesale (e.g. when loading from storage)
+    pub async fn restore_state(&self, mut state: NeuralState) {
+        state.spike_history.set_capacity(self.config.spike_retention.max_per_node);
+        state.membrane_history.set_capacity(self.config.spike_retention.max_per_node);
+        *self.neural_state.write().await = state;
+    }
+    
+    /// Align neural state with the nodes of a graph
+    ///
+    /// Drops entries for nodes that no longer exist and initializes any
+    /// nodes that have no state yet.
+    pub async fn sync_with_graph(&self, graph: &HyperGraph) {
+        let mut state = self.neural_state.write().await;
+        let exists = |id: &Uuid| graph.get_node(id).is_some();
+        
+        state.activations.retain(|id, _| exists(id));
+        state.membranes.retain(|id, _| exists(id));
+        state.refractory_until.retain(|id, _| exists(id));
+        state.spike_history.retain_nodes(exists);
+        state.membrane_history.retain_nodes(exists);
+        state.synaptic_weights.retain(|(a, b), _| exists(a) && exists(b));
+        state.conductance_updates.retain(|update| graph.get_edge(&update.edge_id).is_some());
+        state.eligibility.retain(|edge_id, _| graph.get_edge(edge_id).is_some());
+        
+        for node_id in graph.nodes().keys() {
+            state.activations.entry(*node_id).or_insert(0.0);
+        }
+    }
+    
+    /// Drop all neural state belonging to a removed node
+    pub async fn forget_node(&self, node_id: Uuid) {
+        let mut state = self.neural_state.write().await;
+        
+        state.activations.remove(&node_id);
+        state.membranes.remove(&node_id);
+        state.refractory_until.remove(&node_id);
+        state.spike_history.remove_node(&node_id);
+        state.membrane_history.remove_node(&node_id);
+        state.synaptic_weights.retain(|(a, b), _| *a != node_id && *b != node_id);
+    }
+    
     /// Process a new node addition (initialize neural state)
     pub async fn process_new_node(&self, node_id: Uuid) -> Result<()> {
         let mut state = self.neural_state.write().await;
-        state.activations.insert(node_id, 0.0);
+        state.activations.entry(node_id).or_insert(0.0);
         Ok(())
     }
     
-    /// Propagate a spike through the network
-    pub async fn propagate_spike(&self, source_nodes: Vec<Uuid>, initial_strength: f64) -> Result<()> {
+    /// Fire the source nodes and propagate the resulting spikes through the network
+    ///
+    /// Nodes that are still refractory don't fire. Every spike of the cascade
+    /// is recorded in the neural state.
+    pub async fn propagate_spike(
+        &self,
+        graph: &HyperGraph,
+        source_nodes: Vec<Uuid>,
+        initial_strength: f64,
+    ) -> Result<SimulationResult> {
         let mut state = self.neural_state.write().await;
-        let now = Utc::now();
+        let now = self.clock.now();
         
+        let mut simulator = Simulator::new(graph, self.simulation_config_for(&state), now)
+            .with_refractory(&state.refractory_until)
+            .with_membranes(&state.membranes)
+            .with_history(&state.spike_history);
         for node_id in source_nodes {
-            // Check refractory period
-            if let Some(refractory_end) = state.refractory_until.get(&node_id) {
-                if now < *refractory_end {
-                    continue; // Node is still in refractory period
+            simulator.inject_spike(node_id, initial_strength, 0.0);
+        }
+        let mut result = simulator.run();
+        
+        // Reward learning needs the STDP pairing even when STDP itself is off
+        let pairing = self.config.stdp.clone().or_else(|| self.config.reward.as_ref().map(|_| StdpConfig::default()));
+        if let Some(pairing) = pairing {
+            let window = Duration::microseconds((pairing.window * 1000.0) as i64);
+            let earlier = state.spike_history.between(now - window, now);
+            let updates = pairing.updates(graph, &earlier, &result.spikes, now);
+            if let Some(reward) = &self.config.reward {
+                for update in &updates {
+                    state
+                        .eligibility
+                        .entry(update.edge_id)
+                        .or_insert(EligibilityTrace { value: 0.0, updated_at: now })
+                        .add(update.change(), now, reward.eligibility_time_constant);
                 }
             }
-            
-            // Create spike event
-            let spike_event = SpikeEvent {
-                node_id,
-                timestamp: now,
-                intensity: initial_strength,
-                propagation_depth: 0,
-            };
-            
-            // Update activation
-            state.activations.insert(node_id, initial_strength);
-            
-            // Set refractory period
-            let refractory_end = now + Duration::milliseconds(self.config.refractory_period as i64);
-            state.refractory_until.insert(node_id, refractory_end);
-            
-            // Record spike
-            state.spike_history.push(spike_event);
+            if self.config.stdp.is_some() {
+                result.conductance_updates = updates;
+            }
+        }
+        
+        let config = self.simulation_config_for(&state);
+        state.membranes.extend(result.membranes.clone());
+        for (node_id, membrane) in &result.membrane_changes {
+            state.membrane_history.push(*node_id, *membrane);
+        }
+        for node_id in &result.reached {
+            if let (Some(membrane), Some(node)) = (result.membranes.get(node_id), graph.get_node(node_id)) {
+                let activation = config.model_for(&node.node_type).activation(membrane);
+                state.activations.insert(*node_id, activation);
+            }
+        }
+        for spike in &result.spikes {
+            state.activations.insert(spike.node_id, spike.intensity);
+            state.spike_history.push(spike.clone());
             state.total_spikes += 1;
         }
+        state.refractory_until.extend(result.refractory_until.clone());
         
         state.last_update = now;
+        Ok(result)
+    }
+    
+    /// Spikes of a node within `window` of now, oldest first
+    pub async fn recent_spikes(&self, node_id: Uuid, window: Duration) -> Vec<SpikeEvent> {
+        let state = self.neural_state.read().await;
+        let now = self.clock.now();
+        state.spike_history.node_window(&node_id, now - window, now).cloned().collect()
+    }
+    
+    /// All recorded spikes between two times (inclusive), in time order
+    pub async fn spikes_between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<SpikeEvent> {
+        self.neural_state.read().await.spike_history.between(from, to)
+    }
+    
+    /// Spike train of a node over the last `window`
+    pub async fn spike_train(&self, node_id: Uuid, window: Duration) -> SpikeTrain {
+        let state = self.neural_state.read().await;
+        let now = self.clock.now();
+        SpikeTrain::from_history(&state.spike_history, node_id, now - window, now)
+    }
+    
+    /// ISI distribution, firing rates and bursts of a node over the last `window`
+    pub async fn analyze_spike_train(&self, node_id: Uuid, window: Duration) -> Result<SpikeTrainSummary> {
+        let train = self.spike_train(node_id, window).await;
+        self.config.spike_analysis.summarize(&train)
+    }
+    
+    /// Distances, synchrony and cross-correlogram of two nodes over the last `window`
+    pub async fn compare_spike_trains(&self, a: Uuid, b: Uuid, window: Duration) -> Result<SpikeTrainComparison> {
+        let state = self.neural_state.read().await;
+        let now = self.clock.now();
+        let train_a = SpikeTrain::from_history(&state.spike_history, a, now - window, now);
+        let train_b = SpikeTrain::from_history(&state.spike_history, b, now - window, now);
+        self.config.spike_analysis.compare(&train_a, &train_b)
+    }
+    
+    /// Spikes and, if asked for, membrane traces of a set of nodes over a window
+    pub async fn raster(&self, graph: &HyperGraph, query: &RasterQuery) -> Result<Raster> {
+        let samples = query.samples()?;
+        let state = self.neural_state.read().await;
+        
+        let node_ids = if query.node_ids.is_empty() {
+            let mut node_ids: Vec<Uuid> = graph.nodes().keys().copied().collect();
+            node_ids.sort();
+            node_ids
+        } else {
+            query.node_ids.clone()
+        };
+        
+        let mut spikes: Vec<SpikeEvent> = node_ids
+            .iter()
+            .flat_map(|node_id| state.spike_history.node_window(node_id, query.from, query.to))
+            .cloned()
+            .collect();
+        spikes.sort_by_key(|spike| spike.timestamp);
+        
+        let traces = query.membrane_interval.zip(samples).map(|(interval, samples)| {
+            let config = self.simulation_config_for(&state);
+            let potentials = node_ids
+                .iter()
+                .map(|node_id| {
+                    let node_type = graph.get_node(node_id).map_or(DEFAULT_NODE_TYPE, |node| node.node_type.as_str());
+                    let model = config.model_for(node_type);
+                    state.membrane_history.trace(node_id, model.as_ref(), query.from, interval, samples)
+                })
+                .collect();
+            MembraneTraces { interval, potentials }
+        });
+        
+        Ok(Raster {
+            from: query.from,
+            to: query.to,
+            node_ids,
+            spikes,
+            traces,
+        })
+    }
+    
+    /// Write the spikes of a raster query in the given format
+    pub async fn export_spikes(&self, graph: &HyperGraph, query: &RasterQuery, format: ExportFormat, out: impl Write) -> Result<()> {
+        self.raster(graph, query).await?.write_spikes(format, out)?;
+        Ok(())
+    }
+    
+    /// Write the membrane traces of a raster query in the given format
+    pub async fn export_membranes(&self, graph: &HyperGraph, query: &RasterQuery, format: ExportFormat, out: impl Write) -> Result<()> {
+        if query.membrane_interval.is_none() {
+            return Err(anyhow::anyhow!("No membrane sampling interval given"));
+        }
+        self.raster(graph, query).await?.write_membranes(format, out)?;
         Ok(())
     }
     
+    /// Add applied plasticity updates to the record, dropping the oldest past the limit
+    pub async fn record_conductance_updates(&self, updates: &[ConductanceUpdate]) {
+        let limit = self.config.stdp.as_ref().map_or(0, |stdp| stdp.history_limit);
+        let mut state = self.neural_state.write().await;
+        
+        state.conductance_updates.extend_from_slice(updates);
+        let excess = state.conductance_updates.len().saturating_sub(limit);
+        state.conductance_updates.drain(..excess);
+    }
+    
+    /// Conductance changes a reward would cause on the given edges
+    ///
+    /// Edges without eligibility are left out. Empty when reward learning is disabled.
+    pub async fn reward_updates(&self, graph: &HyperGraph, edge_ids: &[Uuid], reward: f64) -> Vec<RewardUpdate> {
+        let Some(config) = &self.config.reward else {
+            return Vec::new();
+        };
+        let state = self.neural_state.read().await;
+        let now = self.clock.now();
+        
+        edge_ids
+            .iter()
+            .filter_map(|edge_id| {
+                let edge = graph.get_edge(edge_id)?;
+                let eligibility = state.eligibility.get(edge_id)?.at(now, config.eligibility_time_constant);
+                let change = config.learning_rate * reward * eligibility;
+                (change != 0.0).then(|| RewardUpdate {
+                    edge_id: *edge_id,
+                    eligibility,
+                    change,
+                    previous: edge.conductance,
+                    conductance: edge.adjusted_conductance(change, config.min_conductance, config.max_conductance),
+                })
+            })
+            .collect()
+    }
+    
+    /// Current eligibility trace of an edge
+    pub async fn eligibility(&self, edge_id: Uuid) -> f64 {
+        let state = self.neural_state.read().await;
+        match (&self.config.reward, state.eligibility.get(&edge_id)) {
+            (Some(config), Some(trace)) => trace.at(self.clock.now(), config.eligibility_time_constant),
+            _ => 0.0,
+        }
+    }
+    
+    /// Recorded plasticity updates, optionally for a single edge
+    pub async fn conductance_updates(&self, edge_id: Option<Uuid>) -> Vec<ConductanceUpdate> {
+        let state = self.neural_state.read().await;
+        state
+            .conductance_updates
+            .iter()
+            .filter(|update| edge_id.is_none_or(|edge_id| update.edge_id == edge_id))
+            .cloned()
+            .collect()
+    }
+    
+    /// Simulation parameters derived from the configuration and neural state
+    pub async fn simulation_config(&self) -> SimulationConfig {
+        let state = self.neural_state.read().await;
+        self.simulation_config_for(&state)
+    }
+    
+    /// Set the neuron parameters used for nodes of a type
+    pub async fn set_neuron_parameters(&self, node_type: &str, parameters: LifParameters) {
+        let mut state = self.neural_state.write().await;
+        state.neuron_parameters.insert(node_type.to_string(), parameters);
+    }
+    
+    /// Neuron parameters in effect for nodes of a type
+    pub async fn neuron_parameters(&self, node_type: &str) -> LifParameters {
+        let state = self.neural_state.read().await;
+        self.simulation_config_for(&state).parameters_for(node_type)
+    }
+    
+    /// Set the coincidence rule for hyperedges of a relationship type
+    pub async fn set_firing_rule(&self, relationship: &str, rule: FiringRule) {
+        let mut state = self.neural_state.write().await;
+        state.firing_rules.insert(relationship.to_string(), rule);
+    }
+    
+    /// Coincidence rule in effect for hyperedges of a relationship type
+    pub async fn firing_rule(&self, relationship: &str) -> Option<FiringRule> {
+        let state = self.neural_state.read().await;
+        self.simulation_config_for(&state).firing_rules.get(relationship).copied()
+    }
+    
+    /// Neuron model in effect for nodes of a type
+    pub async fn neuron_model(&self, node_type: &str) -> Arc<dyn NeuronModel> {
+        let state = self.neural_state.read().await;
+        self.simulation_config_for(&state).model_for(node_type)
+    }
+    
+    /// Membrane potential of a node as of now, after leaking since its last input
+    pub async fn membrane_potential(&self, graph: &HyperGraph, node_id: Uuid) -> Option<f64> {
+        self.membrane_state(graph, node_id).await.map(|membrane| membrane.potential)
+    }
+    
+    /// Full membrane state of a node as of now, integrated through its model
+    pub async fn membrane_state(&self, graph: &HyperGraph, node_id: Uuid) -> Option<MembraneState> {
+        let node = graph.get_node(&node_id)?;
+        let state = self.neural_state.read().await;
+        let model = self.simulation_config_for(&state).model_for(&node.node_type);
+        let now = self.clock.now();
+        
+        let mut membrane = state
+            .membranes
+            .get(&node_id)
+            .copied()
+            .unwrap_or_else(|| model.resting_state(now));
+        let elapsed = (now - membrane.updated_at).num_microseconds().unwrap_or(0) as f64 / 1000.0;
+        model.advance(&mut membrane, elapsed);
+        membrane.updated_at = now;
+        
+        Some(membrane)
+    }
+    
     /// Find similar nodes based on activation patterns and connectivity
     pub async fn find_similar_nodes(
         &self,
@@ -125,7 +470,7 @@ impl SpikeProcessor {
         let target_activation = state.activations.get(&target_node).unwrap_or(&0.0);
         let target_neighbors = graph.get_neighbors(&target_node);
         
-        for (node_id, _node) in graph.nodes() {
+        for node_id in graph.nodes().keys() {
             if *node_id == target_node {
                 continue;
             }
@@ -169,8 +514,137 @@ impl SpikeProcessor {
         Ok(similarities)
     }
     
-    /// Implement stable matching algorithm for goal alignment
-    pub async fn stable_matching(
+    /// Two-sided stable matching, with preferences scored by the configured source
+    pub async fn stable_matching(&self, graph: &HyperGraph, config: &MatchingConfig) -> Result<StableMatching> {
+        let (proposers, acceptors, preferences) = self.matching_preferences(graph, config).await?;
+        Ok(NetworkAnalyzer::gale_shapley(&proposers, &acceptors, &preferences))
+    }
+    
+    /// Many-to-one stable matching where each acceptor takes as many proposers
+    /// as its capacity property allows
+    pub async fn capacitated_matching(
+        &self,
+        graph: &HyperGraph,
+        config: &CapacitatedMatchingConfig,
+    ) -> Result<CapacitatedMatching> {
+        let (proposers, acceptors, preferences) = self.matching_preferences(graph, &config.matching).await?;
+        
+        let quota = |node_id: &Uuid, key: Option<&String>, default: usize| -> Result<usize> {
+            let value = key.and_then(|key| Some((key, graph.get_node(node_id)?.properties.get(key)?)));
+            let Some((key, value)) = value else {
+                return Ok(default);
+            };
+            let number = match value {
+                serde_json::Value::String(text) => text.trim().parse::<f64>().ok(),
+                value => value.as_f64(),
+            };
+            match number {
+                Some(number) if number >= 0.0 && number.fract() == 0.0 && number <= usize::MAX as f64 => {
+                    Ok(number as usize)
+                }
+                _ => Err(anyhow::anyhow!("Property {} of node {} is not a non-negative integer: {}", key, node_id, value)),
+            }
+        };
+        let capacities = acceptors
+            .iter()
+            .map(|acceptor| {
+                let capacity = Capacity {
+                    upper: quota(acceptor, Some(&config.capacity_property), 1)?,
+                    lower: quota(acceptor, config.lower_quota_property.as_ref(), 0)?,
+                };
+                if capacity.lower > capacity.upper {
+                    return Err(anyhow::anyhow!(
+                        "Node {} has a lower quota of {} above its capacity of {}",
+                        acceptor, capacity.lower, capacity.upper
+                    ));
+                }
+                Ok((*acceptor, capacity))
+            })
+            .collect::<Result<Vec<(Uuid, Capacity)>>>()?;
+        
+        Ok(NetworkAnalyzer::hospitals_residents(&proposers, &capacities, &preferences, &config.relationship))
+    }
+    
+    /// Teams of candidates that maximize combined alignment and complementarity
+    pub async fn form_teams(&self, graph: &HyperGraph, config: &TeamConfig) -> Result<Vec<TeamProposal>> {
+        let state = self.neural_state.read().await;
+        
+        let mut candidates: Vec<(Uuid, String)> =
+            graph.filter_nodes(&config.candidates).iter().map(|node| (node.id, node.node_type.clone())).collect();
+        candidates.sort();
+        
+        let mut scores = vec![vec![(0.0, 0.0); candidates.len()]; candidates.len()];
+        for i in 0..candidates.len() {
+            for j in i + 1..candidates.len() {
+                let (node1, node2) = (candidates[i].0, candidates[j].0);
+                let alignment = NetworkAnalyzer::analyze_goal_alignment(graph, node1, node2)?.alignment_score;
+                let complementarity = self.calculate_complementarity(graph, &state, node1, node2).await?;
+                scores[i][j] = (alignment, complementarity);
+                scores[j][i] = (alignment, complementarity);
+            }
+        }
+        
+        NetworkAnalyzer::form_teams(&candidates, &scores, config)
+    }
+    
+    /// Both sides of a matching problem, sorted, and their preferences
+    async fn matching_preferences(
+        &self,
+        graph: &HyperGraph,
+        config: &MatchingConfig,
+    ) -> Result<(Vec<Uuid>, Vec<Uuid>, Preferences)> {
+        let state = self.neural_state.read().await;
+        
+        let mut proposers: Vec<Uuid> = graph.filter_nodes(&config.proposers).iter().map(|node| node.id).collect();
+        proposers.sort();
+        let mut acceptors: Vec<Uuid> = graph
+            .filter_nodes(&config.acceptors)
+            .iter()
+            .map(|node| node.id)
+            .filter(|id| proposers.binary_search(id).is_err())
+            .collect();
+        acceptors.sort();
+        
+        // Every source is symmetric, so both sides rank by the same score
+        let mut proposer_scores: HashMap<Uuid, Vec<(Uuid, f64)>> = HashMap::new();
+        let mut acceptor_scores: HashMap<Uuid, Vec<(Uuid, f64)>> = HashMap::new();
+        for proposer in &proposers {
+            for acceptor in &acceptors {
+                let score = match config.source {
+                    PreferenceSource::Alignment => {
+                        NetworkAnalyzer::analyze_goal_alignment(graph, *proposer, *acceptor)?.alignment_score
+                    }
+                    PreferenceSource::Complementarity => {
+                        self.calculate_complementarity(graph, &state, *proposer, *acceptor).await?
+                    }
+                    PreferenceSource::Conductance => graph
+                        .get_node_edges(proposer)
+                        .iter()
+                        .filter(|edge| edge.node_ids.contains(acceptor))
+                        .map(|edge| edge.conductance)
+                        .sum(),
+                };
+                if score >= config.min_score {
+                    proposer_scores.entry(*proposer).or_default().push((*acceptor, score));
+                    acceptor_scores.entry(*acceptor).or_default().push((*proposer, score));
+                }
+            }
+        }
+        
+        let mut preferences = Preferences::new();
+        for (node_id, scores) in proposer_scores.into_iter().chain(acceptor_scores) {
+            preferences.insert(node_id, scores);
+        }
+        Ok((proposers, acceptors, preferences))
+    }
+    
+    /// Pairs of active nodes whose activation levels and network positions
+    /// complement each other, best first
+    ///
+    /// This is a discovery scan, not a matching: a node may appear in several
+    /// pairs, which is what relationship discovery wants to surface. Use
+    /// `stable_matching` when every node should get at most one partner.
+    pub async fn complementary_pairs(
         &self,
         graph: &HyperGraph,
         max_results: usize,
@@ -185,8 +659,6 @@ impl SpikeProcessor {
             .map(|(&id, &activation)| (id, activation))
             .collect();
         
-        // Simple stable matching implementation
-        // TODO: Implement full Gale-Shapley algorithm for optimal matching
         for i in 0..active_nodes.len() {
             for j in (i+1)..active_nodes.len() {
                 let (node1, _activation1) = active_nodes[i];
@@ -216,49 +688,46 @@ impl SpikeProcessor {
     }
     
     /// Simulate network effect cascade from a node activation
+    ///
+    /// This is a what-if run on a fresh simulator: the neural state is not
+    /// changed. Returns every node that received input with the highest
+    /// activation it reached, in order of first arrival, along with when and
+    /// by which path the first spike got there. Nodes whose net input was
+    /// inhibitory are reported with that negative input instead.
     pub async fn simulate_cascade(
         &self,
         graph: &HyperGraph,
         source_node: Uuid,
         activation_strength: f64,
-    ) -> Result<Vec<(Uuid, f64)>> {
-        let mut cascade_effects = Vec::new();
-        let mut visited = std::collections::HashSet::new();
-        let mut activation_queue = vec![(source_node, activation_strength, 0)];
-        
-        while let Some((current_node, current_activation, depth)) = activation_queue.pop() {
-            if depth >= self.config.max_cascade_depth || visited.contains(&current_node) {
-                continue;
-            }
-            
-            visited.insert(current_node);
-            cascade_effects.push((current_node, current_activation));
-            
-            // Propagate to neighbors
-            let neighbors = graph.get_neighbors(&current_node);
-            for neighbor in neighbors {
-                if !visited.contains(&neighbor.id) {
-                    // Calculate propagated activation based on edge strength
-                    let edges = graph.get_node_edges(&current_node);
-                    let mut max_conductance: f64 = 0.0;
-                    
-                    for edge in edges {
-                        if edge.node_ids.contains(&neighbor.id) {
-                            max_conductance = max_conductance.max(edge.conductance);
-                        }
-                    }
-                    
-                    let propagated_activation = current_activation * max_conductance * self.config.decay_rate;
-                    
-                    if propagated_activation > 0.01 { // Minimum threshold
-                        activation_queue.push((neighbor.id, propagated_activation, depth + 1));
-                    }
-                }
-            }
-        }
-        
-        // Remove the source node from results
-        cascade_effects.retain(|(id, _)| *id != source_node);
+    ) -> Result<Vec<CascadeArrival>> {
+        let state = self.neural_state.read().await;
+        let mut simulator = Simulator::new(graph, self.simulation_config_for(&state), self.clock.now())
+            .with_membranes(&state.membranes)
+            .with_history(&state.spike_history);
+        drop(state);
+        simulator.inject_spike(source_node, activation_strength, 0.0);
+        let result = simulator.run();
+        
+        // Nodes held down by inhibition report their (negative) net input
+        let cascade_effects = result
+            .reached
+            .iter()
+            .filter(|id| **id != source_node)
+            .filter_map(|id| {
+                let path = result.paths.get(id)?;
+                let effect = match result.net_input.get(id) {
+                    Some(input) if *input < 0.0 => *input,
+                    _ => result.peak_potentials[id],
+                };
+                Some(CascadeArrival {
+                    node_id: *id,
+                    effect,
+                    arrival_time: path.time,
+                    path: path.nodes.clone(),
+                    edges: path.edges.clone(),
+                })
+            })
+            .collect();
         
         Ok(cascade_effects)
     }
@@ -302,51 +771,88 @@ impl SpikeProcessor {
     
     /// Apply time-based decay to all neural states
     pub async fn apply_temporal_decay(&self) -> Result<()> {
+        self.decay_activations().await;
+        self.prune().await;
+        
         let mut state = self.neural_state.write().await;
-        let now = Utc::now();
+        state.processing_cycles += 1;
+        state.last_update = self.clock.now();
         
-        // Decay activations
+        Ok(())
+    }
+    
+    /// Decay every activation by the configured rate
+    pub async fn decay_activations(&self) {
+        let mut state = self.neural_state.write().await;
         for activation in state.activations.values_mut() {
             *activation *= self.config.decay_rate;
         }
+    }
+    
+    /// Drop spikes past the retention age, expired refractory periods and
+    /// eligibility traces that have decayed away
+    ///
+    /// Returns the number of spikes and refractory periods removed.
+    pub async fn prune(&self) -> (usize, usize) {
+        let mut state = self.neural_state.write().await;
+        let now = self.clock.now();
         
-        // Clean up old spike history (keep only last hour)
-        let cutoff = now - Duration::hours(1);
-        state.spike_history.retain(|spike| spike.timestamp > cutoff);
+        // An age reaching back past the earliest representable time prunes nothing
+        let cutoff = match self.config.spike_retention.max_age() {
+            Ok(Some(max_age)) => now.checked_sub_signed(max_age),
+            _ => None,
+        };
+        let pruned_spikes = match cutoff {
+            Some(cutoff) => {
+                state.membrane_history.prune(cutoff);
+                state.spike_history.prune(cutoff)
+            }
+            None => 0,
+        };
         
-        // Clean up expired refractory periods
+        let refractory = state.refractory_until.len();
         state.refractory_until.retain(|_, &mut end_time| end_time > now);
+        let expired_refractory = refractory - state.refractory_until.len();
         
-        state.processing_cycles += 1;
-        state.last_update = now;
+        if let Some(reward) = &self.config.reward {
+            state.eligibility.retain(|_, trace| trace.at(now, reward.eligibility_time_constant).abs() > 1e-9);
+        }
         
-        Ok(())
+        (pruned_spikes, expired_refractory)
     }
     
     // Helper methods
     
+    fn simulation_config_for(&self, state: &NeuralState) -> SimulationConfig {
+        let mut config = SimulationConfig {
+            neuron_parameters: state.neuron_parameters.clone(),
+            ..SimulationConfig::from_database_config(&self.config)
+        };
+        config.firing_rules.extend(state.firing_rules.iter().map(|(relationship, rule)| (relationship.clone(), *rule)));
+        config
+    }
+    
     fn calculate_temporal_similarity(&self, state: &NeuralState, node1: Uuid, node2: Uuid) -> f64 {
-        let recent_cutoff = Utc::now() - Duration::minutes(10);
-        
-        let node1_recent_spikes = state.spike_history
-            .iter()
-            .filter(|spike| spike.node_id == node1 && spike.timestamp > recent_cutoff)
-            .count();
+        let now = self.clock.now();
+        let recent_cutoff = now - Duration::minutes(10);
         
-        let node2_recent_spikes = state.spike_history
-            .iter()
-            .filter(|spike| spike.node_id == node2 && spike.timestamp > recent_cutoff)
-            .count();
+        let node1_recent_spikes = state.spike_history.count_since(&node1, recent_cutoff);
+        let node2_recent_spikes = state.spike_history.count_since(&node2, recent_cutoff);
         
         if node1_recent_spikes == 0 && node2_recent_spikes == 0 {
             1.0 // Both inactive - high similarity
         } else if node1_recent_spikes == 0 || node2_recent_spikes == 0 {
             0.0 // One active, one inactive - low similarity
         } else {
-            // Both active - calculate correlation in spike timing
+            // Both active - compare spike counts and spike timing
             let max_spikes = node1_recent_spikes.max(node2_recent_spikes) as f64;
             let min_spikes = node1_recent_spikes.min(node2_recent_spikes) as f64;
-            min_spikes / max_spikes
+            
+            let train1 = SpikeTrain::from_history(&state.spike_history, node1, recent_cutoff, now);
+            let train2 = SpikeTrain::from_history(&state.spike_history, node2, recent_cutoff, now);
+            let synchrony = train1.synchrony(&train2, self.config.spike_analysis.tau);
+            
+            (min_spikes / max_spikes) * 0.5 + synchrony * 0.5
         }
     }
     
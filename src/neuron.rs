@@ -0,0 +1,597 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+
+/// Step used to integrate models without a closed-form solution (ms)
+const INTEGRATION_STEP: f64 = 0.1;
+
+/// How far ahead numerically integrated models are projected (ms)
+const PROJECTION_HORIZON: f64 = 500.0;
+
+/// Dynamics of a single neuron
+///
+/// The simulator only calls into the model at events: it lets the membrane
+/// evolve between inputs with `advance`, applies input with `receive`, and
+/// asks `project` when the neuron will next reach threshold on its own.
+pub trait NeuronModel: Send + Sync + fmt::Debug {
+    /// Membrane at rest
+    fn resting_state(&self, at: DateTime<Utc>) -> MembraneState;
+    
+    /// Let the membrane evolve without input for `dt` milliseconds
+    fn advance(&self, state: &mut MembraneState, dt: f64);
+    
+    /// Deliver synaptic input of the given weight
+    fn receive(&self, state: &mut MembraneState, weight: f64);
+    
+    /// Reset the membrane after a spike
+    fn reset(&self, state: &mut MembraneState);
+    
+    /// Where the membrane is heading without further input
+    fn project(&self, state: &MembraneState) -> Projection;
+    
+    /// Activity reported for a state, also used as the intensity of its spikes
+    fn activation(&self, state: &MembraneState) -> f64 {
+        state.potential
+    }
+    
+    /// Refractory period of the model (ms), or `None` for the configured one
+    fn refractory_period(&self) -> Option<f64> {
+        None
+    }
+    
+    /// Check that the parameters describe a neuron that rests below its
+    /// threshold and can be integrated
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Parameters of a leaky integrate-and-fire neuron
+///
+/// Between inputs the membrane relaxes towards the resting potential with
+/// the membrane time constant. Input either jumps the potential directly
+/// or, with a synaptic time constant, arrives as a decaying current.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LifParameters {
+    /// Potential the membrane relaxes towards
+    pub resting_potential: f64,
+    /// Potential right after a spike
+    pub reset_potential: f64,
+    /// Potential at which the neuron fires
+    pub threshold: f64,
+    /// Leak time constant (ms)
+    pub membrane_time_constant: f64,
+    /// Decay time constant of the input current (ms); 0 applies input instantly
+    pub synaptic_time_constant: f64,
+}
+
+impl LifParameters {
+    /// Default parameters with the given firing threshold
+    pub fn with_threshold(threshold: f64) -> Self {
+        Self {
+            resting_potential: 0.0,
+            reset_potential: 0.0,
+            threshold,
+            membrane_time_constant: 20.0,
+            synaptic_time_constant: 0.0,
+        }
+    }
+    
+    /// Bisect for the threshold crossing within one integration step
+    fn refine_crossing(&self, start: &MembraneState, elapsed: f64, step: f64) -> f64 {
+        let (mut low, mut high) = (0.0, step);
+        for _ in 0..30 {
+            let middle = (low + high) / 2.0;
+            let mut probe = *start;
+            self.advance(&mut probe, middle);
+            if probe.potential >= self.threshold {
+                high = middle;
+            } else {
+                low = middle;
+            }
+        }
+        elapsed + high
+    }
+}
+
+impl NeuronModel for LifParameters {
+    fn resting_state(&self, at: DateTime<Utc>) -> MembraneState {
+        MembraneState::at_rest(self.resting_potential, at)
+    }
+    
+    fn advance(&self, state: &mut MembraneState, dt: f64) {
+        if dt <= 0.0 {
+            return;
+        }
+        
+        let tau_m = self.membrane_time_constant.max(f64::EPSILON);
+        let tau_s = self.synaptic_time_constant;
+        let leak = (-dt / tau_m).exp();
+        let offset = state.potential - self.resting_potential;
+        
+        // Exact solution of dV/dt = -(V - V_rest)/tau_m + I, dI/dt = -I/tau_s
+        let driven = if tau_s <= 0.0 || state.input_current == 0.0 {
+            0.0
+        } else if (tau_m - tau_s).abs() < 1e-9 {
+            state.input_current * dt * leak
+        } else {
+            let synaptic = (-dt / tau_s).exp();
+            state.input_current * tau_m * tau_s / (tau_m - tau_s) * (leak - synaptic)
+        };
+        
+        state.potential = self.resting_potential + offset * leak + driven;
+        if tau_s > 0.0 {
+            state.input_current *= (-dt / tau_s).exp();
+        }
+    }
+    
+    fn receive(&self, state: &mut MembraneState, weight: f64) {
+        if self.synaptic_time_constant <= 0.0 {
+            state.potential += weight;
+        } else {
+            // Scaled so the total charge delivered equals the weight
+            state.input_current += weight / self.synaptic_time_constant;
+        }
+    }
+    
+    fn reset(&self, state: &mut MembraneState) {
+        state.potential = self.reset_potential;
+    }
+    
+    fn validate(&self) -> Result<()> {
+        let potentials = [self.resting_potential, self.reset_potential, self.threshold];
+        if !potentials.iter().all(|potential| potential.is_finite()) {
+            return Err(anyhow!("LIF potentials must be finite"));
+        }
+        if !(self.resting_potential < self.threshold && self.reset_potential < self.threshold) {
+            return Err(anyhow!("LIF resting and reset potentials must lie below the threshold"));
+        }
+        if !(self.membrane_time_constant.is_finite() && self.membrane_time_constant > 0.0) {
+            return Err(anyhow!("LIF membrane time constant must be a positive duration"));
+        }
+        if !(self.synaptic_time_constant.is_finite() && self.synaptic_time_constant >= 0.0) {
+            return Err(anyhow!("LIF synaptic time constant must be a non-negative duration"));
+        }
+        Ok(())
+    }
+    
+    fn project(&self, state: &MembraneState) -> Projection {
+        if state.potential >= self.threshold {
+            return Projection { crossing: Some(0.0), peak: state.potential };
+        }
+        
+        let mut peak = state.potential;
+        
+        // Without a current the potential only relaxes towards rest
+        let no_current = self.synaptic_time_constant <= 0.0 || state.input_current == 0.0;
+        if no_current && self.resting_potential < self.threshold {
+            return Projection { crossing: None, peak };
+        }
+        
+        let fastest = if self.synaptic_time_constant > 0.0 {
+            self.membrane_time_constant.min(self.synaptic_time_constant)
+        } else {
+            self.membrane_time_constant
+        };
+        let step = fastest.max(1e-3) / 20.0;
+        let horizon = 10.0 * self.membrane_time_constant.max(self.synaptic_time_constant);
+        
+        let mut previous = *state;
+        let mut elapsed = 0.0;
+        while elapsed < horizon {
+            let mut next = previous;
+            self.advance(&mut next, step);
+            
+            if next.potential >= self.threshold {
+                let crossing = self.refine_crossing(&previous, elapsed, step);
+                return Projection { crossing: Some(crossing), peak: self.threshold };
+            }
+            
+            // Past the peak of the current the potential only falls
+            if next.potential < previous.potential && next.potential < peak {
+                break;
+            }
+            
+            peak = peak.max(next.potential);
+            previous = next;
+            elapsed += step;
+        }
+        
+        Projection { crossing: None, peak }
+    }
+}
+
+/// Parameters of an Izhikevich neuron
+///
+/// Two variables (potential `v` in mV and recovery `u`) reproduce regular
+/// spiking, bursting, chattering and other firing patterns depending on
+/// `a`, `b`, `c` and `d`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IzhikevichParameters {
+    /// Recovery time scale
+    pub a: f64,
+    /// Sensitivity of recovery to the potential
+    pub b: f64,
+    /// Potential after a spike (mV)
+    pub c: f64,
+    /// Recovery increment after a spike
+    pub d: f64,
+    /// Spike cutoff (mV)
+    pub peak: f64,
+    /// Potential jump per unit of input weight (mV)
+    pub input_gain: f64,
+    /// Absolute refractory period (ms); the reset already makes the model
+    /// relatively refractory
+    pub refractory_period: f64,
+}
+
+impl IzhikevichParameters {
+    /// Regular spiking cortical neuron
+    pub fn regular_spiking() -> Self {
+        Self { a: 0.02, b: 0.2, c: -65.0, d: 8.0, peak: 30.0, input_gain: 100.0, refractory_period: 0.0 }
+    }
+    
+    /// Intrinsically bursting neuron
+    pub fn intrinsically_bursting() -> Self {
+        Self { c: -55.0, d: 4.0, ..Self::regular_spiking() }
+    }
+    
+    /// Chattering neuron (fast rhythmic bursts)
+    pub fn chattering() -> Self {
+        Self { c: -50.0, d: 2.0, ..Self::regular_spiking() }
+    }
+    
+    fn resting_potential(&self) -> f64 {
+        // Stable fixed point of 0.04v² + (5 - b)v + 140 = 0
+        let p = 5.0 - self.b;
+        (-p - (p * p - 4.0 * 0.04 * 140.0).max(0.0).sqrt()) / (2.0 * 0.04)
+    }
+    
+    fn derivatives(&self, state: &MembraneState) -> (f64, f64) {
+        let v = state.potential;
+        let dv = 0.04 * v * v + 5.0 * v + 140.0 - state.recovery;
+        let du = self.a * (self.b * v - state.recovery);
+        (dv, du)
+    }
+}
+
+impl NeuronModel for IzhikevichParameters {
+    fn resting_state(&self, at: DateTime<Utc>) -> MembraneState {
+        let v = self.resting_potential();
+        MembraneState { recovery: self.b * v, ..MembraneState::at_rest(v, at) }
+    }
+    
+    fn advance(&self, state: &mut MembraneState, dt: f64) {
+        integrate(state, dt, self.peak, |state| self.derivatives(state));
+    }
+    
+    fn receive(&self, state: &mut MembraneState, weight: f64) {
+        state.potential += weight * self.input_gain;
+    }
+    
+    fn reset(&self, state: &mut MembraneState) {
+        state.potential = self.c;
+        state.recovery += self.d;
+    }
+    
+    fn validate(&self) -> Result<()> {
+        let values = [self.a, self.b, self.c, self.d, self.peak, self.input_gain, self.refractory_period];
+        if !values.iter().all(|value| value.is_finite()) {
+            return Err(anyhow!("Izhikevich parameters must be finite"));
+        }
+        if self.a <= 0.0 {
+            return Err(anyhow!("Izhikevich recovery time scale a must be positive"));
+        }
+        // Without a real root of 0.04v² + (5 - b)v + 140 there is no resting state
+        if (5.0 - self.b).powi(2) < 4.0 * 0.04 * 140.0 {
+            return Err(anyhow!("Izhikevich b of {} leaves the neuron without a resting state", self.b));
+        }
+        if !(self.resting_potential() < self.peak && self.c < self.peak) {
+            return Err(anyhow!("Izhikevich resting and reset potentials must lie below the peak"));
+        }
+        if self.refractory_period < 0.0 {
+            return Err(anyhow!("Izhikevich refractory period must be a non-negative duration"));
+        }
+        Ok(())
+    }
+    
+    fn project(&self, state: &MembraneState) -> Projection {
+        project_numerically(self, state, self.peak)
+    }
+    
+    fn activation(&self, state: &MembraneState) -> f64 {
+        normalized(state.potential, self.resting_potential(), self.peak)
+    }
+    
+    fn refractory_period(&self) -> Option<f64> {
+        Some(self.refractory_period)
+    }
+}
+
+/// Parameters of an adaptive exponential integrate-and-fire (AdEx) neuron
+///
+/// Potentials are in mV and the adaptation current is expressed as the
+/// potential it would hold the membrane at, so no capacitance is needed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdExParameters {
+    /// Leak reversal potential (mV)
+    pub resting_potential: f64,
+    /// Potential after a spike (mV)
+    pub reset_potential: f64,
+    /// Soft threshold where the exponential term takes over (mV)
+    pub threshold: f64,
+    /// Sharpness of the spike initiation (mV)
+    pub slope_factor: f64,
+    /// Spike cutoff (mV)
+    pub peak: f64,
+    /// Membrane time constant (ms)
+    pub membrane_time_constant: f64,
+    /// Adaptation time constant (ms)
+    pub adaptation_time_constant: f64,
+    /// Subthreshold adaptation coupling
+    pub subthreshold_adaptation: f64,
+    /// Adaptation added by each spike (mV)
+    pub spike_adaptation: f64,
+    /// Potential jump per unit of input weight (mV)
+    pub input_gain: f64,
+    /// Absolute refractory period (ms); the reset already makes the model
+    /// relatively refractory
+    pub refractory_period: f64,
+}
+
+impl Default for AdExParameters {
+    fn default() -> Self {
+        Self {
+            resting_potential: -70.6,
+            reset_potential: -70.6,
+            threshold: -50.4,
+            slope_factor: 2.0,
+            peak: 0.0,
+            membrane_time_constant: 9.4,
+            adaptation_time_constant: 144.0,
+            subthreshold_adaptation: 0.13,
+            spike_adaptation: 2.7,
+            input_gain: 40.0,
+            refractory_period: 0.0,
+        }
+    }
+}
+
+impl AdExParameters {
+    fn derivatives(&self, state: &MembraneState) -> (f64, f64) {
+        let v = state.potential;
+        // Capped so the exponential can't overflow before the spike is cut off
+        let exponent = ((v - self.threshold) / self.slope_factor).min(20.0);
+        let dv = (-(v - self.resting_potential) + self.slope_factor * exponent.exp() - state.recovery)
+            / self.membrane_time_constant;
+        let dw = (self.subthreshold_adaptation * (v - self.resting_potential) - state.recovery)
+            / self.adaptation_time_constant;
+        (dv, dw)
+    }
+}
+
+impl NeuronModel for AdExParameters {
+    fn resting_state(&self, at: DateTime<Utc>) -> MembraneState {
+        MembraneState::at_rest(self.resting_potential, at)
+    }
+    
+    fn advance(&self, state: &mut MembraneState, dt: f64) {
+        integrate(state, dt, self.peak, |state| self.derivatives(state));
+    }
+    
+    fn receive(&self, state: &mut MembraneState, weight: f64) {
+        state.potential += weight * self.input_gain;
+    }
+    
+    fn reset(&self, state: &mut MembraneState) {
+        state.potential = self.reset_potential;
+        state.recovery += self.spike_adaptation;
+    }
+    
+    fn validate(&self) -> Result<()> {
+        let values = [
+            self.resting_potential,
+            self.reset_potential,
+            self.threshold,
+            self.slope_factor,
+            self.peak,
+            self.membrane_time_constant,
+            self.adaptation_time_constant,
+            self.subthreshold_adaptation,
+            self.spike_adaptation,
+            self.input_gain,
+            self.refractory_period,
+        ];
+        if !values.iter().all(|value| value.is_finite()) {
+            return Err(anyhow!("AdEx parameters must be finite"));
+        }
+        if !(self.slope_factor > 0.0 && self.membrane_time_constant > 0.0 && self.adaptation_time_constant > 0.0) {
+            return Err(anyhow!("AdEx slope factor and time constants must be positive"));
+        }
+        if !(self.resting_potential < self.threshold && self.threshold < self.peak && self.reset_potential < self.peak) {
+            return Err(anyhow!("AdEx potentials must rest below the threshold and reset below the peak"));
+        }
+        if self.refractory_period < 0.0 {
+            return Err(anyhow!("AdEx refractory period must be a non-negative duration"));
+        }
+        Ok(())
+    }
+    
+    fn project(&self, state: &MembraneState) -> Projection {
+        project_numerically(self, state, self.peak)
+    }
+    
+    fn activation(&self, state: &MembraneState) -> f64 {
+        normalized(state.potential, self.resting_potential, self.peak)
+    }
+    
+    fn refractory_period(&self) -> Option<f64> {
+        Some(self.refractory_period)
+    }
+}
+
+/// Parameters of a continuous rate unit
+///
+/// The unit integrates input leakily and reports a sigmoid firing rate
+/// instead of discrete spikes. Whenever input leaves its rate at or above
+/// `emit_threshold` it passes the rate on as a graded event. Having emitted,
+/// the unit stays quiet until the next input arrives, so a rate held above
+/// the threshold is passed on once per input rather than continuously.
+/// Rate units use `recovery` as that marker: it is 1 after an emission and
+/// cleared by the next input.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateParameters {
+    /// Time constant of the input integration (ms)
+    pub time_constant: f64,
+    /// Steepness of the sigmoid
+    pub gain: f64,
+    /// Input at which the rate is one half
+    pub midpoint: f64,
+    /// Minimum rate that is passed on to neighbours
+    pub emit_threshold: f64,
+}
+
+impl Default for RateParameters {
+    fn default() -> Self {
+        Self { time_constant: 20.0, gain: 8.0, midpoint: 0.5, emit_threshold: 0.5 }
+    }
+}
+
+impl RateParameters {
+    /// Firing rate for an integrated input, between 0 and 1
+    pub fn rate(&self, input: f64) -> f64 {
+        1.0 / (1.0 + (-self.gain * (input - self.midpoint)).exp())
+    }
+}
+
+impl NeuronModel for RateParameters {
+    fn resting_state(&self, at: DateTime<Utc>) -> MembraneState {
+        MembraneState::at_rest(0.0, at)
+    }
+    
+    fn advance(&self, state: &mut MembraneState, dt: f64) {
+        if dt > 0.0 {
+            state.potential *= (-dt / self.time_constant.max(f64::EPSILON)).exp();
+        }
+    }
+    
+    fn receive(&self, state: &mut MembraneState, weight: f64) {
+        state.potential += weight;
+        state.recovery = 0.0;
+    }
+    
+    fn reset(&self, state: &mut MembraneState) {
+        // The activity carries on; only note that the current rate was passed on
+        state.recovery = 1.0;
+    }
+    
+    fn validate(&self) -> Result<()> {
+        if !(self.time_constant.is_finite() && self.time_constant > 0.0) {
+            return Err(anyhow!("Rate unit time constant must be a positive duration"));
+        }
+        if !(self.gain.is_finite() && self.midpoint.is_finite() && self.emit_threshold.is_finite()) {
+            return Err(anyhow!("Rate unit gain, midpoint and emit threshold must be finite"));
+        }
+        Ok(())
+    }
+    
+    fn project(&self, state: &MembraneState) -> Projection {
+        let emitted = state.recovery > 0.0;
+        let rate = self.rate(state.potential);
+        let crossing = (!emitted && rate >= self.emit_threshold).then_some(0.0);
+        Projection { crossing, peak: state.potential }
+    }
+    
+    fn activation(&self, state: &MembraneState) -> f64 {
+        self.rate(state.potential)
+    }
+}
+
+/// Forward-Euler integration of a two-variable model, stopping at the spike
+/// cutoff or once the state has settled
+fn integrate(
+    state: &mut MembraneState,
+    dt: f64,
+    peak: f64,
+    derivatives: impl Fn(&MembraneState) -> (f64, f64),
+) {
+    let mut remaining = dt;
+    while remaining > 0.0 && state.potential < peak {
+        let step = remaining.min(INTEGRATION_STEP);
+        let (dv, du) = derivatives(state);
+        
+        // Nothing more will happen without input
+        if dv.abs() < 1e-9 && du.abs() < 1e-9 {
+            break;
+        }
+        
+        state.potential += dv * step;
+        state.recovery += du * step;
+        remaining -= step;
+    }
+    state.potential = state.potential.min(peak);
+}
+
+/// Step a numerically integrated model forward to find its next spike
+fn project_numerically(model: &dyn NeuronModel, state: &MembraneState, peak: f64) -> Projection {
+    let mut probe = *state;
+    let mut highest = state.potential;
+    let mut elapsed = 0.0;
+    
+    while elapsed < PROJECTION_HORIZON {
+        if probe.potential >= peak {
+            return Projection { crossing: Some(elapsed), peak };
+        }
+        
+        let before = probe;
+        model.advance(&mut probe, INTEGRATION_STEP);
+        elapsed += INTEGRATION_STEP;
+        highest = highest.max(probe.potential);
+        
+        if probe == before {
+            break;
+        }
+    }
+    
+    Projection { crossing: None, peak: highest }
+}
+
+/// Map a potential onto 0..1 between rest and spike cutoff
+fn normalized(potential: f64, rest: f64, peak: f64) -> f64 {
+    ((potential - rest) / (peak - rest)).clamp(0.0, 1.0)
+}
+
+/// Dynamic state of a neuron's membrane
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MembraneState {
+    pub potential: f64,
+    /// Synaptic current integrated from incoming hyperedges
+    pub input_current: f64,
+    /// Time the state was last brought up to date
+    pub updated_at: DateTime<Utc>,
+    /// Slow recovery or adaptation variable of two-variable models
+    #[serde(default)]
+    pub recovery: f64,
+}
+
+impl MembraneState {
+    /// State at the given potential with no input or recovery
+    pub fn at_rest(potential: f64, at: DateTime<Utc>) -> Self {
+        Self {
+            potential,
+            input_current: 0.0,
+            updated_at: at,
+            recovery: 0.0,
+        }
+    }
+}
+
+/// Forecast of a membrane's trajectory without further input
+#[derive(Debug, Clone, Copy)]
+pub struct Projection {
+    /// Time until the threshold is reached (ms), if it will be
+    pub crossing: Option<f64>,
+    /// Highest potential on the way
+    pub peak: f64,
+}
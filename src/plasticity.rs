@@ -0,0 +1,332 @@
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use anyhow::{anyhow, Result};
+
+use crate::hypergraph::HyperGraph;
+use crate::neural::SpikeEvent;
+
+/// Spike-timing-dependent plasticity on hyperedge conductance
+///
+/// When two members of a hyperedge spike within `window` of each other and
+/// the edge carries spikes from the earlier to the later one, the pair is
+/// causal and the edge is potentiated; if the edge only carries the other
+/// way it is depressed. The size of the change falls off exponentially with
+/// the spike interval, with separate amplitudes and time constants on each
+/// side of the window.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StdpConfig {
+    /// Potentiation for a causal pair at zero interval
+    pub potentiation: f64,
+    /// Depression for an anti-causal pair at zero interval
+    pub depression: f64,
+    /// Decay of potentiation with the interval (ms)
+    pub potentiation_time_constant: f64,
+    /// Decay of depression with the interval (ms)
+    pub depression_time_constant: f64,
+    /// Pairs further apart than this are ignored (ms)
+    pub window: f64,
+    /// Conductance magnitude never drops below this
+    pub min_conductance: f64,
+    /// Conductance magnitude never rises above this
+    pub max_conductance: f64,
+    /// Number of recorded updates kept in the neural state
+    pub history_limit: usize,
+}
+
+impl Default for StdpConfig {
+    fn default() -> Self {
+        Self {
+            potentiation: 0.01,
+            depression: 0.012,
+            potentiation_time_constant: 20.0,
+            depression_time_constant: 20.0,
+            window: 100.0,
+            min_conductance: 0.0,
+            max_conductance: 1.0,
+            history_limit: 10_000,
+        }
+    }
+}
+
+/// A recorded STDP change to one hyperedge
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConductanceUpdate {
+    pub edge_id: Uuid,
+    pub timestamp: DateTime<Utc>,
+    /// Summed potentiation from causal pairs
+    pub potentiation: f64,
+    /// Summed depression from anti-causal pairs
+    pub depression: f64,
+    /// Number of spike pairs that contributed
+    pub pairs: usize,
+    /// Conductance before the update
+    pub previous: f64,
+    /// Conductance after the update, within the configured bounds
+    pub conductance: f64,
+}
+
+impl ConductanceUpdate {
+    /// Net change requested by the learning window, before bounds
+    pub fn change(&self) -> f64 {
+        self.potentiation - self.depression
+    }
+}
+
+/// Check conductance bounds before they reach `f64::clamp`, which panics on
+/// a NaN bound or on `min > max`
+pub(crate) fn validate_conductance_bounds(min: f64, max: f64) -> Result<()> {
+    if !(min.is_finite() && max.is_finite() && 0.0 <= min && min <= max) {
+        return Err(anyhow!("Invalid conductance bounds [{}, {}]", min, max));
+    }
+    Ok(())
+}
+
+impl StdpConfig {
+    pub fn validate(&self) -> Result<()> {
+        let non_negative = |value: f64| value.is_finite() && value >= 0.0;
+        let positive = |value: f64| value.is_finite() && value > 0.0;
+        
+        if !(non_negative(self.potentiation) && non_negative(self.depression)) {
+            return Err(anyhow!("STDP amplitudes must be non-negative"));
+        }
+        if !(positive(self.potentiation_time_constant) && positive(self.depression_time_constant)) {
+            return Err(anyhow!("STDP time constants must be positive durations"));
+        }
+        if !non_negative(self.window) {
+            return Err(anyhow!("STDP window must be a non-negative duration"));
+        }
+        validate_conductance_bounds(self.min_conductance, self.max_conductance)
+    }
+    
+    /// Weight change for a pair of spikes `interval` ms apart (positive when
+    /// the earlier spike can reach the later one)
+    pub fn window_change(&self, interval: f64, causal: bool) -> f64 {
+        if interval <= 0.0 || interval > self.window {
+            return 0.0;
+        }
+        if causal {
+            self.potentiation * (-interval / self.potentiation_time_constant).exp()
+        } else {
+            -self.depression * (-interval / self.depression_time_constant).exp()
+        }
+    }
+    
+    /// Conductance changes caused by new spikes
+    ///
+    /// `earlier` holds spikes that have already been learned from; only pairs
+    /// with at least one spike from `new` are counted, so every pair is
+    /// applied once.
+    pub fn updates(
+        &self,
+        graph: &HyperGraph,
+        earlier: &[SpikeEvent],
+        new: &[SpikeEvent],
+        at: DateTime<Utc>,
+    ) -> Vec<ConductanceUpdate> {
+        let Some(first_new) = new.iter().map(|spike| spike.timestamp).min() else {
+            return Vec::new();
+        };
+        let horizon = first_new - chrono::Duration::microseconds((self.window * 1000.0) as i64);
+        
+        let mut spikes: Vec<(&SpikeEvent, bool)> = earlier
+            .iter()
+            .filter(|spike| spike.timestamp >= horizon)
+            .map(|spike| (spike, false))
+            .chain(new.iter().map(|spike| (spike, true)))
+            .collect();
+        spikes.sort_by_key(|(spike, _)| spike.timestamp);
+        
+        // Edges each node can send spikes along, by target
+        let mut outgoing: HashMap<Uuid, HashMap<Uuid, Vec<Uuid>>> = HashMap::new();
+        let mut carries = |from: Uuid, to: Uuid| -> Vec<Uuid> {
+            outgoing
+                .entry(from)
+                .or_insert_with(|| {
+                    graph
+                        .get_outgoing(&from)
+                        .into_iter()
+                        .map(|(target, edges)| (target, edges.iter().map(|edge| edge.id).collect()))
+                        .collect()
+                })
+                .get(&to)
+                .cloned()
+                .unwrap_or_default()
+        };
+        
+        // Ordered by edge id so the result doesn't depend on hashing
+        let mut totals: BTreeMap<Uuid, (f64, f64, usize)> = BTreeMap::new();
+        for (i, (pre, pre_is_new)) in spikes.iter().enumerate() {
+            for (post, post_is_new) in &spikes[i + 1..] {
+                if !(*pre_is_new || *post_is_new) || pre.node_id == post.node_id {
+                    continue;
+                }
+                
+                let interval = (post.timestamp - pre.timestamp).num_microseconds().unwrap_or(i64::MAX) as f64 / 1000.0;
+                if interval > self.window {
+                    break;
+                }
+                // Simultaneous spikes have no order to learn from
+                if interval <= 0.0 {
+                    continue;
+                }
+                
+                let forward = carries(pre.node_id, post.node_id);
+                for edge_id in &forward {
+                    let total = totals.entry(*edge_id).or_default();
+                    total.0 += self.window_change(interval, true);
+                    total.2 += 1;
+                }
+                for edge_id in carries(post.node_id, pre.node_id) {
+                    if forward.contains(&edge_id) {
+                        continue;
+                    }
+                    let total = totals.entry(edge_id).or_default();
+                    total.1 -= self.window_change(interval, false);
+                    total.2 += 1;
+                }
+            }
+        }
+        
+        totals
+            .into_iter()
+            .filter_map(|(edge_id, (potentiation, depression, pairs))| {
+                let edge = graph.get_edge(&edge_id)?;
+                Some(ConductanceUpdate {
+                    edge_id,
+                    timestamp: at,
+                    potentiation,
+                    depression,
+                    pairs,
+                    previous: edge.conductance,
+                    conductance: edge.adjusted_conductance(
+                        potentiation - depression,
+                        self.min_conductance,
+                        self.max_conductance,
+                    ),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Reward-modulated learning from reported outcomes
+///
+/// Spike pairs that STDP would learn from also leave an eligibility trace
+/// on the edge, which decays with `eligibility_time_constant`. A reported
+/// outcome acts as a reward signal: each eligible edge's conductance moves
+/// by `learning_rate × reward × trace`, so a success strengthens the edges
+/// that were recently active in causal order and a failure weakens them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RewardConfig {
+    pub learning_rate: f64,
+    /// Decay of eligibility traces (ms)
+    pub eligibility_time_constant: f64,
+    /// Conductance magnitude never drops below this
+    pub min_conductance: f64,
+    /// Conductance magnitude never rises above this
+    pub max_conductance: f64,
+}
+
+impl Default for RewardConfig {
+    fn default() -> Self {
+        Self {
+            learning_rate: 5.0,
+            eligibility_time_constant: 3_600_000.0, // one hour
+            min_conductance: 0.0,
+            max_conductance: 1.0,
+        }
+    }
+}
+
+impl RewardConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !(self.learning_rate.is_finite() && self.learning_rate >= 0.0) {
+            return Err(anyhow!("Reward learning rate must be non-negative"));
+        }
+        if !(self.eligibility_time_constant.is_finite() && self.eligibility_time_constant > 0.0) {
+            return Err(anyhow!("Eligibility time constant must be a positive duration"));
+        }
+        validate_conductance_bounds(self.min_conductance, self.max_conductance)
+    }
+}
+
+/// Decaying record of an edge's recent causal activity
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EligibilityTrace {
+    pub value: f64,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl EligibilityTrace {
+    /// Value of the trace at a later time
+    pub fn at(&self, now: DateTime<Utc>, time_constant: f64) -> f64 {
+        let elapsed = (now - self.updated_at).num_microseconds().unwrap_or(i64::MAX) as f64 / 1000.0;
+        self.value * (-elapsed.max(0.0) / time_constant).exp()
+    }
+    
+    /// Decay the trace up to `now` and add to it
+    pub fn add(&mut self, amount: f64, now: DateTime<Utc>, time_constant: f64) {
+        self.value = self.at(now, time_constant) + amount;
+        self.updated_at = now;
+    }
+}
+
+/// What a reported outcome refers to
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutcomeTarget {
+    /// A single hyperedge
+    Edge(Uuid),
+    /// A pair of nodes: the edges joining them, or, if there are none, the
+    /// edges of either node
+    Pair(Uuid, Uuid),
+}
+
+impl OutcomeTarget {
+    /// Edges an outcome is credited to
+    pub fn edges(&self, graph: &HyperGraph) -> Result<Vec<Uuid>> {
+        match self {
+            OutcomeTarget::Edge(edge_id) => {
+                graph
+                    .get_edge(edge_id)
+                    .ok_or_else(|| anyhow!("Edge {} not found", edge_id))?;
+                Ok(vec![*edge_id])
+            }
+            OutcomeTarget::Pair(a, b) => {
+                for node_id in [a, b] {
+                    graph
+                        .get_node(node_id)
+                        .ok_or_else(|| anyhow!("Node {} not found", node_id))?;
+                }
+                
+                let edges_of = |node_id: &Uuid| -> Vec<Uuid> {
+                    graph.get_node_edges(node_id).iter().map(|edge| edge.id).collect()
+                };
+                let of_a = edges_of(a);
+                let of_b = edges_of(b);
+                let joining: Vec<Uuid> = of_a.iter().filter(|id| of_b.contains(id)).copied().collect();
+                
+                let mut edges = if joining.is_empty() { [of_a, of_b].concat() } else { joining };
+                edges.sort();
+                edges.dedup();
+                Ok(edges)
+            }
+        }
+    }
+}
+
+/// A conductance change caused by a reported outcome
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RewardUpdate {
+    pub edge_id: Uuid,
+    /// Eligibility trace of the edge when the outcome arrived
+    pub eligibility: f64,
+    /// Requested change, before bounds
+    pub change: f64,
+    /// Conductance before the update
+    pub previous: f64,
+    /// Conductance after the update
+    pub conductance: f64,
+}
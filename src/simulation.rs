@@ -0,0 +1,733 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use anyhow::{anyhow, Result};
+
+use crate::core::DatabaseConfig;
+use crate::history::SpikeHistory;
+use crate::hypergraph::{HyperEdge, HyperGraph, DEFAULT_NODE_TYPE, DEFAULT_SYNAPTIC_DELAY};
+use crate::neural::SpikeEvent;
+use crate::neuron::{LifParameters, MembraneState, NeuronModel};
+use crate::plasticity::ConductanceUpdate;
+
+/// Arrivals weaker than this are not delivered
+const MIN_ARRIVAL_WEIGHT: f64 = 0.01;
+
+/// Source of the current time for neural processing
+pub trait Clock: Send + Sync + fmt::Debug {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// Wall-clock time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves when told to, for deterministic runs
+#[derive(Debug)]
+pub struct VirtualClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl VirtualClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self {
+            now: Mutex::new(start),
+        }
+    }
+    
+    /// Move the clock forward
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+    
+    /// Jump the clock to a given time
+    pub fn set(&self, time: DateTime<Utc>) {
+        *self.now.lock().unwrap() = time;
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// Parameters for a simulation run
+#[derive(Debug, Clone)]
+pub struct SimulationConfig {
+    /// Firing threshold of node types without their own neuron parameters
+    pub spike_threshold: f64,
+    /// Neuron models per node type
+    pub neuron_models: HashMap<String, Arc<dyn NeuronModel>>,
+    /// LIF parameters per node type, taking precedence over `neuron_models`
+    pub neuron_parameters: HashMap<String, LifParameters>,
+    /// Coincidence detection per relationship type
+    pub firing_rules: HashMap<String, FiringRule>,
+    /// Time after a spike during which arrivals are ignored (ms)
+    pub refractory_period: f64,
+    /// Transmission delay of edges without their own (ms)
+    pub synaptic_delay: f64,
+    /// Attenuation applied to each delivered spike
+    pub decay_rate: f64,
+    /// Arrivals deeper than this are dropped
+    pub max_depth: usize,
+    /// Upper bound on processed arrivals, as a guard against runaway loops
+    pub max_events: usize,
+    /// Stop once virtual time passes this point (ms), if set
+    pub duration: Option<f64>,
+}
+
+impl SimulationConfig {
+    pub fn from_database_config(config: &DatabaseConfig) -> Self {
+        Self {
+            spike_threshold: config.spike_threshold,
+            neuron_models: config.neuron_models.clone(),
+            neuron_parameters: HashMap::new(),
+            firing_rules: config.firing_rules.clone(),
+            refractory_period: config.refractory_period as f64,
+            synaptic_delay: DEFAULT_SYNAPTIC_DELAY,
+            decay_rate: config.decay_rate,
+            max_depth: config.max_cascade_depth,
+            max_events: 100_000,
+            duration: None,
+        }
+    }
+    
+    /// Neuron parameters for a node type
+    pub fn parameters_for(&self, node_type: &str) -> LifParameters {
+        self.neuron_parameters
+            .get(node_type)
+            .cloned()
+            .unwrap_or_else(|| LifParameters::with_threshold(self.spike_threshold))
+    }
+    
+    /// Neuron model for a node type
+    ///
+    /// LIF parameters set for the type win over a configured model; types
+    /// with neither are LIF neurons at the configured threshold.
+    pub fn model_for(&self, node_type: &str) -> Arc<dyn NeuronModel> {
+        if let Some(parameters) = self.neuron_parameters.get(node_type) {
+            return Arc::new(parameters.clone());
+        }
+        self.neuron_models
+            .get(node_type)
+            .cloned()
+            .unwrap_or_else(|| Arc::new(LifParameters::with_threshold(self.spike_threshold)))
+    }
+    
+    /// Firing rule of an edge, if its relationship type has one
+    pub fn firing_rule_for(&self, edge: &HyperEdge) -> Option<&FiringRule> {
+        self.firing_rules.get(&edge.relationship)
+    }
+}
+
+/// Coincidence detection on a hyperedge
+///
+/// An edge whose relationship type has a firing rule no longer relays each
+/// spike on its own. It fires once `threshold` of its members have spiked
+/// within `window` of each other, and then delivers `strength` (scaled by
+/// its conductance) to every member that didn't take part.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FiringRule {
+    /// Number of members that have to spike together (k of n)
+    pub threshold: usize,
+    /// Spikes further apart than this don't count together (ms)
+    pub window: f64,
+    /// Activation delivered to each remaining member
+    pub strength: f64,
+}
+
+impl FiringRule {
+    pub fn validate(&self) -> Result<()> {
+        if self.threshold == 0 {
+            return Err(anyhow!("Firing rule threshold must be at least one member"));
+        }
+        if !(self.window.is_finite() && self.window >= 0.0) {
+            return Err(anyhow!("Firing rule window must be a non-negative duration"));
+        }
+        if !self.strength.is_finite() {
+            return Err(anyhow!("Firing rule strength must be finite"));
+        }
+        Ok(())
+    }
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self::from_database_config(&DatabaseConfig::default())
+    }
+}
+
+/// A spike on its way to a node
+#[derive(Debug, Clone)]
+pub struct SpikeArrival {
+    /// Virtual time of arrival (ms since the start of the run)
+    pub time: f64,
+    pub target: Uuid,
+    pub source: Uuid,
+    pub edge_id: Uuid,
+    pub weight: f64,
+    pub depth: usize,
+}
+
+/// How input first reached a node during a cascade
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CascadeArrival {
+    pub node_id: Uuid,
+    /// Activation the node reached, or its net input when inhibition won
+    pub effect: f64,
+    /// Time of the first arrival (ms after the source fired)
+    pub arrival_time: f64,
+    /// Nodes the delivering spike passed through, from the source to this node
+    pub path: Vec<Uuid>,
+    /// Hyperedges it crossed, one per hop
+    pub edges: Vec<Uuid>,
+}
+
+/// Route of the first spike to reach a node
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArrivalPath {
+    /// Virtual time of arrival (ms since the start of the run)
+    pub time: f64,
+    /// Nodes from the injected source to the node
+    pub nodes: Vec<Uuid>,
+    /// Hyperedges crossed, one per hop
+    pub edges: Vec<Uuid>,
+}
+
+/// An arrival together with the index of the spike that sent it
+type Cause = (SpikeArrival, usize);
+
+/// Something that happens at a point of virtual time
+#[derive(Debug, Clone)]
+enum Event {
+    /// A spike reaches its target
+    Arrival(SpikeArrival, usize),
+    /// A membrane is projected to reach threshold; stale once `version` changes
+    Threshold { node_id: Uuid, version: u64, depth: usize, cause: Option<Cause> },
+}
+
+#[derive(Debug, Clone)]
+struct ScheduledEvent {
+    time: f64,
+    seq: u64,
+    event: Event,
+}
+
+impl PartialEq for ScheduledEvent {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ScheduledEvent {}
+
+impl PartialOrd for ScheduledEvent {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ScheduledEvent {
+    // Reversed so that `BinaryHeap` pops the earliest arrival first;
+    // ties are broken by scheduling order to keep runs reproducible
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .time
+            .total_cmp(&self.time)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+/// Outcome of a simulation run
+#[derive(Debug, Clone)]
+pub struct SimulationResult {
+    /// Every spike fired, in time order
+    pub spikes: Vec<SpikeEvent>,
+    /// Highest activation each node reached (including injected sources)
+    pub peak_potentials: HashMap<Uuid, f64>,
+    /// Signed sum of the input delivered to each node; negative when
+    /// inhibition outweighed excitation
+    pub net_input: HashMap<Uuid, f64>,
+    /// Membrane state of every node that took part, as of the end of the run
+    pub membranes: HashMap<Uuid, MembraneState>,
+    /// Membrane state right after every input and reset, in time order
+    pub membrane_changes: Vec<(Uuid, MembraneState)>,
+    /// When each node that fired leaves its refractory period
+    pub refractory_until: HashMap<Uuid, DateTime<Utc>>,
+    /// Nodes in the order they first received input
+    pub reached: Vec<Uuid>,
+    /// How the first spike reached each node that received one
+    pub paths: HashMap<Uuid, ArrivalPath>,
+    /// Virtual time at which the run started
+    pub start: DateTime<Utc>,
+    /// Virtual time of the last processed event
+    pub end: DateTime<Utc>,
+    pub events_processed: usize,
+    /// Plasticity the spikes caused, filled in by `SpikeProcessor` when STDP is enabled
+    pub conductance_updates: Vec<ConductanceUpdate>,
+}
+
+/// A node's membrane while it takes part in a run
+#[derive(Debug, Clone)]
+struct Neuron {
+    model: Arc<dyn NeuronModel>,
+    membrane: MembraneState,
+    /// Virtual time the membrane was last brought up to date
+    updated: f64,
+    /// Bumped whenever a pending threshold projection becomes stale
+    version: u64,
+}
+
+/// Discrete-event spiking simulator
+///
+/// Spikes are delivered through a priority queue of timestamped arrivals.
+/// Each node integrates its input through the neuron model of its type and
+/// fires when the model reaches threshold, after which it resets and ignores
+/// input for the refractory period. Inhibitory edges deliver negative
+/// weights that push their targets away from threshold. Time is virtual, so
+/// identical inputs always produce identical runs.
+pub struct Simulator<'a> {
+    graph: &'a HyperGraph,
+    config: SimulationConfig,
+    start: DateTime<Utc>,
+    now: f64,
+    seq: u64,
+    queue: BinaryHeap<ScheduledEvent>,
+    neurons: HashMap<Uuid, Neuron>,
+    peak_potentials: HashMap<Uuid, f64>,
+    net_input: HashMap<Uuid, f64>,
+    refractory_until: HashMap<Uuid, f64>,
+    /// When each node last fired, so none fires twice at the same instant
+    last_fired: HashMap<Uuid, f64>,
+    spikes: Vec<SpikeEvent>,
+    membrane_changes: Vec<(Uuid, MembraneState)>,
+    /// Recent member spikes on edges with a firing rule, by edge
+    edge_spikes: HashMap<Uuid, Vec<(Uuid, f64)>>,
+    /// Arrival that triggered each spike (`None` for injected ones)
+    spike_causes: Vec<Option<Cause>>,
+    first_arrivals: HashMap<Uuid, Cause>,
+    reached: Vec<Uuid>,
+    events_processed: usize,
+}
+
+impl<'a> Simulator<'a> {
+    pub fn new(graph: &'a HyperGraph, config: SimulationConfig, start: DateTime<Utc>) -> Self {
+        Self {
+            graph,
+            config,
+            start,
+            now: 0.0,
+            seq: 0,
+            queue: BinaryHeap::new(),
+            neurons: HashMap::new(),
+            peak_potentials: HashMap::new(),
+            net_input: HashMap::new(),
+            refractory_until: HashMap::new(),
+            last_fired: HashMap::new(),
+            spikes: Vec::new(),
+            membrane_changes: Vec::new(),
+            edge_spikes: HashMap::new(),
+            spike_causes: Vec::new(),
+            first_arrivals: HashMap::new(),
+            reached: Vec::new(),
+            events_processed: 0,
+        }
+    }
+    
+    /// Carry over refractory periods that are still running at the start
+    pub fn with_refractory(mut self, refractory_until: &HashMap<Uuid, DateTime<Utc>>) -> Self {
+        for (node_id, until) in refractory_until {
+            let remaining = self.offset_of(*until);
+            if remaining > 0.0 {
+                self.refractory_until.insert(*node_id, remaining);
+            }
+        }
+        self
+    }
+    
+    /// Start from existing membrane states instead of rest
+    pub fn with_membranes(mut self, membranes: &HashMap<Uuid, MembraneState>) -> Self {
+        for (node_id, membrane) in membranes {
+            let Some(model) = self.node_model(node_id) else {
+                continue;
+            };
+            let updated = self.offset_of(membrane.updated_at);
+            self.neurons.insert(*node_id, Neuron {
+                model,
+                membrane: *membrane,
+                updated,
+                version: 0,
+            });
+        }
+        self
+    }
+    
+    /// Let coincidence-detecting edges see spikes from before the start
+    pub fn with_history(mut self, history: &SpikeHistory) -> Self {
+        let Some(window) = self.config.firing_rules.values().map(|rule| rule.window).max_by(f64::total_cmp) else {
+            return self;
+        };
+        
+        let from = self.time_at(-window);
+        let recent: Vec<(Uuid, f64)> = history
+            .between(from, self.start)
+            .into_iter()
+            .map(|spike| (spike.node_id, self.offset_of(spike.timestamp)))
+            .collect();
+        
+        for (node_id, at) in recent {
+            for edge in self.graph.get_node_edges(&node_id) {
+                if let Some(rule) = self.config.firing_rule_for(edge).copied() {
+                    self.observe_member_spike(edge.id, &rule, node_id, at);
+                }
+            }
+        }
+        self
+    }
+    
+    /// Force a node to fire at a virtual time (ms since the start)
+    ///
+    /// Returns false if the node is refractory at that time.
+    pub fn inject_spike(&mut self, node_id: Uuid, intensity: f64, at: f64) -> bool {
+        self.now = self.now.max(at);
+        if self.is_refractory(&node_id, at) {
+            return false;
+        }
+        
+        self.record_input(node_id, intensity);
+        self.fire(node_id, intensity, at, 0, None);
+        true
+    }
+    
+    /// Process arrivals until the queue drains or a limit is hit
+    pub fn run(mut self) -> SimulationResult {
+        while let Some(scheduled) = self.queue.pop() {
+            if self.events_processed >= self.config.max_events {
+                tracing::warn!("Simulation stopped after {} events", self.events_processed);
+                break;
+            }
+            if self.config.duration.is_some_and(|duration| scheduled.time > duration) {
+                break;
+            }
+            
+            self.events_processed += 1;
+            self.now = scheduled.time;
+            match scheduled.event {
+                Event::Arrival(arrival, spike) => self.deliver(arrival, spike),
+                Event::Threshold { node_id, version, depth, cause } => {
+                    self.reach_threshold(node_id, version, scheduled.time, depth, cause)
+                }
+            }
+        }
+        
+        self.finish()
+    }
+    
+    /// Virtual time as an absolute timestamp
+    pub fn time_at(&self, offset: f64) -> DateTime<Utc> {
+        self.start + Duration::microseconds((offset * 1000.0).round() as i64)
+    }
+    
+    // Helper methods
+    
+    fn offset_of(&self, time: DateTime<Utc>) -> f64 {
+        (time - self.start).num_microseconds().unwrap_or(i64::MAX) as f64 / 1000.0
+    }
+    
+    /// Whether a node ignores input at a given time
+    ///
+    /// A node that just fired is refractory for the rest of that instant even
+    /// with a zero refractory period; otherwise a reset that leaves it at
+    /// threshold would fire it again and again without time moving on.
+    fn is_refractory(&self, node_id: &Uuid, at: f64) -> bool {
+        self.refractory_until.get(node_id).is_some_and(|until| at < *until)
+            || self.last_fired.get(node_id) == Some(&at)
+    }
+    
+    fn record_input(&mut self, node_id: Uuid, potential: f64) {
+        let peak = self.peak_potentials.entry(node_id).or_insert_with(|| {
+            self.reached.push(node_id);
+            potential
+        });
+        *peak = peak.max(potential);
+    }
+    
+    fn node_model(&self, node_id: &Uuid) -> Option<Arc<dyn NeuronModel>> {
+        let node = self.graph.get_node(node_id)?;
+        Some(self.config.model_for(&node.node_type))
+    }
+    
+    /// The node's neuron, brought up to date with the given time
+    fn neuron_at(&mut self, node_id: Uuid, at: f64) -> &mut Neuron {
+        let start = self.start;
+        if !self.neurons.contains_key(&node_id) {
+            let model = self
+                .node_model(&node_id)
+                .unwrap_or_else(|| self.config.model_for(DEFAULT_NODE_TYPE));
+            self.neurons.insert(node_id, Neuron {
+                membrane: model.resting_state(start),
+                model,
+                updated: at,
+                version: 0,
+            });
+        }
+        
+        let neuron = self.neurons.get_mut(&node_id).expect("neuron was just inserted");
+        neuron.model.advance(&mut neuron.membrane, at - neuron.updated);
+        neuron.updated = neuron.updated.max(at);
+        neuron
+    }
+    
+    fn record_membrane(&mut self, node_id: Uuid, at: f64) {
+        let mut membrane = self.neurons[&node_id].membrane;
+        membrane.updated_at = self.time_at(at);
+        self.membrane_changes.push((node_id, membrane));
+    }
+    
+    fn schedule(&mut self, time: f64, event: Event) {
+        self.seq += 1;
+        self.queue.push(ScheduledEvent { time, seq: self.seq, event });
+    }
+    
+    fn deliver(&mut self, arrival: SpikeArrival, spike: usize) {
+        if self.is_refractory(&arrival.target, arrival.time) {
+            return;
+        }
+        
+        *self.net_input.entry(arrival.target).or_default() += arrival.weight;
+        self.first_arrivals
+            .entry(arrival.target)
+            .or_insert_with(|| (arrival.clone(), spike));
+        
+        let neuron = self.neuron_at(arrival.target, arrival.time);
+        neuron.model.receive(&mut neuron.membrane, arrival.weight);
+        neuron.version += 1;
+        let (target, time, depth) = (arrival.target, arrival.time, arrival.depth);
+        self.record_membrane(target, time);
+        self.settle(target, time, depth, Some((arrival, spike)));
+    }
+    
+    /// Fire now or schedule the threshold crossing the membrane is heading for
+    fn settle(&mut self, node_id: Uuid, at: f64, depth: usize, cause: Option<Cause>) {
+        let neuron = &self.neurons[&node_id];
+        let activation = neuron.model.activation(&neuron.membrane);
+        let projection = neuron.model.project(&neuron.membrane);
+        let peak = neuron.model.activation(&MembraneState {
+            potential: projection.peak.max(neuron.membrane.potential),
+            ..neuron.membrane
+        });
+        let version = neuron.version;
+        
+        self.record_input(node_id, peak.max(activation));
+        
+        match projection.crossing {
+            Some(delay) if delay <= 0.0 && !self.is_refractory(&node_id, at) => {
+                self.fire(node_id, activation, at, depth, cause);
+            }
+            Some(delay) => self.schedule(at + delay.max(0.0), Event::Threshold {
+                node_id,
+                version,
+                depth,
+                cause,
+            }),
+            None => {}
+        }
+    }
+    
+    fn reach_threshold(&mut self, node_id: Uuid, version: u64, at: f64, depth: usize, cause: Option<Cause>) {
+        let stale = self.neurons.get(&node_id).is_none_or(|neuron| neuron.version != version);
+        if stale || self.is_refractory(&node_id, at) {
+            return;
+        }
+        
+        let neuron = self.neuron_at(node_id, at);
+        let intensity = neuron.model.activation(&neuron.membrane);
+        self.fire(node_id, intensity, at, depth, cause);
+    }
+    
+    fn fire(&mut self, node_id: Uuid, intensity: f64, at: f64, depth: usize, cause: Option<Cause>) {
+        let neuron = self.neuron_at(node_id, at);
+        neuron.model.reset(&mut neuron.membrane);
+        neuron.version += 1;
+        let refractory_period = neuron.model.refractory_period().unwrap_or(self.config.refractory_period);
+        self.record_membrane(node_id, at);
+        
+        self.refractory_until.insert(node_id, at + refractory_period);
+        self.last_fired.insert(node_id, at);
+        self.spikes.push(SpikeEvent {
+            node_id,
+            timestamp: self.time_at(at),
+            intensity,
+            propagation_depth: depth,
+        });
+        let spike = self.spikes.len() - 1;
+        self.spike_causes.push(cause.clone());
+        
+        // Models with intrinsic dynamics can keep firing (e.g. bursts)
+        self.settle(node_id, at, depth, cause);
+        self.detect_coincidences(node_id, at, depth, spike);
+        
+        if depth + 1 >= self.config.max_depth {
+            return;
+        }
+        
+        // Sorted so that simultaneous arrivals are always queued in the same order
+        let mut outgoing: Vec<_> = self
+            .graph
+            .get_outgoing(&node_id)
+            .into_iter()
+            .flat_map(|(target, edges)| edges.into_iter().map(move |edge| (target, edge)))
+            .filter(|(_, edge)| self.config.firing_rule_for(edge).is_none())
+            .collect();
+        outgoing.sort_by_key(|(target, edge)| (*target, edge.id));
+        
+        let sent_at = self.time_at(at);
+        let arrivals: Vec<_> = outgoing
+            .into_iter()
+            .map(|(target, edge)| SpikeArrival {
+                time: at + edge.delay_at(sent_at, self.config.synaptic_delay),
+                target,
+                source: node_id,
+                edge_id: edge.id,
+                weight: intensity * edge.conductance * self.config.decay_rate,
+                depth: depth + 1,
+            })
+            .filter(|arrival| arrival.weight.abs() >= MIN_ARRIVAL_WEIGHT)
+            .collect();
+        
+        for arrival in arrivals {
+            self.schedule(arrival.time, Event::Arrival(arrival, spike));
+        }
+    }
+    
+    /// Record a member's spike on an edge with a firing rule
+    ///
+    /// Returns the members that spiked together once enough of them have,
+    /// after which the edge starts counting afresh.
+    fn observe_member_spike(&mut self, edge_id: Uuid, rule: &FiringRule, node_id: Uuid, at: f64) -> Option<Vec<Uuid>> {
+        let recent = self.edge_spikes.entry(edge_id).or_default();
+        recent.retain(|(member, time)| *member != node_id && at - time <= rule.window);
+        recent.push((node_id, at));
+        
+        if recent.len() < rule.threshold {
+            return None;
+        }
+        Some(recent.drain(..).map(|(member, _)| member).collect())
+    }
+    
+    /// Fire the edges of a spiking node whose firing rule is now met
+    fn detect_coincidences(&mut self, node_id: Uuid, at: f64, depth: usize, spike: usize) {
+        let graph = self.graph;
+        let mut edges: Vec<_> = graph
+            .get_node_edges(&node_id)
+            .into_iter()
+            .filter_map(|edge| Some((edge, *self.config.firing_rule_for(edge)?)))
+            .collect();
+        edges.sort_by_key(|(edge, _)| edge.id);
+        
+        let sent_at = self.time_at(at);
+        let mut arrivals = Vec::new();
+        for (edge, rule) in edges {
+            let Some(coincident) = self.observe_member_spike(edge.id, &rule, node_id, at) else {
+                continue;
+            };
+            if depth + 1 >= self.config.max_depth {
+                continue;
+            }
+            
+            let delay = edge.delay_at(sent_at, self.config.synaptic_delay);
+            arrivals.extend(
+                edge.node_ids
+                    .iter()
+                    .filter(|member| !coincident.contains(member))
+                    .map(|target| SpikeArrival {
+                        time: at + delay,
+                        target: *target,
+                        source: node_id,
+                        edge_id: edge.id,
+                        weight: rule.strength * edge.conductance * self.config.decay_rate,
+                        depth: depth + 1,
+                    }),
+            );
+        }
+        
+        for arrival in arrivals {
+            if arrival.weight.abs() >= MIN_ARRIVAL_WEIGHT {
+                self.schedule(arrival.time, Event::Arrival(arrival, spike));
+            }
+        }
+    }
+    
+    /// Follow the spikes that led to a node's first arrival back to an injected one
+    fn path_to(&self, node_id: Uuid) -> Option<ArrivalPath> {
+        let (first, spike) = self.first_arrivals.get(&node_id)?;
+        let mut nodes = vec![node_id];
+        let mut edges = Vec::new();
+        let (mut arrival, mut spike) = (first, *spike);
+        
+        // A cause always belongs to an earlier spike, so this ends
+        loop {
+            nodes.push(arrival.source);
+            edges.push(arrival.edge_id);
+            match &self.spike_causes[spike] {
+                Some((cause, cause_spike)) => (arrival, spike) = (cause, *cause_spike),
+                None => break,
+            }
+        }
+        
+        nodes.reverse();
+        edges.reverse();
+        Some(ArrivalPath { time: first.time, nodes, edges })
+    }
+    
+    fn finish(mut self) -> SimulationResult {
+        let end = self.now;
+        let node_ids: Vec<Uuid> = self.neurons.keys().copied().collect();
+        let membranes = node_ids
+            .into_iter()
+            .map(|node_id| {
+                let mut membrane = self.neuron_at(node_id, end).membrane;
+                membrane.updated_at = self.time_at(end);
+                (node_id, membrane)
+            })
+            .collect();
+        
+        let refractory_until = self
+            .refractory_until
+            .iter()
+            .map(|(node_id, until)| (*node_id, self.time_at(*until)))
+            .collect();
+        
+        let paths = self
+            .first_arrivals
+            .keys()
+            .filter_map(|node_id| Some((*node_id, self.path_to(*node_id)?)))
+            .collect();
+        
+        SimulationResult {
+            end: self.time_at(end),
+            paths,
+            spikes: self.spikes,
+            peak_potentials: self.peak_potentials,
+            net_input: self.net_input,
+            membranes,
+            membrane_changes: self.membrane_changes,
+            refractory_until,
+            reached: self.reached,
+            start: self.start,
+            events_processed: self.events_processed,
+            conductance_updates: Vec::new(),
+        }
+    }
+}
@@ -1,22 +1,505 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use tokio::fs;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
 use anyhow::{Result, Context};
-use bincode;
 
-use crate::hypergraph::HyperGraph;
+use crate::hypergraph::{json_compat, CascadePolicy, EdgeDelay, EdgeType, EdgeUpdate, HyperGraph, NodeMetadata, NodeUpdate};
+use crate::neural::NeuralState;
+use crate::plasticity::validate_conductance_bounds;
+
+/// Size of the per-record header in the write-ahead log (length + CRC32)
+const WAL_RECORD_HEADER_LEN: usize = 8;
+
+/// Magic bytes at the start of `graph.wal`
+const WAL_MAGIC: &[u8; 4] = b"NGWL";
+
+/// Size of the `graph.wal` header (magic + version)
+const WAL_FILE_HEADER_LEN: usize = 8;
+
+/// Current layout of the `WalEntry` records in `graph.wal`
+///
+/// Records are positional bincode, so any change to `WalEntry` other than
+/// appending a variant needs a bump and a conversion in `replay_wal`. Since
+/// v2 each record payload is the record's sequence number followed by the
+/// entry; v1 payloads are the bare entry.
+pub const WAL_FORMAT_VERSION: u32 = 2;
+
+/// Header written at the start of a fresh write-ahead log
+fn wal_file_header() -> [u8; WAL_FILE_HEADER_LEN] {
+    let mut header = [0; WAL_FILE_HEADER_LEN];
+    header[..4].copy_from_slice(WAL_MAGIC);
+    header[4..].copy_from_slice(&WAL_FORMAT_VERSION.to_le_bytes());
+    header
+}
+
+/// Magic bytes at the start of `graph.bin`
+const GRAPH_MAGIC: &[u8; 4] = b"NGGR";
+
+/// Size of the `graph.bin` header (magic + version + CRC32 + last applied
+/// write-ahead log sequence number)
+///
+/// Before v4 the header stopped after the CRC32, which covered only the
+/// payload; since v4 it covers the sequence number as well.
+const GRAPH_HEADER_LEN: usize = 20;
+
+/// Size of the `graph.bin` header before v4
+const GRAPH_HEADER_LEN_V3: usize = 12;
+
+/// Current on-disk format version of `graph.bin`
+///
+/// Since v3 the payload is MessagePack with named fields, so fields added
+/// to `HyperGraph`, `HyperNode` or `HyperEdge` with `#[serde(default)]`
+/// need no migration. Bump this and register a step in `GRAPH_MIGRATIONS`
+/// for any other change.
+pub const GRAPH_FORMAT_VERSION: u32 = 4;
+
+/// A step that upgrades a graph snapshot payload by one format version
+pub struct Migration {
+    /// Version this step upgrades from (it produces `from_version + 1`)
+    pub from_version: u32,
+    pub description: &'static str,
+    pub upgrade: fn(Vec<u8>) -> Result<Vec<u8>>,
+}
+
+/// Registered graph snapshot migrations, applied in order on load
+pub static GRAPH_MIGRATIONS: &[Migration] = &[
+    Migration {
+        from_version: 0,
+        description: "headerless snapshot",
+        upgrade: reject_pre_header_snapshot,
+    },
+    Migration {
+        from_version: 1,
+        description: "add declared property index list",
+        upgrade: migrate_v1_add_index_definitions,
+    },
+    Migration {
+        from_version: 2,
+        description: "re-encode as MessagePack with named fields",
+        upgrade: migrate_v2_to_named_fields,
+    },
+    Migration {
+        from_version: 3,
+        description: "record the last applied write-ahead log sequence number in the header",
+        upgrade: Ok,
+    },
+];
+
+/// Snapshots from before the header have no known payload layout to upgrade
+fn reject_pre_header_snapshot(_payload: Vec<u8>) -> Result<Vec<u8>> {
+    Err(anyhow::anyhow!("Pre-header snapshots are not supported, export the data with the release that wrote them"))
+}
+
+/// v2 appended `indexed_properties` to `HyperGraph`; older snapshots have none
+fn migrate_v1_add_index_definitions(mut payload: Vec<u8>) -> Result<Vec<u8>> {
+    let no_indexes = std::collections::BTreeSet::<String>::new();
+    payload.extend(bincode::serialize(&no_indexes)?);
+    Ok(payload)
+}
+
+/// v3 stores the same fields by name; edges gain a transmission delay that
+/// the new encoding defaults to the simulation's
+fn migrate_v2_to_named_fields(payload: Vec<u8>) -> Result<Vec<u8>> {
+    let graph: legacy::HyperGraphV2 = bincode::deserialize(&payload)?;
+    Ok(rmp_serde::to_vec_named(&graph)?)
+}
+
+/// Upgrade a payload from `from` to `to` through the registered steps
+fn run_migrations(kind: &str, migrations: &[Migration], from: u32, to: u32, mut payload: Vec<u8>) -> Result<Vec<u8>> {
+    for version in from..to {
+        let migration = migrations
+            .iter()
+            .find(|m| m.from_version == version)
+            .ok_or_else(|| anyhow::anyhow!("No migration registered from {} format version {}", kind, version))?;
+        
+        payload = (migration.upgrade)(payload)
+            .with_context(|| format!("Migration from {} format version {} failed: {}", kind, version, migration.description))?;
+    }
+    
+    Ok(payload)
+}
+
+/// Magic bytes at the start of `neural.bin`
+const NEURAL_MAGIC: &[u8; 4] = b"NGNS";
+
+/// Size of the `neural.bin` header (magic + version + write-ahead log
+/// sequence number of the checkpoint it was saved with)
+const NEURAL_HEADER_LEN: usize = 16;
+
+/// Size of the `neural.bin` header before v4
+const NEURAL_HEADER_LEN_V3: usize = 8;
+
+/// Current on-disk format version of `neural.bin`
+///
+/// Since v3 the payload is MessagePack with named fields, so fields added
+/// to `NeuralState` with `#[serde(default)]` need no migration. Bump this
+/// and register a step in `NEURAL_MIGRATIONS` for any other change.
+pub const NEURAL_FORMAT_VERSION: u32 = 4;
+
+/// Registered neural state migrations, applied in order on load
+pub static NEURAL_MIGRATIONS: &[Migration] = &[
+    Migration {
+        from_version: 1,
+        description: "add neuron parameters and membrane states",
+        upgrade: migrate_neural_v1_add_membranes,
+    },
+    Migration {
+        from_version: 2,
+        description: "re-encode as MessagePack with named fields",
+        upgrade: migrate_neural_v2_to_named_fields,
+    },
+    Migration {
+        from_version: 3,
+        description: "record the write-ahead log sequence number of the checkpoint in the header",
+        upgrade: Ok,
+    },
+];
+
+/// v2 appended `neuron_parameters` and `membranes` to `NeuralState`
+fn migrate_neural_v1_add_membranes(mut payload: Vec<u8>) -> Result<Vec<u8>> {
+    payload.extend(bincode::serialize(&HashMap::<String, legacy::LifParametersV2>::new())?);
+    payload.extend(bincode::serialize(&HashMap::<Uuid, legacy::MembraneStateV2>::new())?);
+    Ok(payload)
+}
+
+/// v3 stores the same fields by name; membranes gain a `recovery` variable
+/// that the new encoding defaults to zero
+fn migrate_neural_v2_to_named_fields(payload: Vec<u8>) -> Result<Vec<u8>> {
+    let state: legacy::NeuralStateV2 = bincode::deserialize(&payload)?;
+    Ok(rmp_serde::to_vec_named(&state)?)
+}
+
+/// Layouts of earlier on-disk formats, frozen for migrations
+mod legacy {
+    use std::collections::{BTreeSet, HashMap, HashSet};
+    use serde::{Deserialize, Serialize};
+    use uuid::Uuid;
+    use chrono::{DateTime, Utc};
+    
+    /// `NeuralState` as of neural format v2 (positional bincode)
+    #[derive(Serialize, Deserialize)]
+    pub struct NeuralStateV2 {
+        pub activations: HashMap<Uuid, f64>,
+        pub spike_history: Vec<SpikeEventV2>,
+        pub refractory_until: HashMap<Uuid, DateTime<Utc>>,
+        pub synaptic_weights: HashMap<(Uuid, Uuid), f64>,
+        pub total_spikes: u64,
+        pub processing_cycles: u64,
+        pub last_update: DateTime<Utc>,
+        pub neuron_parameters: HashMap<String, LifParametersV2>,
+        pub membranes: HashMap<Uuid, MembraneStateV2>,
+    }
+    
+    #[derive(Serialize, Deserialize)]
+    pub struct SpikeEventV2 {
+        pub node_id: Uuid,
+        pub timestamp: DateTime<Utc>,
+        pub intensity: f64,
+        pub propagation_depth: usize,
+    }
+    
+    #[derive(Serialize, Deserialize)]
+    pub struct LifParametersV2 {
+        pub resting_potential: f64,
+        pub reset_potential: f64,
+        pub threshold: f64,
+        pub membrane_time_constant: f64,
+        pub synaptic_time_constant: f64,
+    }
+    
+    #[derive(Serialize, Deserialize)]
+    pub struct MembraneStateV2 {
+        pub potential: f64,
+        pub input_current: f64,
+        pub updated_at: DateTime<Utc>,
+    }
+    
+    /// `HyperGraph` as of graph format v2 (positional bincode)
+    ///
+    /// JSON-typed fields were already stored as JSON text, which is kept as is.
+    #[derive(Serialize, Deserialize)]
+    pub struct HyperGraphV2 {
+        pub nodes: HashMap<Uuid, HyperNodeV2>,
+        pub edges: HashMap<Uuid, HyperEdgeV2>,
+        pub node_to_edges: HashMap<Uuid, HashSet<Uuid>>,
+        pub edge_to_nodes: HashMap<Uuid, Vec<Uuid>>,
+        pub indexed_properties: BTreeSet<String>,
+    }
+    
+    #[derive(Serialize, Deserialize)]
+    pub struct HyperNodeV2 {
+        pub id: Uuid,
+        pub data: String,
+        pub created_at: DateTime<Utc>,
+        pub updated_at: DateTime<Utc>,
+        pub activation_level: f64,
+        pub last_spike_time: Option<DateTime<Utc>>,
+        pub spike_count: u64,
+        pub node_type: String,
+        pub tags: Vec<String>,
+        pub properties: String,
+    }
+    
+    #[derive(Serialize, Deserialize)]
+    pub struct HyperEdgeV2 {
+        pub id: Uuid,
+        pub node_ids: Vec<Uuid>,
+        pub relationship: String,
+        pub strength: f64,
+        pub created_at: DateTime<Utc>,
+        pub updated_at: DateTime<Utc>,
+        pub conductance: f64,
+        pub last_activation: Option<DateTime<Utc>>,
+        pub activation_count: u64,
+        pub edge_type: EdgeTypeV2,
+        pub weight_decay: f64,
+        pub properties: String,
+    }
+    
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    pub enum EdgeTypeV2 {
+        Symmetric,
+        Directional { from: Uuid, to: Vec<Uuid> },
+        Hub { center: Uuid, periphery: Vec<Uuid> },
+        Chain,
+    }
+}
 
 /// Storage engine for persisting hypergraph data
+///
+/// The graph is kept as a snapshot in `graph.bin`. Individual mutations
+/// are appended to `graph.wal` and folded into the snapshot on checkpoint.
+/// Neural state is snapshotted alongside it in `neural.bin`.
 pub struct StorageEngine {
     data_dir: PathBuf,
     graph_file: PathBuf,
+    neural_file: PathBuf,
+    wal_file: PathBuf,
     backup_dir: PathBuf,
+    wal: Mutex<WalWriter>,
+}
+
+/// A single graph mutation recorded in the write-ahead log
+///
+/// Mutations that stamp the graph carry the time they were made (`at`), so
+/// that replay reproduces the same timestamps as the live run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum WalEntry {
+    /// Node without metadata, as logged by older versions
+    AddNode {
+        id: Uuid,
+        #[serde(with = "json_compat")]
+        data: serde_json::Value,
+        at: DateTime<Utc>,
+    },
+    AddHyperedge {
+        id: Uuid,
+        node_ids: Vec<Uuid>,
+        relationship: String,
+        strength: f64,
+        at: DateTime<Utc>,
+    },
+    RemoveNode {
+        id: Uuid,
+        policy: CascadePolicy,
+        at: DateTime<Utc>,
+    },
+    RemoveHyperedge {
+        id: Uuid,
+    },
+    UpdateNode {
+        id: Uuid,
+        update: NodeUpdate,
+        at: DateTime<Utc>,
+    },
+    UpdateHyperedge {
+        id: Uuid,
+        update: EdgeUpdate,
+        at: DateTime<Utc>,
+    },
+    AddNodeWithMetadata {
+        id: Uuid,
+        #[serde(with = "json_compat")]
+        data: serde_json::Value,
+        metadata: NodeMetadata,
+        at: DateTime<Utc>,
+    },
+    SetNodeType {
+        id: Uuid,
+        node_type: String,
+        at: DateTime<Utc>,
+    },
+    EditNodeTags {
+        id: Uuid,
+        add: Vec<String>,
+        remove: Vec<String>,
+        at: DateTime<Utc>,
+    },
+    CreatePropertyIndex {
+        key: String,
+    },
+    DropPropertyIndex {
+        key: String,
+    },
+    AddTypedHyperedge {
+        id: Uuid,
+        node_ids: Vec<Uuid>,
+        relationship: String,
+        strength: f64,
+        edge_type: EdgeType,
+        at: DateTime<Utc>,
+    },
+    /// Plasticity changes to conductances, clamped into `[min, max]`
+    AdjustConductances {
+        changes: Vec<(Uuid, f64)>,
+        min: f64,
+        max: f64,
+        at: DateTime<Utc>,
+    },
+    SetEdgeDelay {
+        id: Uuid,
+        delay: EdgeDelay,
+        at: DateTime<Utc>,
+    },
+    /// Periodic decay of node activations and edge conductances
+    ApplyDecay {
+        decay_rate: f64,
+    },
+}
+
+impl WalEntry {
+    /// Apply the mutation to a graph (used both live and during replay)
+    pub fn apply(&self, graph: &mut HyperGraph) -> Result<()> {
+        match self {
+            WalEntry::AddNode { id, data, at } => {
+                graph.add_node_with_metadata_at(*id, data.clone(), NodeMetadata::default(), *at)
+            }
+            WalEntry::AddHyperedge { id, node_ids, relationship, strength, at } => {
+                graph.add_typed_hyperedge_at(*id, node_ids.clone(), relationship.clone(), *strength, EdgeType::Symmetric, *at)
+            }
+            WalEntry::RemoveNode { id, policy, at } => graph.remove_node_at(id, *policy, *at).map(|_| ()),
+            WalEntry::RemoveHyperedge { id } => graph.remove_hyperedge(id).map(|_| ()),
+            WalEntry::UpdateNode { id, update, at } => graph.update_node_at(id, update.clone(), *at),
+            WalEntry::UpdateHyperedge { id, update, at } => graph.update_hyperedge_at(id, update.clone(), *at),
+            WalEntry::AddNodeWithMetadata { id, data, metadata, at } => {
+                graph.add_node_with_metadata_at(*id, data.clone(), metadata.clone(), *at)
+            }
+            WalEntry::SetNodeType { id, node_type, at } => graph.set_node_type_at(id, node_type.clone(), *at),
+            WalEntry::EditNodeTags { id, add, remove, at } => graph.edit_node_tags_at(id, add, remove, *at),
+            WalEntry::CreatePropertyIndex { key } => {
+                graph.create_property_index(key);
+                Ok(())
+            }
+            WalEntry::DropPropertyIndex { key } => {
+                graph.drop_property_index(key);
+                Ok(())
+            }
+            WalEntry::AddTypedHyperedge { id, node_ids, relationship, strength, edge_type, at } => {
+                graph.add_typed_hyperedge_at(
+                    *id,
+                    node_ids.clone(),
+                    relationship.clone(),
+                    *strength,
+                    edge_type.clone(),
+                    *at,
+                )
+            }
+            WalEntry::AdjustConductances { changes, min, max, at } => {
+                for (edge_id, change) in changes {
+                    graph.adjust_conductance_at(edge_id, *change, *min, *max, *at)?;
+                }
+                Ok(())
+            }
+            WalEntry::SetEdgeDelay { id, delay, at } => graph.set_edge_delay_at(id, delay.clone(), *at),
+            WalEntry::ApplyDecay { decay_rate } => {
+                graph.apply_decay(*decay_rate);
+                Ok(())
+            }
+        }
+    }
+    
+    /// Check that the mutation would apply cleanly, without changing the graph
+    ///
+    /// Entries are logged before they are applied, so anything that fails
+    /// here must never reach the log, where it would fail replay as well.
+    pub fn validate(&self, graph: &HyperGraph) -> Result<()> {
+        let node_exists = |id: &Uuid| {
+            if graph.get_node(id).is_some() { Ok(()) } else { Err(anyhow::anyhow!("Node {} not found", id)) }
+        };
+        let edge_exists = |id: &Uuid| {
+            if graph.get_edge(id).is_some() { Ok(()) } else { Err(anyhow::anyhow!("Edge {} not found", id)) }
+        };
+        
+        match self {
+            WalEntry::AddHyperedge { node_ids, .. } => node_ids.iter().try_for_each(node_exists),
+            WalEntry::AddTypedHyperedge { node_ids, edge_type, .. } => {
+                node_ids.iter().try_for_each(node_exists)?;
+                edge_type.validate(node_ids)
+            }
+            WalEntry::RemoveNode { id, .. }
+            | WalEntry::UpdateNode { id, .. }
+            | WalEntry::SetNodeType { id, .. }
+            | WalEntry::EditNodeTags { id, .. } => node_exists(id),
+            WalEntry::RemoveHyperedge { id } | WalEntry::UpdateHyperedge { id, .. } => edge_exists(id),
+            WalEntry::AdjustConductances { changes, min, max, .. } => {
+                validate_conductance_bounds(*min, *max)?;
+                changes.iter().try_for_each(|(edge_id, change)| {
+                    if !change.is_finite() {
+                        return Err(anyhow::anyhow!("Conductance change for edge {} is not finite", edge_id));
+                    }
+                    edge_exists(edge_id)
+                })
+            }
+            WalEntry::SetEdgeDelay { id, delay, .. } => {
+                delay.validate()?;
+                edge_exists(id)
+            }
+            WalEntry::AddNode { .. }
+            | WalEntry::AddNodeWithMetadata { .. }
+            | WalEntry::CreatePropertyIndex { .. }
+            | WalEntry::DropPropertyIndex { .. }
+            | WalEntry::ApplyDecay { .. } => Ok(()),
+        }
+    }
+    
+    /// Frame the entry as a log record: `[len: u32][crc32: u32][payload]`,
+    /// where the payload is `seq` followed by the entry
+    fn encode_record(&self, seq: u64) -> Result<Vec<u8>> {
+        let payload = bincode::serialize(&(seq, self))
+            .context("Failed to serialize WAL entry")?;
+        
+        let mut record = Vec::with_capacity(WAL_RECORD_HEADER_LEN + payload.len());
+        record.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        record.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+        record.extend_from_slice(&payload);
+        Ok(record)
+    }
+}
+
+/// Append handle for the write-ahead log
+struct WalWriter {
+    file: Option<fs::File>,
+    /// Records written since the last checkpoint
+    pending: usize,
+    /// Whether the log is empty, so the next append has to write the header
+    empty: bool,
+    /// Sequence number of the last record logged or folded into the
+    /// snapshot, once the log has been read
+    last_seq: Option<u64>,
 }
 
 impl StorageEngine {
     pub async fn new<P: AsRef<Path>>(data_dir: P) -> Result<Self> {
         let data_dir = data_dir.as_ref().to_path_buf();
         let graph_file = data_dir.join("graph.bin");
+        let neural_file = data_dir.join("neural.bin");
+        let wal_file = data_dir.join("graph.wal");
         let backup_dir = data_dir.join("backups");
         
         // Create directories if they don't exist
@@ -28,32 +511,38 @@ impl StorageEngine {
         Ok(Self {
             data_dir,
             graph_file,
+            neural_file,
+            wal_file,
             backup_dir,
+            wal: Mutex::new(WalWriter { file: None, pending: 0, empty: false, last_seq: None }),
         })
     }
     
+    /// Directory holding all database files
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+    
     /// Save the hypergraph to persistent storage
-    pub async fn save_graph(&self, graph: &HyperGraph) -> Result<()> {
+    ///
+    /// `wal_seq` is the sequence number of the last write-ahead log record
+    /// the graph includes; replay skips that record and everything before it.
+    pub async fn save_graph(&self, graph: &HyperGraph, wal_seq: u64) -> Result<()> {
         // Serialize the graph
-        let serialized = bincode::serialize(graph)
+        let payload = rmp_serde::to_vec_named(graph)
             .context("Failed to serialize hypergraph")?;
         
-        // Write to a temporary file first
-        let temp_file = self.graph_file.with_extension("tmp");
-        let mut file = fs::File::create(&temp_file).await
-            .context("Failed to create temporary file")?;
-        
-        file.write_all(&serialized).await
-            .context("Failed to write graph data")?;
-        
-        file.sync_all().await
-            .context("Failed to sync file to disk")?;
+        let mut contents = Vec::with_capacity(GRAPH_HEADER_LEN + payload.len());
+        contents.extend_from_slice(GRAPH_MAGIC);
+        contents.extend_from_slice(&GRAPH_FORMAT_VERSION.to_le_bytes());
+        contents.extend_from_slice(&[0; 4]);
+        contents.extend_from_slice(&wal_seq.to_le_bytes());
+        contents.extend_from_slice(&payload);
+        let checksum = crc32fast::hash(&contents[GRAPH_HEADER_LEN_V3..]);
+        contents[8..12].copy_from_slice(&checksum.to_le_bytes());
         
-        // Atomically replace the old file
-        fs::rename(&temp_file, &self.graph_file).await
-            .context("Failed to replace graph file")?;
-        
-        Ok(())
+        Self::write_atomic(&self.graph_file, &contents).await
+            .context("Failed to write graph data")
     }
     
     /// Load the hypergraph from persistent storage
@@ -69,25 +558,480 @@ impl StorageEngine {
         file.read_to_end(&mut contents).await
             .context("Failed to read graph file")?;
         
-        let graph = bincode::deserialize(&contents)
-            .context("Failed to deserialize hypergraph")?;
+        let (graph, version, wal_seq) = Self::decode_graph(&contents)?;
+        
+        // Upgrade in place, keeping the original around as a backup
+        if version < GRAPH_FORMAT_VERSION {
+            let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
+            let backup_file = self.backup_dir.join(format!("graph_{}_pre_v{}.bin", timestamp, GRAPH_FORMAT_VERSION));
+            fs::copy(&self.graph_file, &backup_file).await
+                .context("Failed to back up graph file before migration")?;
+            
+            self.save_graph(&graph, wal_seq).await?;
+            tracing::info!(
+                "Migrated graph snapshot from format version {} to {}",
+                version,
+                GRAPH_FORMAT_VERSION
+            );
+        }
         
         Ok(graph)
     }
     
-    /// Create a backup of the current graph
-    pub async fn backup_graph(&self) -> Result<PathBuf> {
+    /// Decode a graph snapshot, running migrations for older versions
+    ///
+    /// Returns the graph, the format version the snapshot was stored in and
+    /// the last write-ahead log sequence number it includes.
+    fn decode_graph(contents: &[u8]) -> Result<(HyperGraph, u32, u64)> {
+        let (stored_version, wal_seq, mut payload) = if contents.starts_with(GRAPH_MAGIC) {
+            if contents.len() < GRAPH_HEADER_LEN_V3 {
+                return Err(anyhow::anyhow!("Graph snapshot header is truncated"));
+            }
+            
+            let version = u32::from_le_bytes(contents[4..8].try_into()?);
+            let checksum = u32::from_le_bytes(contents[8..12].try_into()?);
+            let header_len = if version < 4 { GRAPH_HEADER_LEN_V3 } else { GRAPH_HEADER_LEN };
+            if contents.len() < header_len {
+                return Err(anyhow::anyhow!("Graph snapshot header is truncated"));
+            }
+            
+            if crc32fast::hash(&contents[GRAPH_HEADER_LEN_V3..]) != checksum {
+                return Err(anyhow::anyhow!("Graph snapshot checksum mismatch, the file is corrupt"));
+            }
+            
+            // Snapshots before v4 don't say which log records they include;
+            // logs were truncated on every checkpoint, so replay all of them
+            let wal_seq = if version < 4 { 0 } else { u64::from_le_bytes(contents[12..20].try_into()?) };
+            (version, wal_seq, contents[header_len..].to_vec())
+        } else {
+            // Snapshots written before the header was introduced
+            (0, 0, contents.to_vec())
+        };
+        
+        if stored_version > GRAPH_FORMAT_VERSION {
+            return Err(anyhow::anyhow!(
+                "Unsupported graph format version {} (this build supports up to {})",
+                stored_version,
+                GRAPH_FORMAT_VERSION
+            ));
+        }
+        
+        payload = run_migrations("graph", GRAPH_MIGRATIONS, stored_version, GRAPH_FORMAT_VERSION, payload)?;
+        
+        let mut graph: HyperGraph = rmp_serde::from_slice(&payload)
+            .context("Failed to deserialize hypergraph")?;
+        graph.rebuild_indexes();
+        
+        Ok((graph, stored_version, wal_seq))
+    }
+    
+    /// Last write-ahead log sequence number included in the snapshot
+    ///
+    /// Only the header is read; `load_graph` verifies the checksum.
+    async fn snapshot_wal_seq(&self) -> Result<u64> {
         if !self.graph_file.exists() {
-            return Err(anyhow::anyhow!("No graph file to backup"));
+            return Ok(0);
+        }
+        
+        let mut header = Vec::with_capacity(GRAPH_HEADER_LEN);
+        fs::File::open(&self.graph_file).await
+            .context("Failed to open graph file")?
+            .take(GRAPH_HEADER_LEN as u64)
+            .read_to_end(&mut header).await
+            .context("Failed to read graph file")?;
+        
+        if header.len() < GRAPH_HEADER_LEN || !header.starts_with(GRAPH_MAGIC) {
+            return Ok(0);
+        }
+        let version = u32::from_le_bytes(header[4..8].try_into()?);
+        if version < 4 {
+            return Ok(0);
+        }
+        Ok(u64::from_le_bytes(header[12..20].try_into()?))
+    }
+    
+    /// Save the neural state (activations, spike history, weights)
+    ///
+    /// `wal_seq` is the sequence number the graph snapshot saved alongside
+    /// it records, so the two can be told apart from another checkpoint's.
+    pub async fn save_neural_state(&self, state: &NeuralState, wal_seq: u64) -> Result<()> {
+        let payload = rmp_serde::to_vec_named(state)
+            .context("Failed to serialize neural state")?;
+        
+        let mut contents = Vec::with_capacity(NEURAL_HEADER_LEN + payload.len());
+        contents.extend_from_slice(NEURAL_MAGIC);
+        contents.extend_from_slice(&NEURAL_FORMAT_VERSION.to_le_bytes());
+        contents.extend_from_slice(&wal_seq.to_le_bytes());
+        contents.extend_from_slice(&payload);
+        
+        Self::write_atomic(&self.neural_file, &contents).await
+            .context("Failed to write neural state")
+    }
+    
+    /// Load the neural state, if one has been saved
+    pub async fn load_neural_state(&self) -> Result<Option<NeuralState>> {
+        if !self.neural_file.exists() {
+            return Ok(None);
+        }
+        
+        let contents = fs::read(&self.neural_file).await
+            .context("Failed to read neural state file")?;
+        
+        Self::decode_neural_state(&self.neural_file, &contents).map(Some)
+    }
+    
+    /// Decode a neural state file, running migrations for older versions
+    fn decode_neural_state(path: &Path, contents: &[u8]) -> Result<NeuralState> {
+        if contents.len() < NEURAL_HEADER_LEN_V3 || &contents[..4] != NEURAL_MAGIC {
+            return Err(anyhow::anyhow!("{:?} is not a neural state file", path));
+        }
+        
+        let version = u32::from_le_bytes(contents[4..8].try_into()?);
+        if version == 0 || version > NEURAL_FORMAT_VERSION {
+            return Err(anyhow::anyhow!(
+                "Unsupported neural state format version {} (this build supports up to {})",
+                version,
+                NEURAL_FORMAT_VERSION
+            ));
+        }
+        
+        let header_len = if version < 4 { NEURAL_HEADER_LEN_V3 } else { NEURAL_HEADER_LEN };
+        if contents.len() < header_len {
+            return Err(anyhow::anyhow!("Neural state header is truncated"));
+        }
+        
+        let payload = run_migrations("neural state", NEURAL_MIGRATIONS, version, NEURAL_FORMAT_VERSION, contents[header_len..].to_vec())?;
+        rmp_serde::from_slice(&payload)
+            .context("Failed to deserialize neural state")
+    }
+    
+    /// Append a mutation to the write-ahead log
+    ///
+    /// Each record is framed as `[len: u32][crc32: u32][payload]`, numbered
+    /// one past the last record, and synced before returning. Returns the
+    /// number of records since the last checkpoint.
+    pub async fn append_wal(&self, entry: &WalEntry) -> Result<usize> {
+        let mut wal = self.wal.lock().await;
+        let seq = match wal.last_seq {
+            Some(seq) => seq,
+            None => {
+                self.read_wal(&mut wal).await?;
+                wal.last_seq.expect("set by read_wal")
+            }
+        } + 1;
+        let record = entry.encode_record(seq)?;
+        
+        if wal.file.is_none() {
+            let file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.wal_file)
+                .await
+                .context("Failed to open write-ahead log")?;
+            wal.empty = file.metadata().await?.len() == 0;
+            wal.file = Some(file);
+        }
+        
+        let mut contents = Vec::with_capacity(WAL_FILE_HEADER_LEN + record.len());
+        if wal.empty {
+            contents.extend_from_slice(&wal_file_header());
+        }
+        contents.extend_from_slice(&record);
+        
+        let file = wal.file.as_mut().expect("WAL file opened above");
+        file.write_all(&contents).await
+            .context("Failed to append to write-ahead log")?;
+        file.sync_data().await
+            .context("Failed to sync write-ahead log")?;
+        
+        wal.empty = false;
+        wal.pending += 1;
+        wal.last_seq = Some(seq);
+        Ok(wal.pending)
+    }
+    
+    /// Read every intact record the snapshot doesn't already include
+    ///
+    /// Replay stops at the first truncated record or checksum mismatch (a
+    /// torn write from a crash); the log is cut back to the last good record
+    /// so that later appends are not hidden behind the damaged tail. A record
+    /// that passes its checksum but doesn't decode is an error, since
+    /// dropping it would lose a mutation that was acknowledged.
+    ///
+    /// Records numbered at or below the snapshot's sequence number were
+    /// folded into it by a checkpoint that crashed before truncating the log,
+    /// and are skipped.
+    pub async fn replay_wal(&self) -> Result<Vec<WalEntry>> {
+        let mut wal = self.wal.lock().await;
+        self.read_wal(&mut wal).await
+    }
+    
+    /// Body of `replay_wal`, also run before the first append so that new
+    /// records are numbered after the existing ones
+    async fn read_wal(&self, wal: &mut WalWriter) -> Result<Vec<WalEntry>> {
+        let snapshot_seq = self.snapshot_wal_seq().await?;
+        
+        if !self.wal_file.exists() {
+            wal.pending = 0;
+            wal.last_seq = Some(snapshot_seq);
+            return Ok(Vec::new());
         }
         
-        let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
-        let backup_file = self.backup_dir.join(format!("graph_{}.bin", timestamp));
+        let contents = fs::read(&self.wal_file).await
+            .context("Failed to read write-ahead log")?;
+        
+        // A log shorter than its header was torn while writing the first record
+        let (version, mut offset) = if contents.len() < WAL_FILE_HEADER_LEN {
+            (WAL_FORMAT_VERSION, 0)
+        } else if contents.starts_with(WAL_MAGIC) {
+            let version = u32::from_le_bytes(contents[4..8].try_into()?);
+            if version == 0 || version > WAL_FORMAT_VERSION {
+                return Err(anyhow::anyhow!(
+                    "Unsupported write-ahead log format version {} (this build supports up to {})",
+                    version,
+                    WAL_FORMAT_VERSION
+                ));
+            }
+            (version, WAL_FILE_HEADER_LEN)
+        } else {
+            return Err(anyhow::anyhow!("{:?} is not a write-ahead log", self.wal_file));
+        };
+        
+        let mut records: Vec<(u64, WalEntry)> = Vec::new();
+        
+        while offset + WAL_RECORD_HEADER_LEN <= contents.len() {
+            let len = u32::from_le_bytes(contents[offset..offset + 4].try_into()?) as usize;
+            let checksum = u32::from_le_bytes(contents[offset + 4..offset + 8].try_into()?);
+            let start = offset + WAL_RECORD_HEADER_LEN;
+            
+            if start + len > contents.len() {
+                break;
+            }
+            
+            let payload = &contents[start..start + len];
+            if crc32fast::hash(payload) != checksum {
+                break;
+            }
+            
+            // v1 records carry no sequence number; they were written after
+            // the snapshot they belong to, so number them on from it
+            let record = if version == 1 {
+                bincode::deserialize(payload).map(|entry| (snapshot_seq + records.len() as u64 + 1, entry))
+            } else {
+                bincode::deserialize(payload)
+            };
+            records.push(record.with_context(|| format!(
+                "Write-ahead log record {} is intact but can't be decoded",
+                records.len()
+            ))?);
+            
+            offset = start + len;
+        }
+        
+        if offset < contents.len() {
+            tracing::warn!(
+                "Discarding {} bytes of damaged write-ahead log after {} records",
+                contents.len() - offset,
+                records.len()
+            );
+            let file = fs::OpenOptions::new()
+                .write(true)
+                .open(&self.wal_file)
+                .await
+                .context("Failed to open write-ahead log")?;
+            file.set_len(offset as u64).await
+                .context("Failed to truncate damaged write-ahead log")?;
+            file.sync_all().await?;
+        }
+        
+        // Rewrite older logs in the current format so appends can follow
+        if version < WAL_FORMAT_VERSION {
+            let mut upgraded = wal_file_header().to_vec();
+            for (seq, entry) in &records {
+                upgraded.extend(entry.encode_record(*seq)?);
+            }
+            Self::write_atomic(&self.wal_file, &upgraded).await
+                .context("Failed to upgrade write-ahead log")?;
+            wal.file = None;
+            tracing::info!(
+                "Migrated write-ahead log from format version {} to {}",
+                version,
+                WAL_FORMAT_VERSION
+            );
+        }
         
-        fs::copy(&self.graph_file, &backup_file).await
+        let last_logged = records.last().map_or(0, |(seq, _)| *seq);
+        let skipped = records.iter().take_while(|(seq, _)| *seq <= snapshot_seq).count();
+        if skipped > 0 {
+            tracing::warn!("Skipping {} write-ahead log records already in the snapshot", skipped);
+        }
+        
+        let entries: Vec<WalEntry> = records.into_iter().skip(skipped).map(|(_, entry)| entry).collect();
+        wal.pending = entries.len();
+        wal.last_seq = Some(last_logged.max(snapshot_seq));
+        Ok(entries)
+    }
+    
+    /// Write a full snapshot of the graph and neural state, then truncate
+    /// the write-ahead log
+    ///
+    /// Callers must make sure no mutations are appended concurrently,
+    /// otherwise they would be dropped from the log without being in the snapshot.
+    /// The snapshot records the last logged sequence number, so a crash
+    /// before the truncation doesn't replay those records a second time.
+    pub async fn checkpoint(&self, graph: &HyperGraph, neural: &NeuralState) -> Result<()> {
+        let mut wal = self.wal.lock().await;
+        self.checkpoint_locked(&mut wal, graph, neural).await
+    }
+    
+    /// Checkpoint, then copy the fresh snapshot into a new backup
+    ///
+    /// The log stays locked throughout, so no other checkpoint can replace
+    /// `graph.bin` or `neural.bin` while they are being copied.
+    pub async fn checkpoint_and_backup(&self, graph: &HyperGraph, neural: &NeuralState) -> Result<PathBuf> {
+        let mut wal = self.wal.lock().await;
+        self.checkpoint_locked(&mut wal, graph, neural).await?;
+        self.backup_snapshot().await
+    }
+    
+    async fn checkpoint_locked(&self, wal: &mut WalWriter, graph: &HyperGraph, neural: &NeuralState) -> Result<()> {
+        if wal.last_seq.is_none() {
+            self.read_wal(wal).await?;
+        }
+        
+        let wal_seq = wal.last_seq.unwrap_or_default();
+        self.save_graph(graph, wal_seq).await?;
+        self.save_neural_state(neural, wal_seq).await?;
+        
+        if let Some(file) = wal.file.as_mut() {
+            file.set_len(0).await
+                .context("Failed to truncate write-ahead log")?;
+            file.sync_all().await?;
+        } else if self.wal_file.exists() {
+            fs::File::create(&self.wal_file).await
+                .context("Failed to truncate write-ahead log")?;
+        }
+        
+        wal.empty = true;
+        wal.pending = 0;
+        Ok(())
+    }
+    
+    /// Number of write-ahead log records not yet folded into a checkpoint
+    pub async fn wal_pending(&self) -> usize {
+        self.wal.lock().await.pending
+    }
+    
+    /// Write a file by way of a synced temporary file and an atomic rename
+    async fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+        let temp_file = path.with_extension("tmp");
+        let mut file = fs::File::create(&temp_file).await
+            .context("Failed to create temporary file")?;
+        
+        file.write_all(contents).await?;
+        
+        file.sync_all().await
+            .context("Failed to sync file to disk")?;
+        
+        fs::rename(&temp_file, path).await
+            .with_context(|| format!("Failed to replace {:?}", path))?;
+        
+        Ok(())
+    }
+    
+    /// Back up the current snapshot
+    ///
+    /// The graph and neural state are copied together into a directory of
+    /// their own, which is renamed into place once both are synced. Callers
+    /// hold the log lock so both files come from the same checkpoint.
+    async fn backup_snapshot(&self) -> Result<PathBuf> {
+        if !self.graph_file.exists() || !self.neural_file.exists() {
+            return Err(anyhow::anyhow!("No snapshot to backup"));
+        }
+        
+        let timestamp = Utc::now().format("%Y%m%d_%H%M%S_%3f");
+        let mut backup = self.backup_dir.join(format!("backup_{}", timestamp));
+        for n in 1.. {
+            if !backup.exists() {
+                break;
+            }
+            backup = self.backup_dir.join(format!("backup_{}_{}", timestamp, n));
+        }
+        let partial = backup.with_extension("tmp");
+        fs::create_dir_all(&partial).await
+            .context("Failed to create backup")?;
+        
+        for source in [&self.graph_file, &self.neural_file] {
+            let target = partial.join(source.file_name().unwrap_or_default());
+            fs::copy(source, &target).await
+                .context("Failed to create backup")?;
+            fs::File::open(&target).await?.sync_all().await
+                .context("Failed to sync backup to disk")?;
+        }
+        
+        fs::rename(&partial, &backup).await
             .context("Failed to create backup")?;
         
-        Ok(backup_file)
+        Ok(backup)
+    }
+    
+    /// Describe a backup (name, total size and creation time)
+    pub async fn backup_info<P: AsRef<Path>>(&self, backup_path: P) -> Result<BackupInfo> {
+        let backup_path = backup_path.as_ref();
+        let metadata = fs::metadata(backup_path).await
+            .with_context(|| format!("Failed to read backup metadata: {:?}", backup_path))?;
+        
+        let name = backup_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        
+        let created_at = metadata
+            .modified()
+            .map(DateTime::<Utc>::from)
+            .unwrap_or_else(|_| Utc::now());
+        
+        let mut size = metadata.len();
+        if metadata.is_dir() {
+            size = 0;
+            let mut entries = fs::read_dir(backup_path).await
+                .with_context(|| format!("Failed to read backup: {:?}", backup_path))?;
+            while let Some(entry) = entries.next_entry().await? {
+                size += entry.metadata().await?.len();
+            }
+        }
+        
+        Ok(BackupInfo {
+            name,
+            path: backup_path.to_path_buf(),
+            size,
+            created_at,
+        })
+    }
+    
+    /// Resolve a backup by name, rejecting anything outside the backup directory
+    pub fn backup_path(&self, name: &str) -> Result<PathBuf> {
+        let is_plain_name = !name.is_empty()
+            && Path::new(name).file_name().is_some_and(|file_name| file_name == name);
+        
+        let path = self.backup_dir.join(name);
+        if !is_plain_name || !Self::is_backup(&path) {
+            return Err(anyhow::anyhow!("Invalid backup name: {}", name));
+        }
+        
+        if !path.exists() {
+            return Err(anyhow::anyhow!("Backup {} not found", name));
+        }
+        
+        Ok(path)
+    }
+    
+    /// Whether a path in the backup directory names a backup: a snapshot
+    /// directory, or a graph file from before neural state was backed up
+    fn is_backup(path: &Path) -> bool {
+        let is_snapshot = path.file_name().is_some_and(|name| name.to_string_lossy().starts_with("backup_"))
+            && path.extension().is_none();
+        is_snapshot || path.extension().is_some_and(|ext| ext == "bin")
     }
     
     /// List all available backups
@@ -98,7 +1042,7 @@ impl StorageEngine {
         
         while let Some(entry) = entries.next_entry().await? {
             let path = entry.path();
-            if path.extension().map_or(false, |ext| ext == "bin") {
+            if Self::is_backup(&path) {
                 backups.push(path);
             }
         }
@@ -107,21 +1051,30 @@ impl StorageEngine {
         Ok(backups)
     }
     
-    /// Restore graph from a backup
-    pub async fn restore_from_backup<P: AsRef<Path>>(&self, backup_path: P) -> Result<HyperGraph> {
+    /// Read the graph and neural state from a backup
+    ///
+    /// Backups holding only a graph file have no neural state to restore.
+    pub async fn restore_from_backup<P: AsRef<Path>>(&self, backup_path: P) -> Result<(HyperGraph, Option<NeuralState>)> {
         let backup_path = backup_path.as_ref();
+        let is_snapshot = backup_path.is_dir();
+        let graph_file = if is_snapshot { backup_path.join("graph.bin") } else { backup_path.to_path_buf() };
         
-        let mut file = fs::File::open(backup_path).await
-            .with_context(|| format!("Failed to open backup file: {:?}", backup_path))?;
+        let contents = fs::read(&graph_file).await
+            .with_context(|| format!("Failed to read backup file: {:?}", graph_file))?;
+        let (graph, _, _) = Self::decode_graph(&contents)
+            .context("Failed to decode backup")?;
         
-        let mut contents = Vec::new();
-        file.read_to_end(&mut contents).await
-            .context("Failed to read backup file")?;
+        if !is_snapshot {
+            return Ok((graph, None));
+        }
         
-        let graph = bincode::deserialize(&contents)
-            .context("Failed to deserialize backup")?;
+        let neural_file = backup_path.join("neural.bin");
+        let contents = fs::read(&neural_file).await
+            .with_context(|| format!("Failed to read backup file: {:?}", neural_file))?;
+        let neural = Self::decode_neural_state(&neural_file, &contents)
+            .context("Failed to decode backup")?;
         
-        Ok(graph)
+        Ok((graph, Some(neural)))
     }
     
     /// Get storage statistics
@@ -132,18 +1085,32 @@ impl StorageEngine {
             0
         };
         
+        let neural_size = if self.neural_file.exists() {
+            fs::metadata(&self.neural_file).await?.len()
+        } else {
+            0
+        };
+        
+        let wal_size = if self.wal_file.exists() {
+            fs::metadata(&self.wal_file).await?.len()
+        } else {
+            0
+        };
+        
         let backups = self.list_backups().await?;
         let backup_count = backups.len();
         
         let mut total_backup_size = 0;
         for backup in backups {
-            if let Ok(metadata) = fs::metadata(&backup).await {
-                total_backup_size += metadata.len();
+            if let Ok(info) = self.backup_info(&backup).await {
+                total_backup_size += info.size;
             }
         }
         
         Ok(StorageStats {
             graph_file_size: graph_size,
+            neural_file_size: neural_size,
+            wal_file_size: wal_size,
             backup_count,
             total_backup_size,
         })
@@ -169,7 +1136,12 @@ impl StorageEngine {
         let mut removed_count = 0;
         
         for backup_path in backups.iter().take(to_remove) {
-            if fs::remove_file(backup_path).await.is_ok() {
+            let removed = if backup_path.is_dir() {
+                fs::remove_dir_all(backup_path).await
+            } else {
+                fs::remove_file(backup_path).await
+            };
+            if removed.is_ok() {
                 removed_count += 1;
             }
         }
@@ -178,9 +1150,19 @@ impl StorageEngine {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct BackupInfo {
+    pub name: String,
+    pub path: PathBuf,
+    pub size: u64,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone)]
 pub struct StorageStats {
     pub graph_file_size: u64,
+    pub neural_file_size: u64,
+    pub wal_file_size: u64,
     pub backup_count: usize,
     pub total_backup_size: u64,
 }
\ No newline at end of file
@@ -1,10 +1,22 @@
-use neurographite::{Database, HyperGraph, DatabaseConfig};
+use neurographite::{
+    Database, HyperGraph, DatabaseConfig, CascadePolicy, NodeUpdate, EdgeUpdate, NodeMetadata,
+    NodeFilter, PropertyQuery, EdgeType, NetworkAnalyzer, SimulationConfig, Simulator, VirtualClock,
+    LifParameters, NeuralState, NeuronModel, IzhikevichParameters, AdExParameters, RateParameters,
+    StdpConfig, RewardConfig, OutcomeTarget, EdgeDelay, FiringRule, MaintenanceConfig, SpikeHistory, SpikeRetention,
+    SpikeEvent, SpikeTrain, AnalysisConfig, FiringPattern, RasterQuery, ExportFormat, Clock, Preferences,
+    MatchingConfig, PreferenceSource, RoommatesOutcome, Capacity, CapacitatedMatchingConfig, ProposedEdge, TeamConfig,
+    TeamRole, CentralityConfig,
+};
+use neurographite::storage::{StorageEngine, WalEntry};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::sync::Arc;
 use uuid::Uuid;
-use tokio;
 
 #[tokio::test]
 async fn test_database_creation() {
-    let db = Database::new("./test_data").await.unwrap();
+    let data_dir = tempfile::tempdir().unwrap();
+    let db = Database::new(data_dir.path()).await.unwrap();
     let stats = db.stats().await;
     
     assert_eq!(stats.node_count, 0);
@@ -13,7 +25,8 @@ async fn test_database_creation() {
 
 #[tokio::test]
 async fn test_node_operations() {
-    let db = Database::new("./test_data").await.unwrap();
+    let data_dir = tempfile::tempdir().unwrap();
+    let db = Database::new(data_dir.path()).await.unwrap();
     
     // Add a node
     let data = serde_json::json!({
@@ -34,7 +47,8 @@ async fn test_node_operations() {
 
 #[tokio::test]
 async fn test_hyperedge_creation() {
-    let db = Database::new("./test_data").await.unwrap();
+    let data_dir = tempfile::tempdir().unwrap();
+    let db = Database::new(data_dir.path()).await.unwrap();
     
     // Create some nodes
     let node1 = db.add_node(serde_json::json!({"name": "Alice"})).await.unwrap();
@@ -56,7 +70,8 @@ async fn test_hyperedge_creation() {
 
 #[tokio::test]
 async fn test_similarity_search() {
-    let db = Database::new("./test_data").await.unwrap();
+    let data_dir = tempfile::tempdir().unwrap();
+    let db = Database::new(data_dir.path()).await.unwrap();
     
     // Create similar nodes
     let similar_data = serde_json::json!({
@@ -73,7 +88,7 @@ async fn test_similarity_search() {
     
     let target_node = db.add_node(similar_data.clone()).await.unwrap();
     let similar_node = db.add_node(similar_data).await.unwrap();
-    let different_node = db.add_node(different_data).await.unwrap();
+    let _different_node = db.add_node(different_data).await.unwrap();
     
     // Add some connections to create structural similarity
     db.connect_nodes(
@@ -92,7 +107,8 @@ async fn test_similarity_search() {
 
 #[tokio::test]
 async fn test_network_effect_simulation() {
-    let db = Database::new("./test_data").await.unwrap();
+    let data_dir = tempfile::tempdir().unwrap();
+    let db = Database::new(data_dir.path()).await.unwrap();
     
     // Create a chain of connected nodes
     let mut nodes = Vec::new();
@@ -116,12 +132,13 @@ async fn test_network_effect_simulation() {
     
     // Should propagate to other nodes in the chain
     assert!(!effects.is_empty());
-    assert!(effects.iter().any(|(id, _)| *id == nodes[1]));
+    assert!(effects.iter().any(|effect| effect.node_id == nodes[1]));
 }
 
 #[tokio::test]
 async fn test_relationship_discovery() {
-    let db = Database::new("./test_data").await.unwrap();
+    let data_dir = tempfile::tempdir().unwrap();
+    let db = Database::new(data_dir.path()).await.unwrap();
     
     // Create complementary nodes
     let investor_data = serde_json::json!({
@@ -143,6 +160,12 @@ async fn test_relationship_discovery() {
     db.connect_nodes(vec![investor_node], "self_activation".to_string(), 0.8).await.unwrap();
     db.connect_nodes(vec![startup_node], "self_activation".to_string(), 0.8).await.unwrap();
     
+    // Give each side its own network so a match between them bridges the two
+    let fund = db.add_node(serde_json::json!({"type": "fund"})).await.unwrap();
+    let accelerator = db.add_node(serde_json::json!({"type": "accelerator"})).await.unwrap();
+    db.connect_nodes(vec![investor_node, fund], "member_of".to_string(), 0.8).await.unwrap();
+    db.connect_nodes(vec![startup_node, accelerator], "member_of".to_string(), 0.8).await.unwrap();
+    
     // Discover relationships
     let relationships = db.discover_relationships(5).await.unwrap();
     
@@ -152,13 +175,15 @@ async fn test_relationship_discovery() {
 
 #[tokio::test]
 async fn test_custom_configuration() {
+    let data_dir = tempfile::tempdir().unwrap();
     let config = DatabaseConfig {
-        data_dir: "./test_custom_data".to_string(),
+        data_dir: data_dir.path().to_string_lossy().to_string(),
         spike_threshold: 0.5,
         decay_rate: 0.95,
         refractory_period: 50,
         max_cascade_depth: 5,
         sync_interval: 30,
+        ..Default::default()
     };
     
     let db = Database::with_config(config.clone()).await.unwrap();
@@ -216,7 +241,8 @@ fn test_hypergraph_direct_operations() {
 
 #[tokio::test]
 async fn test_error_handling() {
-    let db = Database::new("./test_data").await.unwrap();
+    let data_dir = tempfile::tempdir().unwrap();
+    let db = Database::new(data_dir.path()).await.unwrap();
     
     // Test connecting non-existent nodes
     let fake_node1 = Uuid::new_v4();
@@ -235,4 +261,2434 @@ async fn test_error_handling() {
     
     // Should handle gracefully (may return empty results)
     assert!(similarity_result.is_ok());
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_wal_replay_after_restart() {
+    let data_dir = tempfile::tempdir().unwrap();
+    
+    let (node1, node2) = {
+        let db = Database::new(data_dir.path()).await.unwrap();
+        let node1 = db.add_node(serde_json::json!({"name": "Alice"})).await.unwrap();
+        let node2 = db.add_node(serde_json::json!({"name": "Bob"})).await.unwrap();
+        db.connect_nodes(vec![node1, node2], "friends".to_string(), 0.6).await.unwrap();
+        (node1, node2)
+    };
+    
+    // Nothing was checkpointed, so the state lives only in the log
+    assert!(!data_dir.path().join("graph.bin").exists());
+    
+    let db = Database::new(data_dir.path()).await.unwrap();
+    let stats = db.stats().await;
+    assert_eq!(stats.node_count, 2);
+    assert_eq!(stats.edge_count, 1);
+    
+    // Replay folds the log into a fresh snapshot
+    let wal_len = std::fs::metadata(data_dir.path().join("graph.wal")).unwrap().len();
+    assert_eq!(wal_len, 0);
+    
+    let effects = db.simulate_network_effect(node1, 1.0).await.unwrap();
+    assert!(effects.iter().any(|effect| effect.node_id == node2));
+}
+
+#[tokio::test]
+async fn test_periodic_checkpoint_truncates_wal() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let config = DatabaseConfig {
+        data_dir: data_dir.path().to_string_lossy().to_string(),
+        checkpoint_interval: 3,
+        ..Default::default()
+    };
+    
+    let db = Database::with_config(config.clone()).await.unwrap();
+    for i in 0..4 {
+        db.add_node(serde_json::json!({"id": i})).await.unwrap();
+    }
+    
+    // The third record triggered a checkpoint, leaving one record in the log
+    assert!(data_dir.path().join("graph.bin").exists());
+    assert!(std::fs::metadata(data_dir.path().join("graph.wal")).unwrap().len() > 0);
+    drop(db);
+    
+    let db = Database::with_config(config).await.unwrap();
+    assert_eq!(db.stats().await.node_count, 4);
+}
+
+#[tokio::test]
+async fn test_manual_maintenance_tick() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let config = DatabaseConfig {
+        data_dir: data_dir.path().to_string_lossy().to_string(),
+        maintenance: None,
+        ..Default::default()
+    };
+    let clock = Arc::new(VirtualClock::new(chrono::Utc::now()));
+    let db = Database::with_clock(config.clone(), clock.clone()).await.unwrap();
+    
+    let alice = db.add_node(serde_json::json!({"name": "Alice"})).await.unwrap();
+    let bob = db.add_node(serde_json::json!({"name": "Bob"})).await.unwrap();
+    let edge_id = db.connect_nodes(vec![alice, bob], "friends".to_string(), 0.9).await.unwrap();
+    let activation = db.stats().await.average_activation;
+    assert!(activation > 0.0);
+    
+    // Nothing has expired yet
+    let report = db.run_maintenance().await.unwrap();
+    assert_eq!((report.pruned_spikes, report.expired_refractory), (0, 0));
+    assert!(report.decayed && report.checkpointed);
+    assert!((db.get_hyperedge(edge_id).await.unwrap().conductance - 0.9 * 0.99).abs() < 1e-9);
+    assert!((db.stats().await.average_activation - activation * 0.99).abs() < 1e-9);
+    assert_eq!(std::fs::metadata(data_dir.path().join("graph.wal")).unwrap().len(), 0);
+    
+    // Two hours later the spikes and refractory periods are gone
+    clock.advance(chrono::Duration::hours(2));
+    let report = db.run_maintenance().await.unwrap();
+    assert_eq!((report.pruned_spikes, report.expired_refractory), (2, 2));
+    drop(db);
+    
+    // Decay was logged and checkpointed like any other mutation
+    let db = Database::with_clock(config, clock).await.unwrap();
+    assert!((db.get_hyperedge(edge_id).await.unwrap().conductance - 0.9 * 0.99 * 0.99).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_background_maintenance_checkpoints_and_shuts_down() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let config = DatabaseConfig {
+        data_dir: data_dir.path().to_string_lossy().to_string(),
+        sync_interval: 1,
+        maintenance: Some(MaintenanceConfig { decay_interval: 0, prune_interval: 0 }),
+        ..Default::default()
+    };
+    let db = Database::with_config(config).await.unwrap();
+    db.add_node(serde_json::json!({"name": "Alice"})).await.unwrap();
+    let wal = data_dir.path().join("graph.wal");
+    assert!(std::fs::metadata(&wal).unwrap().len() > 0);
+    
+    // The scheduled checkpoint folds the log into the snapshot
+    tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+    assert_eq!(std::fs::metadata(&wal).unwrap().len(), 0);
+    
+    db.add_node(serde_json::json!({"name": "Bob"})).await.unwrap();
+    db.shutdown().await.unwrap();
+    assert_eq!(std::fs::metadata(&wal).unwrap().len(), 0);
+    db.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_scheduled_decay_runs_every_tick() {
+    assert!(DatabaseConfig::default().maintenance.is_none());
+    
+    let data_dir = tempfile::tempdir().unwrap();
+    let config = DatabaseConfig {
+        data_dir: data_dir.path().to_string_lossy().to_string(),
+        sync_interval: 0,
+        maintenance: Some(MaintenanceConfig { decay_interval: 1, prune_interval: 0 }),
+        ..Default::default()
+    };
+    let db = Database::with_config(config).await.unwrap();
+    let alice = db.add_node(serde_json::json!({"name": "Alice"})).await.unwrap();
+    let bob = db.add_node(serde_json::json!({"name": "Bob"})).await.unwrap();
+    let old_edge = db.connect_nodes(vec![alice, bob], "friends".to_string(), 0.9).await.unwrap();
+    let conductance = |edge_id| { let db = &db; async move { db.get_hyperedge(edge_id).await.unwrap().conductance } };
+    
+    // Each tick decays once, even with nothing else going on
+    tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+    assert!((conductance(old_edge).await - 0.9 * 0.99).abs() < 1e-9);
+    
+    // An edge added later only decays from then on
+    let carol = db.add_node(serde_json::json!({"name": "Carol"})).await.unwrap();
+    let dave = db.add_node(serde_json::json!({"name": "Dave"})).await.unwrap();
+    let new_edge = db.connect_nodes(vec![carol, dave], "friends".to_string(), 0.9).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
+    assert!((conductance(old_edge).await - 0.9 * 0.99 * 0.99).abs() < 1e-9);
+    assert!((conductance(new_edge).await - 0.9 * 0.99).abs() < 1e-9);
+    db.shutdown().await.unwrap();
+}
+
+#[test]
+fn test_spike_history_windows() {
+    let start = chrono::Utc::now();
+    let at = |ms: i64| start + chrono::Duration::milliseconds(ms);
+    let spike = |node_id: Uuid, ms: i64| SpikeEvent { node_id, timestamp: at(ms), intensity: 1.0, propagation_depth: 0 };
+    let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+    
+    let mut history = SpikeHistory::new(3);
+    for ms in [0, 20, 10, 30] {
+        history.push(spike(alice, ms));
+    }
+    history.push(spike(bob, 15));
+    
+    // The oldest of Alice's spikes was evicted; late arrivals are kept in order
+    assert_eq!(history.len(), 4);
+    let times: Vec<_> = history.node(&alice).map(|spike| spike.timestamp).collect();
+    assert_eq!(times, vec![at(10), at(20), at(30)]);
+    assert_eq!(history.latest(&alice).unwrap().timestamp, at(30));
+    assert_eq!(history.node_window(&alice, at(15), at(30)).count(), 2);
+    assert_eq!(history.count_since(&alice, at(20)), 2);
+    assert_eq!(history.count_since(&bob, at(20)), 0);
+    
+    let between: Vec<_> = history.between(at(10), at(20)).iter().map(|spike| spike.node_id).collect();
+    assert_eq!(between, vec![alice, bob, alice]);
+    
+    // Stored as a flat list of spikes in time order
+    let json = serde_json::to_value(&history).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 4);
+    let restored: SpikeHistory = serde_json::from_value(json).unwrap();
+    let timeline = |history: &SpikeHistory| -> Vec<_> {
+        history.between(at(0), at(30)).iter().map(|spike| (spike.node_id, spike.timestamp)).collect()
+    };
+    assert_eq!(timeline(&restored), timeline(&history));
+    
+    assert_eq!(history.prune(at(15)), 2);
+    history.remove_node(&bob);
+    assert_eq!(history.len(), 2);
+    history.set_capacity(1);
+    assert_eq!(history.node(&alice).next().unwrap().timestamp, at(30));
+}
+
+#[tokio::test]
+async fn test_spike_retention() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let config = DatabaseConfig {
+        data_dir: data_dir.path().to_string_lossy().to_string(),
+        maintenance: None,
+        spike_retention: SpikeRetention { max_per_node: 2, max_age: Some(60) },
+        ..Default::default()
+    };
+    let start = chrono::Utc::now();
+    let clock = Arc::new(VirtualClock::new(start));
+    
+    let alice = {
+        let db = Database::with_clock(config.clone(), clock.clone()).await.unwrap();
+        let alice = db.add_node(serde_json::json!({"name": "Alice"})).await.unwrap();
+        for _ in 0..3 {
+            clock.advance(chrono::Duration::seconds(1));
+            db.stimulate(vec![alice], 1.0).await.unwrap();
+        }
+        
+        // Only the two latest spikes are kept
+        let spikes = db.recent_spikes(alice, chrono::Duration::minutes(1)).await;
+        let times: Vec<_> = spikes.iter().map(|spike| spike.timestamp).collect();
+        assert_eq!(times, vec![start + chrono::Duration::seconds(2), start + chrono::Duration::seconds(3)]);
+        assert_eq!(db.recent_spikes(alice, chrono::Duration::milliseconds(500)).await.len(), 1);
+        assert_eq!(db.spikes_between(start, start + chrono::Duration::seconds(2)).await.len(), 1);
+        db.checkpoint().await.unwrap();
+        alice
+    };
+    
+    // The history survives a restart, and spikes past the retention age are pruned
+    let db = Database::with_clock(config.clone(), clock.clone()).await.unwrap();
+    assert_eq!(db.recent_spikes(alice, chrono::Duration::minutes(1)).await.len(), 2);
+    clock.advance(chrono::Duration::milliseconds(59_500));
+    assert_eq!(db.run_maintenance().await.unwrap().pruned_spikes, 1);
+    assert_eq!(db.recent_spikes(alice, chrono::Duration::minutes(1)).await.len(), 1);
+    drop(db);
+    
+    // Ages that don't fit in a duration are rejected up front
+    for max_age in [u64::MAX, i64::MAX as u64] {
+        let retention = SpikeRetention { max_per_node: 2, max_age: Some(max_age) };
+        assert!(retention.validate().is_err());
+        let config = DatabaseConfig { spike_retention: retention, ..config.clone() };
+        assert!(Database::with_clock(config, clock.clone()).await.is_err());
+    }
+    
+    // One reaching back before the earliest representable time prunes nothing
+    let retention = SpikeRetention { max_per_node: 2, max_age: Some(i64::MAX as u64 / 1000) };
+    assert!(retention.validate().is_ok());
+    let db = Database::with_clock(DatabaseConfig { spike_retention: retention, ..config }, clock.clone()).await.unwrap();
+    assert_eq!(db.run_maintenance().await.unwrap().pruned_spikes, 0);
+    assert_eq!(db.recent_spikes(alice, chrono::Duration::minutes(1)).await.len(), 1);
+}
+
+#[test]
+fn test_spike_train_measures() {
+    let start = chrono::Utc::now();
+    let train = |times: &[f64], duration: f64| SpikeTrain::new(Uuid::new_v4(), start, duration, times.to_vec());
+    let config = AnalysisConfig::default();
+    
+    // Regular firing
+    let tonic = train(&[0.0, 50.0, 100.0, 150.0, 200.0], 250.0);
+    let isi = tonic.isi_distribution(10.0).unwrap();
+    assert_eq!((isi.count, isi.mean, isi.cv), (4, 50.0, 0.0));
+    assert_eq!(isi.histogram[5], 4);
+    assert!((tonic.mean_rate() - 20.0).abs() < 1e-9);
+    let rates: Vec<_> = tonic.firing_rates(100.0, 100.0).unwrap().iter().map(|point| (point.time, point.rate)).collect();
+    assert_eq!(rates, vec![(0.0, 20.0), (100.0, 20.0), (200.0, 20.0)]);
+    assert_eq!(tonic.pattern(&config.bursts), FiringPattern::Tonic);
+    
+    let bursting = train(&[0.0, 2.0, 4.0, 6.0, 100.0, 300.0, 302.0, 304.0], 400.0);
+    let bursts = bursting.bursts(&config.bursts);
+    assert_eq!(bursts.len(), 2);
+    assert_eq!((bursts[0].start, bursts[0].end, bursts[0].spikes), (0.0, 6.0, 4));
+    assert_eq!(bursts[1].spikes, 3);
+    assert_eq!(bursting.pattern(&config.bursts), FiringPattern::Bursting);
+    
+    let irregular = train(&[0.0, 1000.0, 1020.0, 3000.0, 3015.0], 4000.0);
+    assert_eq!(irregular.pattern(&config.bursts), FiringPattern::Irregular);
+    assert_eq!(train(&[5.0], 10.0).pattern(&config.bursts), FiringPattern::Silent);
+    
+    // Distances
+    let single = train(&[0.0], 100.0);
+    let empty = train(&[], 100.0);
+    let shifted = train(&[5.0], 100.0);
+    assert_eq!(tonic.victor_purpura(&tonic, 0.1), 0.0);
+    assert_eq!(single.victor_purpura(&empty, 0.1), 1.0);
+    assert!((single.victor_purpura(&shifted, 0.1) - 0.5).abs() < 1e-9);
+    assert_eq!(single.victor_purpura(&shifted, 1.0), 2.0);
+    assert!(tonic.van_rossum(&tonic, 20.0).abs() < 1e-9);
+    assert!((single.van_rossum(&empty, 20.0) - 0.5f64.sqrt()).abs() < 1e-9);
+    assert!((tonic.synchrony(&tonic, 20.0) - 1.0).abs() < 1e-9);
+    assert!(single.synchrony(&shifted, 20.0) > single.synchrony(&train(&[50.0], 100.0), 20.0));
+    
+    // The second train follows the first by 7 ms
+    let leader = train(&[0.0, 100.0], 200.0);
+    let follower = train(&[7.0, 107.0], 200.0);
+    let correlogram = leader.cross_correlogram(&follower, 20.0, 5.0).unwrap();
+    assert_eq!(correlogram.counts.len(), 8);
+    assert_eq!(correlogram.counts.iter().sum::<usize>(), 2);
+    assert_eq!(correlogram.peak_lag(), Some(7.5));
+    assert_eq!(config.compare(&follower, &leader).unwrap().peak_lag, Some(-7.5));
+    
+    // Bins, lags and time constants must be positive and finite
+    assert!(tonic.isi_distribution(0.0).is_err());
+    assert!(tonic.isi_distribution(f64::NAN).is_err());
+    assert!(leader.cross_correlogram(&follower, -1.0, 5.0).is_err());
+    assert!(leader.cross_correlogram(&follower, 20.0, f64::INFINITY).is_err());
+    let bad_tau = AnalysisConfig { tau: 0.0, ..AnalysisConfig::default() };
+    assert!(bad_tau.validate().is_err());
+    assert!(bad_tau.compare(&leader, &follower).is_err());
+    assert!(AnalysisConfig { bin_width: -5.0, ..AnalysisConfig::default() }.summarize(&tonic).is_err());
+    for config in [
+        AnalysisConfig { rate_window: f64::NAN, ..AnalysisConfig::default() },
+        AnalysisConfig { rate_step: 0.0, ..AnalysisConfig::default() },
+        AnalysisConfig { shift_cost: -0.1, ..AnalysisConfig::default() },
+        AnalysisConfig { shift_cost: f64::INFINITY, ..AnalysisConfig::default() },
+    ] {
+        assert!(config.validate().is_err());
+    }
+    assert!(AnalysisConfig { shift_cost: 0.0, ..AnalysisConfig::default() }.validate().is_ok());
+    
+    // Bins and rate points are capped rather than allocated without bound
+    assert!(tonic.isi_distribution(1e-12).is_err());
+    assert!(leader.cross_correlogram(&follower, 20.0, 1e-12).is_err());
+    assert!(tonic.firing_rates(100.0, 1e-12).is_err());
+    assert!(tonic.firing_rates(f64::NAN, 100.0).is_err());
+    assert_eq!(tonic.firing_rates(100.0, 0.01).unwrap().len(), 25_000);
+}
+
+#[tokio::test]
+async fn test_spike_train_analysis() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let config = DatabaseConfig {
+        data_dir: data_dir.path().to_string_lossy().to_string(),
+        maintenance: None,
+        ..Default::default()
+    };
+    let clock = Arc::new(VirtualClock::new(chrono::Utc::now()));
+    let db = Database::with_clock(config, clock.clone()).await.unwrap();
+    
+    let mut ids = Vec::new();
+    for name in ["Alice", "Bob", "Carol"] {
+        ids.push(db.add_node(serde_json::json!({"name": name})).await.unwrap());
+    }
+    let (alice, bob, carol) = (ids[0], ids[1], ids[2]);
+    
+    // Alice and Bob fire together every second, Carol half a second later
+    for _ in 0..5 {
+        clock.advance(chrono::Duration::milliseconds(500));
+        db.stimulate(vec![carol], 1.0).await.unwrap();
+        clock.advance(chrono::Duration::milliseconds(500));
+        db.stimulate(vec![alice, bob], 1.0).await.unwrap();
+    }
+    
+    let window = chrono::Duration::minutes(1);
+    let summary = db.analyze_spike_train(alice, window).await.unwrap();
+    assert_eq!(summary.spike_count, 5);
+    assert!((summary.isi.mean - 1000.0).abs() < 1e-6);
+    assert_eq!(summary.pattern, FiringPattern::Tonic);
+    assert!(summary.bursts.is_empty());
+    
+    let together = db.compare_spike_trains(alice, bob, window).await.unwrap();
+    assert_eq!(together.victor_purpura, 0.0);
+    assert!((together.synchrony - 1.0).abs() < 1e-9);
+    assert_eq!(together.peak_lag, Some(2.5));
+    
+    let apart = db.compare_spike_trains(alice, carol, window).await.unwrap();
+    assert!(apart.van_rossum > together.van_rossum);
+    assert!(apart.synchrony < 0.5);
+    
+    // A database won't open with unusable analysis parameters
+    let config = DatabaseConfig {
+        data_dir: data_dir.path().join("bad").to_string_lossy().to_string(),
+        spike_analysis: AnalysisConfig { max_lag: f64::NAN, ..AnalysisConfig::default() },
+        ..Default::default()
+    };
+    assert!(Database::with_config(config).await.is_err());
+    
+    // Spike timing feeds into similarity
+    let similar = db.find_similar(alice, 0.0).await.unwrap();
+    let score = |id: Uuid| similar.iter().find(|(node, _)| *node == id).unwrap().1;
+    assert!(score(bob) > score(carol));
+    
+    assert!(db.analyze_spike_train(Uuid::new_v4(), window).await.is_err());
+    assert!(db.compare_spike_trains(alice, Uuid::new_v4(), window).await.is_err());
+}
+
+#[tokio::test]
+async fn test_raster_export() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let mut config = DatabaseConfig {
+        data_dir: data_dir.path().to_string_lossy().to_string(),
+        maintenance: None,
+        ..Default::default()
+    };
+    // Bob integrates Alice's spikes slowly without firing
+    let slow = LifParameters { membrane_time_constant: 1000.0, ..LifParameters::with_threshold(10.0) };
+    config.neuron_models.insert("slow".to_string(), Arc::new(slow));
+    let start = chrono::Utc::now();
+    let clock = Arc::new(VirtualClock::new(start));
+    let db = Database::with_clock(config, clock.clone()).await.unwrap();
+    
+    let alice = db.add_node(serde_json::json!({"name": "Alice"})).await.unwrap();
+    let metadata = NodeMetadata { node_type: "slow".to_string(), ..Default::default() };
+    let bob = db.add_node_with_metadata(serde_json::json!({"name": "Bob"}), metadata).await.unwrap();
+    db.connect_nodes(vec![alice, bob], "friends".to_string(), 0.9).await.unwrap();
+    for _ in 0..2 {
+        clock.advance(chrono::Duration::milliseconds(200));
+        db.stimulate(vec![alice], 1.0).await.unwrap();
+    }
+    clock.advance(chrono::Duration::milliseconds(600));
+    
+    let query = RasterQuery { node_ids: vec![alice, bob], from: start, to: clock.now(), membrane_interval: None };
+    let raster = db.raster(&query).await.unwrap();
+    assert_eq!(raster.spikes.len(), 4);
+    assert!(raster.traces.is_none());
+    
+    let mut csv = Vec::new();
+    db.export_spikes(&query, ExportFormat::Csv, &mut csv).await.unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "node_id,node_index,time,timestamp,intensity,propagation_depth");
+    assert_eq!(lines.len(), 5);
+    assert!(lines[3].starts_with(&format!("{},0,200,", alice)));
+    
+    let mut ndjson = Vec::new();
+    db.export_spikes(&query, ExportFormat::Ndjson, &mut ndjson).await.unwrap();
+    let rows: Vec<serde_json::Value> = String::from_utf8(ndjson)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(rows[3]["time"], 400.0);
+    assert_eq!(rows[3]["node_id"], alice.to_string());
+    
+    // .npy: a (spikes, 2) array of node index and time
+    let mut npy = Vec::new();
+    db.export_spikes(&query, ExportFormat::Npy, &mut npy).await.unwrap();
+    assert_eq!(&npy[..8], b"\x93NUMPY\x01\x00");
+    let header_len = u16::from_le_bytes([npy[8], npy[9]]) as usize;
+    assert_eq!((10 + header_len) % 64, 0);
+    let header = std::str::from_utf8(&npy[10..10 + header_len]).unwrap();
+    assert!(header.contains("'descr': '<f8'") && header.contains("'shape': (4, 2)"));
+    let values: Vec<f64> = npy[10 + header_len..]
+        .chunks(8)
+        .map(|bytes| f64::from_le_bytes(bytes.try_into().unwrap()))
+        .collect();
+    assert_eq!(values[4..], [0.0, 200.0, 0.0, 400.0]);
+    
+    // Membrane traces on a 100 ms grid: Bob charges up after each of Alice's
+    // spikes and leaks back afterwards
+    let query = RasterQuery { membrane_interval: Some(100.0), ..query };
+    let raster = db.raster(&query).await.unwrap();
+    let traces = raster.traces.as_ref().unwrap();
+    assert_eq!(traces.potentials.len(), 2);
+    let bob_trace = &traces.potentials[1];
+    assert_eq!(bob_trace.len(), 11);
+    assert!(bob_trace[..3].iter().all(|potential| *potential == 0.0));
+    assert!(bob_trace[3] > 0.0);
+    assert!(bob_trace[5] > bob_trace[3]);
+    assert!(bob_trace[10] < bob_trace[5] && bob_trace[10] > 0.0);
+    
+    let mut csv = Vec::new();
+    db.export_membranes(&query, ExportFormat::Csv, &mut csv).await.unwrap();
+    assert_eq!(String::from_utf8(csv).unwrap().lines().count(), 1 + 2 * 11);
+    let mut npy = Vec::new();
+    db.export_membranes(&query, ExportFormat::Npy, &mut npy).await.unwrap();
+    assert!(std::str::from_utf8(&npy[10..74]).unwrap().contains("'shape': (2, 11)"));
+    
+    // Unknown nodes and unusable intervals are rejected
+    let bad = RasterQuery { node_ids: vec![Uuid::new_v4()], ..query.clone() };
+    assert!(db.raster(&bad).await.is_err());
+    let bad = RasterQuery { membrane_interval: Some(0.0), ..query.clone() };
+    assert!(db.raster(&bad).await.is_err());
+    let spikes_only = RasterQuery { membrane_interval: None, ..query };
+    assert!(db.export_membranes(&spikes_only, ExportFormat::Csv, Vec::new()).await.is_err());
+}
+
+#[tokio::test]
+async fn test_wal_ignores_torn_tail() {
+    let data_dir = tempfile::tempdir().unwrap();
+    
+    {
+        let db = Database::new(data_dir.path()).await.unwrap();
+        db.add_node(serde_json::json!({"name": "Alice"})).await.unwrap();
+        db.add_node(serde_json::json!({"name": "Bob"})).await.unwrap();
+    }
+    
+    // Simulate a crash in the middle of writing a record
+    let mut wal = std::fs::OpenOptions::new()
+        .append(true)
+        .open(data_dir.path().join("graph.wal"))
+        .unwrap();
+    wal.write_all(&[42, 0, 0, 0, 1, 2, 3]).unwrap();
+    drop(wal);
+    
+    let db = Database::new(data_dir.path()).await.unwrap();
+    assert_eq!(db.stats().await.node_count, 2);
+}
+
+#[tokio::test]
+async fn test_wal_rejects_undecodable_record() {
+    let data_dir = tempfile::tempdir().unwrap();
+    
+    {
+        let db = Database::new(data_dir.path()).await.unwrap();
+        db.add_node(serde_json::json!({"name": "Alice"})).await.unwrap();
+    }
+    
+    // A complete record with a valid checksum is not a torn write
+    let payload = [0xff, 0xff, 0xff, 0xff];
+    let wal_path = data_dir.path().join("graph.wal");
+    let mut wal = std::fs::OpenOptions::new().append(true).open(&wal_path).unwrap();
+    wal.write_all(&(payload.len() as u32).to_le_bytes()).unwrap();
+    wal.write_all(&crc32fast::hash(&payload).to_le_bytes()).unwrap();
+    wal.write_all(&payload).unwrap();
+    drop(wal);
+    let wal_len = std::fs::metadata(&wal_path).unwrap().len();
+    
+    assert!(Database::new(data_dir.path()).await.is_err());
+    assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), wal_len);
+}
+
+#[tokio::test]
+async fn test_wal_replay_keeps_recorded_timestamps() {
+    let data_dir = tempfile::tempdir().unwrap();
+    
+    let (alice, edge) = {
+        let db = Database::new(data_dir.path()).await.unwrap();
+        let alice = db.add_node(serde_json::json!({"name": "Alice"})).await.unwrap();
+        let bob = db.add_node(serde_json::json!({"name": "Bob"})).await.unwrap();
+        let edge = db.connect_nodes(vec![alice, bob], "friends".to_string(), 0.6).await.unwrap();
+        (alice, edge)
+    };
+    assert!(!data_dir.path().join("graph.bin").exists());
+    
+    let logged = StorageEngine::new(data_dir.path()).await.unwrap().replay_wal().await.unwrap();
+    let logged_at = |id: Uuid| {
+        logged.iter().find_map(|entry| match entry {
+            WalEntry::AddNodeWithMetadata { id: logged_id, at, .. } | WalEntry::AddHyperedge { id: logged_id, at, .. }
+                if *logged_id == id => Some(*at),
+            _ => None,
+        }).unwrap()
+    };
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    
+    // Reopening replays the log into a fresh snapshot
+    drop(Database::new(data_dir.path()).await.unwrap());
+    let graph = StorageEngine::new(data_dir.path()).await.unwrap().load_graph().await.unwrap();
+    let node = graph.get_node(&alice).unwrap();
+    let edge = graph.get_edge(&edge).unwrap();
+    assert_eq!((node.created_at, node.updated_at), (logged_at(alice), logged_at(alice)));
+    assert_eq!((edge.created_at, edge.updated_at), (logged_at(edge.id), logged_at(edge.id)));
+}
+
+#[tokio::test]
+async fn test_checkpoint_interrupted_before_wal_truncation() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let wal_path = data_dir.path().join("graph.wal");
+    
+    let config = DatabaseConfig {
+        data_dir: data_dir.path().to_string_lossy().to_string(),
+        stdp: Some(StdpConfig::default()),
+        ..Default::default()
+    };
+    let clock = Arc::new(VirtualClock::new(chrono::Utc::now()));
+    
+    let (alice, carol, edge, conductance, wal) = {
+        let db = Database::with_clock(config.clone(), clock.clone()).await.unwrap();
+        let alice = db.add_node(serde_json::json!({"name": "Alice"})).await.unwrap();
+        let bob = db.add_node(serde_json::json!({"name": "Bob"})).await.unwrap();
+        let carol = db.add_node(serde_json::json!({"name": "Carol"})).await.unwrap();
+        let edge = db.connect_nodes(vec![alice, bob], "friends".to_string(), 0.6).await.unwrap();
+        db.checkpoint().await.unwrap();
+        
+        // Records that only apply on top of the previous snapshot
+        db.remove_node(carol, CascadePolicy::DropEdges).await.unwrap();
+        clock.advance(chrono::Duration::milliseconds(200));
+        db.stimulate(vec![alice], 1.0).await.unwrap();
+        clock.advance(chrono::Duration::milliseconds(5));
+        db.stimulate(vec![bob], 1.0).await.unwrap();
+        let conductance = db.get_hyperedge(edge).await.unwrap().conductance;
+        assert_ne!(conductance, 0.6);
+        
+        // Crash after the next snapshot was written but before the log was cut
+        let wal = std::fs::read(&wal_path).unwrap();
+        db.checkpoint().await.unwrap();
+        (alice, carol, edge, conductance, wal)
+    };
+    std::fs::write(&wal_path, &wal).unwrap();
+    
+    // The snapshot already holds every logged record, so none is replayed
+    let db = Database::with_clock(config.clone(), clock.clone()).await.unwrap();
+    assert_eq!(db.stats().await.node_count, 2);
+    assert!(db.get_node(carol).await.is_none());
+    assert_eq!(db.get_hyperedge(edge).await.unwrap().conductance, conductance);
+    
+    // New records are numbered after the stale ones and still replay
+    let dave = db.add_node(serde_json::json!({"name": "Dave"})).await.unwrap();
+    db.connect_nodes(vec![alice, dave], "friends".to_string(), 0.4).await.unwrap();
+    drop(db);
+    let db = Database::with_clock(config, clock).await.unwrap();
+    assert_eq!(db.stats().await.node_count, 3);
+    assert_eq!(db.stats().await.edge_count, 2);
+}
+
+#[tokio::test]
+async fn test_v1_wal_is_migrated() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let wal_path = data_dir.path().join("graph.wal");
+    
+    // v1 records are the bare entry, without a sequence number
+    let id = Uuid::new_v4();
+    let entry = WalEntry::AddNode { id, data: serde_json::json!({"name": "Alice"}), at: chrono::Utc::now() };
+    let payload = bincode::serialize(&entry).unwrap();
+    let mut contents = b"NGWL".to_vec();
+    contents.extend_from_slice(&1u32.to_le_bytes());
+    contents.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    contents.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+    contents.extend_from_slice(&payload);
+    std::fs::write(&wal_path, contents).unwrap();
+    
+    let db = Database::new(data_dir.path()).await.unwrap();
+    assert!(db.get_node(id).await.is_some());
+    db.add_node(serde_json::json!({"name": "Bob"})).await.unwrap();
+    drop(db);
+    
+    let db = Database::new(data_dir.path()).await.unwrap();
+    assert_eq!(db.stats().await.node_count, 2);
+}
+
+#[tokio::test]
+async fn test_neural_state_survives_restart() {
+    let data_dir = tempfile::tempdir().unwrap();
+    
+    let before = {
+        let db = Database::new(data_dir.path()).await.unwrap();
+        let node1 = db.add_node(serde_json::json!({"name": "Alice"})).await.unwrap();
+        let node2 = db.add_node(serde_json::json!({"name": "Bob"})).await.unwrap();
+        db.connect_nodes(vec![node1, node2], "friends".to_string(), 0.9).await.unwrap();
+        db.checkpoint().await.unwrap();
+        db.stats().await
+    };
+    assert!(before.total_spikes > 0);
+    
+    let db = Database::new(data_dir.path()).await.unwrap();
+    let after = db.stats().await;
+    assert_eq!(after.total_spikes, before.total_spikes);
+    assert_eq!(after.active_neurons, before.active_neurons);
+    assert!((after.average_activation - before.average_activation).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_unknown_neural_format_version_is_rejected() {
+    let data_dir = tempfile::tempdir().unwrap();
+    
+    let mut contents = b"NGNS".to_vec();
+    contents.extend_from_slice(&99u32.to_le_bytes());
+    std::fs::write(data_dir.path().join("neural.bin"), contents).unwrap();
+    
+    let result = Database::new(data_dir.path()).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_headerless_snapshot_is_rejected() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let graph_file = data_dir.path().join("graph.bin");
+    
+    // Anything without the magic bytes predates the header
+    let legacy = b"not a versioned snapshot".to_vec();
+    std::fs::write(&graph_file, &legacy).unwrap();
+    
+    let err = Database::new(data_dir.path()).await.err().expect("headerless snapshot should be rejected");
+    assert!(format!("{:#}", err).contains("Pre-header snapshots are not supported"));
+    
+    // Left untouched, with no migration backup
+    assert_eq!(std::fs::read(&graph_file).unwrap(), legacy);
+    assert_eq!(std::fs::read_dir(data_dir.path().join("backups")).unwrap().count(), 0);
+}
+
+#[tokio::test]
+async fn test_snapshot_version_and_checksum_are_checked() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let graph_file = data_dir.path().join("graph.bin");
+    
+    {
+        let db = Database::new(data_dir.path()).await.unwrap();
+        db.add_node(serde_json::json!({"name": "Alice"})).await.unwrap();
+        db.checkpoint().await.unwrap();
+    }
+    let valid = std::fs::read(&graph_file).unwrap();
+    
+    let mut future_version = valid.clone();
+    future_version[4..8].copy_from_slice(&99u32.to_le_bytes());
+    std::fs::write(&graph_file, &future_version).unwrap();
+    let err = Database::new(data_dir.path()).await.err().unwrap();
+    assert!(format!("{:#}", err).contains("Unsupported graph format version 99"));
+    
+    let mut corrupt = valid.clone();
+    let last = corrupt.len() - 1;
+    corrupt[last] ^= 0xff;
+    std::fs::write(&graph_file, &corrupt).unwrap();
+    let err = Database::new(data_dir.path()).await.err().unwrap();
+    assert!(format!("{:#}", err).contains("checksum mismatch"));
+    
+    std::fs::write(&graph_file, &valid).unwrap();
+    assert_eq!(Database::new(data_dir.path()).await.unwrap().stats().await.node_count, 1);
+}
+
+#[tokio::test]
+async fn test_backup_and_restore() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let db = Database::new(data_dir.path()).await.unwrap();
+    
+    let alice = db.add_node(serde_json::json!({"name": "Alice"})).await.unwrap();
+    db.stimulate(vec![alice], 1.0).await.unwrap();
+    let spikes_at_backup = db.stats().await.total_spikes;
+    let backup = db.backup().await.unwrap();
+    assert!(backup.size > 0);
+    
+    // The graph and neural state are backed up together
+    let backup_dir = data_dir.path().join("backups").join(&backup.name);
+    assert!(backup_dir.join("graph.bin").exists());
+    assert!(backup_dir.join("neural.bin").exists());
+    
+    let bob = db.add_node(serde_json::json!({"name": "Bob"})).await.unwrap();
+    db.stimulate(vec![bob], 1.0).await.unwrap();
+    assert_eq!(db.stats().await.node_count, 2);
+    assert!(db.stats().await.total_spikes > spikes_at_backup);
+    
+    let backups = db.list_backups().await.unwrap();
+    assert_eq!(backups.len(), 1);
+    assert_eq!(backups[0].name, backup.name);
+    
+    db.restore_backup(&backup.name).await.unwrap();
+    assert_eq!(db.stats().await.node_count, 1);
+    assert_eq!(db.stats().await.total_spikes, spikes_at_backup);
+    
+    // The restored state is what a restart sees
+    drop(db);
+    let db = Database::new(data_dir.path()).await.unwrap();
+    assert_eq!(db.stats().await.node_count, 1);
+    assert_eq!(db.stats().await.total_spikes, spikes_at_backup);
+    
+    assert!(db.restore_backup("../graph.bin").await.is_err());
+    assert!(db.restore_backup("missing.bin").await.is_err());
+}
+
+#[tokio::test]
+async fn test_backup_pairs_files_of_one_checkpoint() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let config = DatabaseConfig {
+        data_dir: data_dir.path().to_string_lossy().to_string(),
+        checkpoint_interval: 1,
+        ..Default::default()
+    };
+    let db = Arc::new(Database::with_config(config).await.unwrap());
+    db.add_node(serde_json::json!({"name": "Alice"})).await.unwrap();
+    
+    // Every mutation checkpoints, racing the backups
+    let writer = {
+        let db = Arc::clone(&db);
+        tokio::spawn(async move {
+            for i in 0..100 {
+                db.add_node(serde_json::json!({"n": i})).await.unwrap();
+            }
+        })
+    };
+    let mut names = Vec::new();
+    for _ in 0..20 {
+        names.push(db.backup().await.unwrap().name);
+        tokio::task::yield_now().await;
+    }
+    writer.await.unwrap();
+    
+    // Both files of a backup record the same log sequence number
+    for name in names {
+        let backup_dir = data_dir.path().join("backups").join(name);
+        let graph = std::fs::read(backup_dir.join("graph.bin")).unwrap();
+        let neural = std::fs::read(backup_dir.join("neural.bin")).unwrap();
+        assert_eq!(u32::from_le_bytes(neural[4..8].try_into().unwrap()), 4);
+        assert_eq!(graph[12..20], neural[8..16]);
+    }
+}
+
+#[tokio::test]
+async fn test_backup_retention() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let db = Database::new(data_dir.path()).await.unwrap();
+    db.add_node(serde_json::json!({"name": "Alice"})).await.unwrap();
+    
+    let mut names = Vec::new();
+    for _ in 0..3 {
+        names.push(db.backup().await.unwrap().name);
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    }
+    
+    assert_eq!(db.apply_backup_retention(1).await.unwrap(), 2);
+    
+    let remaining = db.list_backups().await.unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].name, names[2]);
+}
+
+#[tokio::test]
+async fn test_scheduled_backups() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let config = DatabaseConfig {
+        data_dir: data_dir.path().to_string_lossy().to_string(),
+        backup_interval: Some(1),
+        backup_retention: 5,
+        ..Default::default()
+    };
+    
+    let db = Database::with_config(config).await.unwrap();
+    db.add_node(serde_json::json!({"name": "Alice"})).await.unwrap();
+    
+    tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+    assert!(!db.list_backups().await.unwrap().is_empty());
+}
+
+#[test]
+fn test_hypergraph_node_removal_policies() {
+    let mut graph = HyperGraph::new();
+    let nodes: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+    for (i, id) in nodes.iter().enumerate() {
+        graph.add_node(*id, serde_json::json!({"id": i})).unwrap();
+    }
+    
+    let triad = Uuid::new_v4();
+    let pair = Uuid::new_v4();
+    graph.add_hyperedge(triad, nodes.clone(), "team".to_string(), 0.8).unwrap();
+    graph.add_hyperedge(pair, vec![nodes[0], nodes[1]], "pair".to_string(), 0.5).unwrap();
+    
+    // Shrinking keeps the triad as a pair and drops the pair that falls to one member
+    let mut shrunk = graph.clone();
+    let removal = shrunk.remove_node(&nodes[0], CascadePolicy::ShrinkEdges).unwrap();
+    assert_eq!(removal.shrunk_edges, vec![triad]);
+    assert_eq!(removal.removed_edges, vec![pair]);
+    assert_eq!(shrunk.get_edge(&triad).unwrap().node_ids, vec![nodes[1], nodes[2]]);
+    assert_eq!(shrunk.get_neighbors(&nodes[1]).len(), 1);
+    
+    // Dropping removes every incident edge
+    let removal = graph.remove_node(&nodes[0], CascadePolicy::DropEdges).unwrap();
+    assert_eq!(removal.removed_edges.len(), 2);
+    assert_eq!(graph.edge_count(), 0);
+    assert!(graph.get_neighbors(&nodes[1]).is_empty());
+    assert!(graph.remove_node(&nodes[0], CascadePolicy::DropEdges).is_err());
+}
+
+#[test]
+fn test_hypergraph_partial_updates() {
+    let mut graph = HyperGraph::new();
+    let node1 = Uuid::new_v4();
+    let node2 = Uuid::new_v4();
+    graph.add_node(node1, serde_json::json!({"name": "Alice"})).unwrap();
+    graph.add_node(node2, serde_json::json!({"name": "Bob"})).unwrap();
+    
+    let mut properties = std::collections::HashMap::new();
+    properties.insert("city".to_string(), serde_json::json!("Berlin"));
+    properties.insert("role".to_string(), serde_json::json!("founder"));
+    graph.update_node(&node1, NodeUpdate {
+        tags: Some(vec!["ai".to_string()]),
+        properties: Some(properties),
+        ..Default::default()
+    }).unwrap();
+    
+    // A null value removes the key, other fields stay untouched
+    let mut patch = std::collections::HashMap::new();
+    patch.insert("role".to_string(), serde_json::Value::Null);
+    graph.update_node(&node1, NodeUpdate {
+        properties: Some(patch),
+        ..Default::default()
+    }).unwrap();
+    
+    let node = graph.get_node(&node1).unwrap();
+    assert_eq!(node.data, serde_json::json!({"name": "Alice"}));
+    assert_eq!(node.tags, vec!["ai".to_string()]);
+    assert_eq!(node.properties.len(), 1);
+    assert_eq!(graph.find_nodes_by_property("city", &serde_json::json!("Berlin")).len(), 1);
+    
+    let edge_id = Uuid::new_v4();
+    graph.add_hyperedge(edge_id, vec![node1, node2], "knows".to_string(), 0.4).unwrap();
+    graph.update_hyperedge(&edge_id, EdgeUpdate {
+        relationship: Some("cofounders".to_string()),
+        strength: Some(0.9),
+        ..Default::default()
+    }).unwrap();
+    
+    let edge = graph.get_edge(&edge_id).unwrap();
+    assert_eq!(edge.relationship, "cofounders");
+    assert_eq!(edge.strength, 0.9);
+    assert_eq!(edge.conductance, 0.4);
+}
+
+#[tokio::test]
+async fn test_database_delete_and_update() {
+    let data_dir = tempfile::tempdir().unwrap();
+    
+    let bob = {
+        let db = Database::new(data_dir.path()).await.unwrap();
+        let alice = db.add_node(serde_json::json!({"name": "Alice"})).await.unwrap();
+        let bob = db.add_node(serde_json::json!({"name": "Bob"})).await.unwrap();
+        let carol = db.add_node(serde_json::json!({"name": "Carol"})).await.unwrap();
+        let edge = db.connect_nodes(vec![alice, bob], "friends".to_string(), 0.9).await.unwrap();
+        db.connect_nodes(vec![bob, carol], "friends".to_string(), 0.9).await.unwrap();
+        
+        let active_before = db.stats().await.active_neurons;
+        db.remove_node(alice, CascadePolicy::DropEdges).await.unwrap();
+        
+        let stats = db.stats().await;
+        assert_eq!(stats.node_count, 2);
+        assert_eq!(stats.edge_count, 1);
+        assert_eq!(stats.active_neurons, active_before - 1);
+        assert!(db.get_hyperedge(edge).await.is_none());
+        
+        db.update_node(bob, NodeUpdate {
+            data: Some(serde_json::json!({"name": "Robert"})),
+            ..Default::default()
+        }).await.unwrap();
+        assert!(db.update_node(alice, NodeUpdate::default()).await.is_err());
+        bob
+    };
+    
+    // Deletes and updates are replayed from the log
+    let db = Database::new(data_dir.path()).await.unwrap();
+    let stats = db.stats().await;
+    assert_eq!(stats.node_count, 2);
+    assert_eq!(stats.edge_count, 1);
+    assert_eq!(db.get_node(bob).await.unwrap().data, serde_json::json!({"name": "Robert"}));
+}
+
+#[tokio::test]
+async fn test_node_metadata_end_to_end() {
+    let data_dir = tempfile::tempdir().unwrap();
+    
+    let node_id = {
+        let db = Database::new(data_dir.path()).await.unwrap();
+        
+        let mut properties = std::collections::HashMap::new();
+        properties.insert("stage".to_string(), serde_json::json!("seed"));
+        let node_id = db.add_node_with_metadata(
+            serde_json::json!({"name": "Acme"}),
+            NodeMetadata {
+                node_type: "startup".to_string(),
+                tags: vec!["ai".to_string(), "b2b".to_string()],
+                properties,
+            },
+        ).await.unwrap();
+        
+        db.set_node_type(node_id, "company".to_string()).await.unwrap();
+        db.edit_node_tags(node_id, vec!["saas".to_string()], vec!["b2b".to_string()]).await.unwrap();
+        
+        let mut patch = std::collections::HashMap::new();
+        patch.insert("employees".to_string(), serde_json::json!(12));
+        db.set_node_properties(node_id, patch).await.unwrap();
+        
+        node_id
+    };
+    
+    let db = Database::new(data_dir.path()).await.unwrap();
+    let node = db.get_node(node_id).await.unwrap();
+    assert_eq!(node.node_type, "company");
+    assert_eq!(node.tags, vec!["ai".to_string(), "saas".to_string()]);
+    assert_eq!(node.properties.get("stage"), Some(&serde_json::json!("seed")));
+    assert_eq!(node.properties.get("employees"), Some(&serde_json::json!(12)));
+    
+    // Plain nodes still get the default type
+    let plain = db.add_node(serde_json::json!({})).await.unwrap();
+    assert_eq!(db.get_node(plain).await.unwrap().node_type, "generic");
+}
+
+#[test]
+fn test_secondary_indexes() {
+    let mut graph = HyperGraph::new();
+    graph.create_property_index("funding");
+    
+    let mut ids = Vec::new();
+    for i in 0..10 {
+        let id = Uuid::new_v4();
+        let mut properties = std::collections::HashMap::new();
+        properties.insert("funding".to_string(), serde_json::json!(i * 100));
+        properties.insert("city".to_string(), serde_json::json!(if i % 2 == 0 { "Berlin" } else { "Paris" }));
+        graph.add_node_with_metadata(id, serde_json::json!({"id": i}), NodeMetadata {
+            node_type: if i < 5 { "startup" } else { "investor" }.to_string(),
+            tags: if i % 3 == 0 { vec!["ai".to_string()] } else { Vec::new() },
+            properties,
+        }).unwrap();
+        ids.push(id);
+    }
+    
+    assert_eq!(graph.find_nodes_by_type("startup").len(), 5);
+    assert_eq!(graph.find_nodes_by_tag("ai").len(), 4);
+    
+    // Indexed numeric range and set membership
+    let range = PropertyQuery::Range { min: Some(200.0), max: Some(500.0) };
+    assert_eq!(graph.query_nodes("funding", &range).len(), 4);
+    let members = PropertyQuery::In(vec![serde_json::json!(0), serde_json::json!(900)]);
+    assert_eq!(graph.query_nodes("funding", &members).len(), 2);
+    
+    // Unindexed keys fall back to a scan
+    assert_eq!(graph.find_nodes_by_property("city", &serde_json::json!("Berlin")).len(), 5);
+    
+    let filter = NodeFilter {
+        node_types: vec!["startup".to_string()],
+        tags: vec!["ai".to_string()],
+        properties: vec![("city".to_string(), PropertyQuery::Equals(serde_json::json!("Berlin")))],
+    };
+    let matched: Vec<Uuid> = graph.filter_nodes(&filter).iter().map(|node| node.id).collect();
+    assert_eq!(matched, vec![ids[0]]);
+    
+    // Indexes follow updates and deletes
+    graph.set_node_type(&ids[0], "investor".to_string()).unwrap();
+    graph.edit_node_tags(&ids[3], &[], &["ai".to_string()]).unwrap();
+    let mut patch = std::collections::HashMap::new();
+    patch.insert("funding".to_string(), serde_json::json!(10_000));
+    graph.update_node(&ids[2], NodeUpdate { properties: Some(patch), ..Default::default() }).unwrap();
+    graph.remove_node(&ids[4], CascadePolicy::DropEdges).unwrap();
+    
+    assert_eq!(graph.find_nodes_by_type("startup").len(), 3);
+    assert_eq!(graph.find_nodes_by_tag("ai").len(), 3);
+    assert_eq!(graph.query_nodes("funding", &range).len(), 2);
+    assert_eq!(graph.query_nodes("funding", &PropertyQuery::Range { min: Some(5_000.0), max: None }).len(), 1);
+    
+    // Signed zeros and NaN bounds agree with a scan instead of panicking
+    let negative_zero = Uuid::new_v4();
+    let mut properties = std::collections::HashMap::new();
+    properties.insert("funding".to_string(), serde_json::json!(-0.0));
+    graph.add_node_with_metadata(negative_zero, serde_json::json!({}), NodeMetadata { properties, ..Default::default() }).unwrap();
+    for (min, max, expected) in [
+        (Some(0.0), Some(-0.0), 2),
+        (Some(-0.0), Some(0.0), 2),
+        (Some(0.0), Some(0.0), 2),
+        (Some(f64::NAN), Some(100.0), 0),
+        (None, Some(f64::NAN), 0),
+    ] {
+        let range = PropertyQuery::Range { min, max };
+        let scanned = graph.nodes().values().filter(|node| node.properties.get("funding").is_some_and(|value| range.matches(value))).count();
+        assert_eq!(graph.query_nodes("funding", &range).len(), expected, "{:?}", range);
+        assert_eq!(scanned, expected, "{:?}", range);
+    }
+}
+
+#[tokio::test]
+async fn test_index_definitions_persist() {
+    let data_dir = tempfile::tempdir().unwrap();
+    
+    {
+        let db = Database::new(data_dir.path()).await.unwrap();
+        db.create_property_index("funding").await.unwrap();
+        
+        let mut properties = std::collections::HashMap::new();
+        properties.insert("funding".to_string(), serde_json::json!(250));
+        db.add_node_with_metadata(serde_json::json!({}), NodeMetadata {
+            properties,
+            ..Default::default()
+        }).await.unwrap();
+        db.checkpoint().await.unwrap();
+    }
+    
+    let db = Database::new(data_dir.path()).await.unwrap();
+    let range = PropertyQuery::Range { min: Some(200.0), max: Some(300.0) };
+    assert_eq!(db.query_nodes("funding", &range).await.len(), 1);
+    
+    let filter = NodeFilter { node_types: vec!["generic".to_string()], ..Default::default() };
+    assert_eq!(db.filter_nodes(&filter).await.len(), 1);
+}
+
+#[test]
+fn test_edge_type_traversal() {
+    let mut graph = HyperGraph::new();
+    let ids: Vec<Uuid> = (0..4).map(|_| Uuid::new_v4()).collect();
+    for id in &ids {
+        graph.add_node(*id, serde_json::json!({})).unwrap();
+    }
+    let neighbor_ids = |graph: &HyperGraph, id: &Uuid| -> HashSet<Uuid> {
+        graph.get_neighbors(id).iter().map(|node| node.id).collect()
+    };
+    
+    // Directional: no backflow
+    let directional = Uuid::new_v4();
+    graph.add_typed_hyperedge(directional, vec![ids[0], ids[1], ids[2]], "funds".to_string(), 0.8,
+        EdgeType::Directional { from: ids[0], to: vec![ids[1], ids[2]] }).unwrap();
+    assert_eq!(neighbor_ids(&graph, &ids[0]), HashSet::from([ids[1], ids[2]]));
+    assert!(neighbor_ids(&graph, &ids[1]).is_empty());
+    graph.remove_hyperedge(&directional).unwrap();
+    
+    // Hub: periphery only reaches the center
+    graph.add_typed_hyperedge(Uuid::new_v4(), ids.clone(), "mentors".to_string(), 0.8,
+        EdgeType::Hub { center: ids[0], periphery: ids[1..].to_vec() }).unwrap();
+    assert_eq!(neighbor_ids(&graph, &ids[0]).len(), 3);
+    assert_eq!(neighbor_ids(&graph, &ids[2]), HashSet::from([ids[0]]));
+    
+    // Chain: only the next member in sequence
+    let mut chain = HyperGraph::new();
+    for id in &ids {
+        chain.add_node(*id, serde_json::json!({})).unwrap();
+    }
+    chain.add_typed_hyperedge(Uuid::new_v4(), ids.clone(), "supplies".to_string(), 0.8, EdgeType::Chain).unwrap();
+    assert_eq!(neighbor_ids(&chain, &ids[1]), HashSet::from([ids[2]]));
+    assert!(neighbor_ids(&chain, &ids[3]).is_empty());
+    
+    let effect = NetworkAnalyzer::analyze_network_effects(&chain, ids[1], 1.0, 10).unwrap();
+    let affected: HashSet<Uuid> = effect.affected_nodes.iter().map(|(id, _)| *id).collect();
+    assert_eq!(affected, HashSet::from([ids[2], ids[3]]));
+    
+    // Anchors must be members of the edge
+    let outsider = Uuid::new_v4();
+    chain.add_node(outsider, serde_json::json!({})).unwrap();
+    let invalid = EdgeType::Directional { from: outsider, to: vec![ids[0]] };
+    assert!(chain.add_typed_hyperedge(Uuid::new_v4(), vec![ids[0], ids[1]], "x".to_string(), 0.5, invalid).is_err());
+}
+
+#[tokio::test]
+async fn test_cascade_respects_edge_types() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let db = Database::new(data_dir.path()).await.unwrap();
+    
+    let mut ids = Vec::new();
+    for i in 0..4 {
+        ids.push(db.add_node(serde_json::json!({"step": i})).await.unwrap());
+    }
+    db.connect_nodes_with_type(ids.clone(), "pipeline".to_string(), 0.9, EdgeType::Chain).await.unwrap();
+    
+    // Upstream of the chain receives nothing from a downstream activation
+    let effects = db.simulate_network_effect(ids[2], 1.0).await.unwrap();
+    let reached: Vec<Uuid> = effects.iter().map(|effect| effect.node_id).collect();
+    assert_eq!(reached, vec![ids[3]]);
+    
+    let effects = db.simulate_network_effect(ids[0], 1.0).await.unwrap();
+    assert_eq!(effects.len(), 3);
+    drop(db);
+    
+    // Edge types survive WAL replay
+    let db = Database::new(data_dir.path()).await.unwrap();
+    let effects = db.simulate_network_effect(ids[2], 1.0).await.unwrap();
+    assert_eq!(effects.len(), 1);
+}
+
+fn test_simulation_config() -> SimulationConfig {
+    SimulationConfig {
+        spike_threshold: 0.7,
+        refractory_period: 10.0,
+        synaptic_delay: 2.0,
+        decay_rate: 1.0,
+        max_depth: 10,
+        max_events: 1000,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_event_driven_simulation() {
+    let mut graph = HyperGraph::new();
+    let ids: Vec<Uuid> = (0..4).map(|_| Uuid::new_v4()).collect();
+    for id in &ids {
+        graph.add_node(*id, serde_json::json!({})).unwrap();
+    }
+    graph.add_hyperedge(Uuid::new_v4(), vec![ids[0], ids[1]], "link".to_string(), 0.8).unwrap();
+    graph.add_hyperedge(Uuid::new_v4(), vec![ids[1], ids[2]], "link".to_string(), 0.8).unwrap();
+    
+    let start = chrono::Utc::now();
+    let run = || {
+        let mut simulator = Simulator::new(&graph, test_simulation_config(), start);
+        simulator.inject_spike(ids[0], 1.0, 0.0);
+        simulator.run()
+    };
+    let result = run();
+    
+    // ids[1] crosses the threshold one delay later; the echo back to ids[0]
+    // lands in its refractory period and ids[2] stays below threshold
+    let fired: Vec<(Uuid, i64)> = result
+        .spikes
+        .iter()
+        .map(|spike| (spike.node_id, (spike.timestamp - start).num_milliseconds()))
+        .collect();
+    assert_eq!(fired, vec![(ids[0], 0), (ids[1], 2)]);
+    assert!((result.peak_potentials[&ids[2]] - 0.64).abs() < 1e-9);
+    assert!(!result.peak_potentials.contains_key(&ids[3]));
+    
+    // Identical inputs give identical runs
+    let again = run();
+    assert_eq!(again.reached, result.reached);
+    assert_eq!(again.events_processed, result.events_processed);
+    
+    // Sub-threshold inputs arriving together sum up to a spike
+    graph.add_hyperedge(Uuid::new_v4(), vec![ids[0], ids[3]], "link".to_string(), 0.4).unwrap();
+    graph.add_hyperedge(Uuid::new_v4(), vec![ids[2], ids[3]], "link".to_string(), 0.4).unwrap();
+    let mut simulator = Simulator::new(&graph, test_simulation_config(), start);
+    simulator.inject_spike(ids[0], 1.0, 0.0);
+    simulator.inject_spike(ids[2], 1.0, 0.0);
+    let result = simulator.run();
+    assert!(result.spikes.iter().any(|spike| spike.node_id == ids[3]));
+}
+
+#[tokio::test]
+async fn test_refractory_period_on_virtual_clock() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let config = DatabaseConfig {
+        data_dir: data_dir.path().to_string_lossy().to_string(),
+        ..Default::default()
+    };
+    let clock = Arc::new(VirtualClock::new(chrono::Utc::now()));
+    let db = Database::with_clock(config, clock.clone()).await.unwrap();
+    
+    let alice = db.add_node(serde_json::json!({"name": "Alice"})).await.unwrap();
+    let bob = db.add_node(serde_json::json!({"name": "Bob"})).await.unwrap();
+    db.connect_nodes(vec![alice, bob], "friends".to_string(), 0.9).await.unwrap();
+    let spikes = db.stats().await.total_spikes;
+    assert_eq!(spikes, 2);
+    
+    // Still refractory: the clock hasn't moved
+    db.connect_nodes(vec![alice, bob], "colleagues".to_string(), 0.9).await.unwrap();
+    assert_eq!(db.stats().await.total_spikes, spikes);
+    
+    clock.advance(chrono::Duration::milliseconds(150));
+    db.connect_nodes(vec![alice], "self".to_string(), 0.9).await.unwrap();
+    assert!(db.stats().await.total_spikes > spikes);
+}
+
+#[test]
+fn test_leaky_integrate_and_fire() {
+    let mut graph = HyperGraph::new();
+    let (a, b, target) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    graph.add_node(a, serde_json::json!({})).unwrap();
+    graph.add_node(b, serde_json::json!({})).unwrap();
+    graph.add_node_with_metadata(target, serde_json::json!({}), NodeMetadata {
+        node_type: "slow".to_string(),
+        ..Default::default()
+    }).unwrap();
+    for source in [a, b] {
+        graph.add_typed_hyperedge(Uuid::new_v4(), vec![source, target], "feeds".to_string(), 0.5,
+            EdgeType::Directional { from: source, to: vec![target] }).unwrap();
+    }
+    
+    let start = chrono::Utc::now();
+    let run = |config: SimulationConfig, second_input_at: f64| {
+        let mut simulator = Simulator::new(&graph, config, start);
+        simulator.inject_spike(a, 1.0, 0.0);
+        simulator.inject_spike(b, 1.0, second_input_at);
+        simulator.run()
+    };
+    let target_spikes = |result: &neurographite::SimulationResult| -> Vec<f64> {
+        result
+            .spikes
+            .iter()
+            .filter(|spike| spike.node_id == target)
+            .map(|spike| (spike.timestamp - start).num_microseconds().unwrap() as f64 / 1000.0)
+            .collect()
+    };
+    
+    // Coincident inputs sum past threshold; spread-out inputs leak away in between
+    assert_eq!(target_spikes(&run(test_simulation_config(), 0.0)), vec![2.0]);
+    let result = run(test_simulation_config(), 50.0);
+    assert!(target_spikes(&result).is_empty());
+    let expected = 0.5 * (-50.0f64 / 20.0).exp() + 0.5;
+    assert!((result.membranes[&target].potential - expected).abs() < 1e-6);
+    
+    // Per-type parameters: a lower threshold and input arriving as a decaying current
+    let mut config = test_simulation_config();
+    config.neuron_parameters.insert("slow".to_string(), LifParameters {
+        threshold: 0.3,
+        synaptic_time_constant: 5.0,
+        ..LifParameters::with_threshold(0.3)
+    });
+    let spikes = target_spikes(&run(config, 50.0));
+    assert_eq!(spikes.len(), 2);
+    assert!(spikes[0] > 2.0 && spikes[0] < 10.0);
+}
+
+#[test]
+fn test_reset_above_threshold_without_refractory_period() {
+    let mut graph = HyperGraph::new();
+    let (stuck, target) = (Uuid::new_v4(), Uuid::new_v4());
+    graph.add_node_with_metadata(stuck, serde_json::json!({}), NodeMetadata {
+        node_type: "stuck".to_string(),
+        ..Default::default()
+    }).unwrap();
+    graph.add_node(target, serde_json::json!({})).unwrap();
+    graph.add_typed_hyperedge(Uuid::new_v4(), vec![stuck, target], "feeds".to_string(), 0.9,
+        EdgeType::Directional { from: stuck, to: vec![target] }).unwrap();
+    
+    // The reset leaves the membrane over threshold and nothing holds it back
+    let mut config = SimulationConfig { refractory_period: 0.0, ..test_simulation_config() };
+    config.neuron_parameters.insert("stuck".to_string(), LifParameters {
+        reset_potential: 1.0,
+        ..LifParameters::with_threshold(0.5)
+    });
+    
+    // It still fires only once at that instant instead of recursing forever
+    let start = chrono::Utc::now();
+    let mut simulator = Simulator::new(&graph, config, start);
+    assert!(simulator.inject_spike(stuck, 1.0, 0.0));
+    assert!(!simulator.inject_spike(stuck, 1.0, 0.0));
+    let result = simulator.run();
+    let stuck_spikes = result.spikes.iter().filter(|spike| spike.node_id == stuck).count();
+    assert_eq!(stuck_spikes, 1);
+    assert!(result.spikes.iter().any(|spike| spike.node_id == target));
+}
+
+#[tokio::test]
+async fn test_membrane_state_and_neuron_parameters() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let config = DatabaseConfig {
+        data_dir: data_dir.path().to_string_lossy().to_string(),
+        ..Default::default()
+    };
+    let clock = Arc::new(VirtualClock::new(chrono::Utc::now()));
+    
+    let parameters = LifParameters {
+        resting_potential: 0.0,
+        reset_potential: -0.2,
+        threshold: 0.95,
+        membrane_time_constant: 10.0,
+        synaptic_time_constant: 0.0,
+    };
+    
+    let target = {
+        let db = Database::with_clock(config.clone(), clock.clone()).await.unwrap();
+        
+        // Parameters that can't describe a resting neuron are turned away
+        for invalid in [
+            LifParameters { resting_potential: 1.0, ..parameters.clone() },
+            LifParameters { reset_potential: 0.95, ..parameters.clone() },
+            LifParameters { threshold: f64::NAN, ..parameters.clone() },
+            LifParameters { membrane_time_constant: 0.0, ..parameters.clone() },
+            LifParameters { synaptic_time_constant: -1.0, ..parameters.clone() },
+        ] {
+            assert!(db.set_neuron_parameters("investor", invalid).await.is_err());
+        }
+        assert_eq!(db.neuron_parameters("investor").await, db.neuron_parameters("anyone").await);
+        
+        db.set_neuron_parameters("investor", parameters.clone()).await.unwrap();
+        
+        let source = db.add_node(serde_json::json!({})).await.unwrap();
+        let target = db.add_node_with_metadata(serde_json::json!({}), NodeMetadata {
+            node_type: "investor".to_string(),
+            ..Default::default()
+        }).await.unwrap();
+        db.connect_nodes_with_type(vec![source, target], "pitch".to_string(), 0.9,
+            EdgeType::Directional { from: source, to: vec![target] }).await.unwrap();
+        
+        // Once out of the refractory period set off by connecting, a spike
+        // from the source arrives below the investor threshold and leaks away
+        clock.advance(chrono::Duration::milliseconds(200));
+        db.stimulate(vec![source], 1.0).await.unwrap();
+        clock.advance(chrono::Duration::milliseconds(1));
+        let potential = db.membrane_potential(target).await.unwrap();
+        assert!(potential > 0.7 && potential < 0.95);
+        clock.advance(chrono::Duration::milliseconds(10));
+        let leaked = db.membrane_potential(target).await.unwrap();
+        assert!((leaked - potential * (-1.0f64).exp()).abs() < 1e-3);
+        
+        db.checkpoint().await.unwrap();
+        target
+    };
+    
+    let db = Database::with_clock(config, clock).await.unwrap();
+    assert_eq!(db.neuron_parameters("investor").await, parameters);
+    assert_eq!(db.neuron_parameters("generic").await.threshold, 0.7);
+    assert!(db.membrane_potential(target).await.unwrap() > 0.0);
+}
+
+#[test]
+fn test_neuron_models_per_node_type() {
+    let mut graph = HyperGraph::new();
+    let source = Uuid::new_v4();
+    graph.add_node(source, serde_json::json!({})).unwrap();
+    let mut targets = Vec::new();
+    for node_type in ["bursting", "adaptive", "rate"] {
+        let target = Uuid::new_v4();
+        graph.add_node_with_metadata(target, serde_json::json!({}), NodeMetadata {
+            node_type: node_type.to_string(),
+            ..Default::default()
+        }).unwrap();
+        graph.add_typed_hyperedge(Uuid::new_v4(), vec![source, target], "drives".to_string(), 0.9,
+            EdgeType::Directional { from: source, to: vec![target] }).unwrap();
+        targets.push(target);
+    }
+    let (bursting, adaptive, rate) = (targets[0], targets[1], targets[2]);
+    
+    let rate_parameters = RateParameters::default();
+    let mut config = test_simulation_config();
+    config.neuron_models.insert("bursting".to_string(), Arc::new(IzhikevichParameters::chattering()));
+    config.neuron_models.insert("adaptive".to_string(), Arc::new(AdExParameters::default()));
+    config.neuron_models.insert("rate".to_string(), Arc::new(rate_parameters.clone()));
+    
+    let start = chrono::Utc::now();
+    let mut simulator = Simulator::new(&graph, config, start);
+    simulator.inject_spike(source, 1.0, 0.0);
+    let result = simulator.run();
+    let spikes_of = |node_id: Uuid| result.spikes.iter().filter(|spike| spike.node_id == node_id).count();
+    
+    // A single input sets off a burst in the chattering neuron
+    assert!(spikes_of(bursting) >= 2);
+    let resting = IzhikevichParameters::chattering().resting_state(start);
+    assert!(result.membranes[&bursting].recovery > resting.recovery);
+    
+    // The AdEx neuron fires once and carries spike-triggered adaptation
+    assert_eq!(spikes_of(adaptive), 1);
+    assert!(result.membranes[&adaptive].recovery > 0.0);
+    
+    // The rate unit passes its sigmoid rate on instead of a fixed spike
+    let weight = 1.0 * 0.9 * 1.0;
+    let emitted: Vec<f64> = result.spikes.iter().filter(|spike| spike.node_id == rate).map(|spike| spike.intensity).collect();
+    assert_eq!(emitted.len(), 1);
+    assert!((emitted[0] - rate_parameters.rate(weight)).abs() < 1e-9);
+    
+    // Having emitted, a rate unit above threshold waits for its next input
+    let mut membrane = rate_parameters.resting_state(start);
+    rate_parameters.receive(&mut membrane, 1.0);
+    assert_eq!(rate_parameters.project(&membrane).crossing, Some(0.0));
+    rate_parameters.reset(&mut membrane);
+    assert!(rate_parameters.rate(membrane.potential) >= rate_parameters.emit_threshold);
+    assert!(rate_parameters.project(&membrane).crossing.is_none());
+    rate_parameters.receive(&mut membrane, 0.1);
+    assert_eq!(rate_parameters.project(&membrane).crossing, Some(0.0));
+    
+    // Without input every model settles back to rest
+    for model in [
+        Arc::new(IzhikevichParameters::regular_spiking()) as Arc<dyn NeuronModel>,
+        Arc::new(AdExParameters::default()),
+        Arc::new(rate_parameters),
+    ] {
+        let mut membrane = model.resting_state(start);
+        model.receive(&mut membrane, 0.05);
+        model.advance(&mut membrane, 2000.0);
+        let rest = model.resting_state(start);
+        assert!((membrane.potential - rest.potential).abs() < 0.5, "{:?} did not settle", model);
+        assert!(model.project(&membrane).crossing.is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_neuron_model_from_database_config() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let mut config = DatabaseConfig {
+        data_dir: data_dir.path().to_string_lossy().to_string(),
+        ..Default::default()
+    };
+    config.neuron_models.insert("cortical".to_string(), Arc::new(IzhikevichParameters::regular_spiking()));
+    let clock = Arc::new(VirtualClock::new(chrono::Utc::now()));
+    
+    let target = {
+        let db = Database::with_clock(config.clone(), clock.clone()).await.unwrap();
+        let source = db.add_node(serde_json::json!({})).await.unwrap();
+        let target = db.add_node_with_metadata(serde_json::json!({}), NodeMetadata {
+            node_type: "cortical".to_string(),
+            ..Default::default()
+        }).await.unwrap();
+        db.connect_nodes_with_type(vec![source, target], "drives".to_string(), 0.9,
+            EdgeType::Directional { from: source, to: vec![target] }).await.unwrap();
+        
+        // The Izhikevich neuron resets to c and its recovery jumps by d
+        let resting = IzhikevichParameters::regular_spiking().resting_state(chrono::Utc::now());
+        let membrane = db.membrane_state(target).await.unwrap();
+        assert!(membrane.recovery > resting.recovery);
+        assert!(membrane.potential < -50.0);
+        
+        db.checkpoint().await.unwrap();
+        target
+    };
+    
+    // Recovery survives a restart
+    let resting = IzhikevichParameters::regular_spiking().resting_state(chrono::Utc::now());
+    let db = Database::with_clock(config.clone(), clock.clone()).await.unwrap();
+    assert!(db.membrane_state(target).await.unwrap().recovery > resting.recovery);
+    drop(db);
+    
+    // Models that can't rest below their threshold keep the database from opening
+    let invalid: [Arc<dyn NeuronModel>; 4] = [
+        Arc::new(IzhikevichParameters { b: 1.0, ..IzhikevichParameters::regular_spiking() }),
+        Arc::new(IzhikevichParameters { a: f64::NAN, ..IzhikevichParameters::regular_spiking() }),
+        Arc::new(AdExParameters { resting_potential: -40.0, ..AdExParameters::default() }),
+        Arc::new(RateParameters { time_constant: 0.0, ..RateParameters::default() }),
+    ];
+    for model in invalid {
+        assert!(model.validate().is_err(), "{:?}", model);
+        let mut config = config.clone();
+        config.neuron_models.insert("cortical".to_string(), model);
+        assert!(Database::with_clock(config, clock.clone()).await.is_err());
+    }
+    assert!(IzhikevichParameters::chattering().validate().is_ok());
+    assert!(AdExParameters::default().validate().is_ok());
+}
+
+#[tokio::test]
+async fn test_spike_timing_dependent_plasticity() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let stdp = StdpConfig { max_conductance: 0.51, ..Default::default() };
+    let config = DatabaseConfig {
+        data_dir: data_dir.path().to_string_lossy().to_string(),
+        stdp: Some(stdp.clone()),
+        ..Default::default()
+    };
+    let clock = Arc::new(VirtualClock::new(chrono::Utc::now()));
+    
+    let (edge_id, depressed) = {
+        let db = Database::with_clock(config.clone(), clock.clone()).await.unwrap();
+        let pre = db.add_node(serde_json::json!({})).await.unwrap();
+        let post = db.add_node(serde_json::json!({})).await.unwrap();
+        let edge_id = db.connect_nodes_with_type(vec![pre, post], "mentors".to_string(), 0.5,
+            EdgeType::Directional { from: pre, to: vec![post] }).await.unwrap();
+        
+        // Members firing together on creation have no order to learn from
+        assert!(db.conductance_updates(None).await.is_empty());
+        
+        // pre then post, 5ms apart: potentiated along the window
+        clock.advance(chrono::Duration::milliseconds(200));
+        db.stimulate(vec![pre], 1.0).await.unwrap();
+        clock.advance(chrono::Duration::milliseconds(5));
+        db.stimulate(vec![post], 1.0).await.unwrap();
+        
+        let potentiated = 0.5 + stdp.window_change(5.0, true);
+        assert!((db.get_hyperedge(edge_id).await.unwrap().conductance - potentiated).abs() < 1e-9);
+        
+        // post then pre: anti-causal for a directional edge, so depressed
+        clock.advance(chrono::Duration::milliseconds(200));
+        db.stimulate(vec![post], 1.0).await.unwrap();
+        clock.advance(chrono::Duration::milliseconds(10));
+        db.stimulate(vec![pre], 1.0).await.unwrap();
+        
+        let depressed = potentiated + stdp.window_change(10.0, false);
+        assert!(depressed < potentiated);
+        assert!((db.get_hyperedge(edge_id).await.unwrap().conductance - depressed).abs() < 1e-9);
+        
+        let updates = db.conductance_updates(Some(edge_id)).await;
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[0].pairs, 1);
+        assert!(updates[0].potentiation > 0.0 && updates[0].depression == 0.0);
+        assert!((updates[1].conductance - depressed).abs() < 1e-9);
+        
+        // Repeated causal pairings saturate at the upper bound
+        for _ in 0..5 {
+            clock.advance(chrono::Duration::milliseconds(200));
+            db.stimulate(vec![pre], 1.0).await.unwrap();
+            clock.advance(chrono::Duration::milliseconds(1));
+            db.stimulate(vec![post], 1.0).await.unwrap();
+        }
+        assert_eq!(db.get_hyperedge(edge_id).await.unwrap().conductance, 0.51);
+        
+        db.checkpoint().await.unwrap();
+        (edge_id, depressed)
+    };
+    
+    // Learned conductance and the update record survive a restart
+    let db = Database::with_clock(config, clock).await.unwrap();
+    assert_eq!(db.get_hyperedge(edge_id).await.unwrap().conductance, 0.51);
+    let updates = db.conductance_updates(Some(edge_id)).await;
+    assert_eq!(updates.len(), 7);
+    assert!((updates[1].conductance - depressed).abs() < 1e-9);
+    
+    // Bounds that can't be clamped to are rejected up front, not at the first spike
+    let inverted = StdpConfig { min_conductance: 0.8, max_conductance: 0.2, ..Default::default() };
+    assert!(inverted.validate().is_err());
+    assert!(StdpConfig { max_conductance: f64::NAN, ..Default::default() }.validate().is_err());
+    assert!(StdpConfig { depression_time_constant: 0.0, ..Default::default() }.validate().is_err());
+    let invalid_dir = tempfile::tempdir().unwrap();
+    let invalid = DatabaseConfig {
+        data_dir: invalid_dir.path().to_string_lossy().to_string(),
+        stdp: Some(inverted),
+        ..Default::default()
+    };
+    assert!(Database::with_config(invalid).await.is_err());
+    
+    // The same goes for logged adjustments, which would otherwise fail replay
+    let mut graph = HyperGraph::new();
+    let adjust = |min: f64, max: f64| WalEntry::AdjustConductances {
+        changes: vec![(edge_id, 0.1)],
+        min,
+        max,
+        at: chrono::Utc::now(),
+    };
+    let edge = db.get_hyperedge(edge_id).await.unwrap();
+    for node_id in &edge.node_ids {
+        graph.add_node(*node_id, serde_json::json!({})).unwrap();
+    }
+    graph.add_hyperedge(edge_id, edge.node_ids, "mentors".to_string(), 0.5).unwrap();
+    assert!(adjust(0.0, 1.0).validate(&graph).is_ok());
+    assert!(adjust(1.0, 0.0).validate(&graph).is_err());
+    assert!(adjust(0.0, f64::NAN).validate(&graph).is_err());
+}
+
+#[tokio::test]
+async fn test_reward_modulated_learning() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let reward = RewardConfig { eligibility_time_constant: 1000.0, ..Default::default() };
+    let config = DatabaseConfig {
+        data_dir: data_dir.path().to_string_lossy().to_string(),
+        stdp: None,
+        reward: Some(reward.clone()),
+        ..Default::default()
+    };
+    let clock = Arc::new(VirtualClock::new(chrono::Utc::now()));
+    let db = Database::with_clock(config, clock.clone()).await.unwrap();
+    
+    let (founder, investor, bystander) = (
+        db.add_node(serde_json::json!({})).await.unwrap(),
+        db.add_node(serde_json::json!({})).await.unwrap(),
+        db.add_node(serde_json::json!({})).await.unwrap(),
+    );
+    let pitch = db.connect_nodes_with_type(vec![founder, investor], "pitch".to_string(), 0.5,
+        EdgeType::Directional { from: founder, to: vec![investor] }).await.unwrap();
+    let idle = db.connect_nodes(vec![investor, bystander], "knows".to_string(), 0.5).await.unwrap();
+    
+    // Without causal activity nothing is eligible, so an outcome changes nothing
+    assert!(db.report_outcome(OutcomeTarget::Edge(pitch), 1.0).await.unwrap().is_empty());
+    
+    // founder then investor leaves a trace without changing the conductance (STDP is off)
+    clock.advance(chrono::Duration::milliseconds(200));
+    db.stimulate(vec![founder], 1.0).await.unwrap();
+    clock.advance(chrono::Duration::milliseconds(5));
+    db.stimulate(vec![investor], 1.0).await.unwrap();
+    
+    let trace = db.eligibility(pitch).await;
+    assert!((trace - StdpConfig::default().window_change(5.0, true)).abs() < 1e-9);
+    assert_eq!(db.get_hyperedge(pitch).await.unwrap().conductance, 0.5);
+    
+    // Traces decay; a success later is credited in proportion
+    clock.advance(chrono::Duration::milliseconds(1000));
+    let decayed = trace * (-1.0f64).exp();
+    let updates = db.report_outcome(OutcomeTarget::Pair(founder, investor), 1.0).await.unwrap();
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].edge_id, pitch);
+    assert!((updates[0].eligibility - decayed).abs() < 1e-9);
+    let strengthened = 0.5 + reward.learning_rate * decayed;
+    assert!((db.get_hyperedge(pitch).await.unwrap().conductance - strengthened).abs() < 1e-9);
+    assert_eq!(db.get_hyperedge(idle).await.unwrap().conductance, 0.5);
+    
+    // A failure weakens the same edge
+    let updates = db.report_outcome(OutcomeTarget::Edge(pitch), -2.0).await.unwrap();
+    assert!(updates[0].conductance < 0.5);
+    
+    // Unknown targets and non-finite rewards are errors, as is reporting with learning disabled
+    assert!(db.report_outcome(OutcomeTarget::Edge(Uuid::new_v4()), 1.0).await.is_err());
+    assert!(db.report_outcome(OutcomeTarget::Edge(pitch), f64::NAN).await.is_err());
+    let disabled_dir = tempfile::tempdir().unwrap();
+    let disabled = Database::with_config(DatabaseConfig {
+        data_dir: disabled_dir.path().to_string_lossy().to_string(),
+        reward: None,
+        ..Default::default()
+    }).await.unwrap();
+    assert!(disabled.report_outcome(OutcomeTarget::Edge(pitch), 1.0).await.is_err());
+    
+    // Learning parameters are checked when the database opens
+    assert!(RewardConfig { learning_rate: f64::NAN, ..Default::default() }.validate().is_err());
+    assert!(RewardConfig { eligibility_time_constant: -1.0, ..Default::default() }.validate().is_err());
+    let inverted = RewardConfig { min_conductance: 1.0, max_conductance: 0.5, ..Default::default() };
+    assert!(inverted.validate().is_err());
+    let invalid_dir = tempfile::tempdir().unwrap();
+    assert!(Database::with_config(DatabaseConfig {
+        data_dir: invalid_dir.path().to_string_lossy().to_string(),
+        reward: Some(inverted),
+        ..Default::default()
+    }).await.is_err());
+}
+
+#[test]
+fn test_inhibitory_edges() {
+    let mut graph = HyperGraph::new();
+    let (source, ally, rival, helper) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    for id in [source, ally, rival, helper] {
+        graph.add_node(id, serde_json::json!({})).unwrap();
+    }
+    graph.add_hyperedge(Uuid::new_v4(), vec![source, ally], "partners".to_string(), 0.9).unwrap();
+    let rivalry = Uuid::new_v4();
+    graph.add_typed_hyperedge(rivalry, vec![source, rival], "competes".to_string(), -0.9,
+        EdgeType::Directional { from: source, to: vec![rival] }).unwrap();
+    graph.add_typed_hyperedge(Uuid::new_v4(), vec![helper, rival], "supports".to_string(), 0.8,
+        EdgeType::Directional { from: helper, to: vec![rival] }).unwrap();
+    assert!(graph.get_edge(&rivalry).unwrap().is_inhibitory());
+    
+    let start = chrono::Utc::now();
+    let run = |sources: &[Uuid]| {
+        let mut simulator = Simulator::new(&graph, test_simulation_config(), start);
+        for source in sources {
+            simulator.inject_spike(*source, 1.0, 0.0);
+        }
+        simulator.run()
+    };
+    let fired = |result: &neurographite::SimulationResult, node_id: Uuid| {
+        result.spikes.iter().any(|spike| spike.node_id == node_id)
+    };
+    
+    // Inhibition lowers the rival's potential below rest
+    let result = run(&[source]);
+    assert!(fired(&result, ally));
+    assert!(!fired(&result, rival));
+    assert!((result.net_input[&rival] + 0.9).abs() < 1e-9);
+    assert!(result.membranes[&rival].potential < 0.0);
+    
+    // Excitation that fires the rival on its own is cancelled out
+    assert!(fired(&run(&[helper]), rival));
+    let result = run(&[source, helper]);
+    assert!(!fired(&result, rival));
+    assert!(result.net_input[&rival] < 0.0);
+    
+    // Signed contributions make the effect genuinely competitive
+    let effect = NetworkAnalyzer::analyze_network_effects(&graph, source, 1.0, 10).unwrap();
+    let rival_effect = effect.affected_nodes.iter().find(|(id, _)| *id == rival).unwrap().1;
+    assert!(rival_effect < 0.0);
+    assert!(matches!(effect.effect_type, neurographite::network::EffectType::Competitive));
+    
+    // Learning changes the magnitude but never turns inhibition into excitation
+    assert!((graph.adjust_conductance(&rivalry, 0.05, 0.0, 1.0).unwrap() + 0.95).abs() < 1e-9);
+    assert_eq!(graph.adjust_conductance(&rivalry, -2.0, 0.0, 1.0).unwrap(), 0.0);
+    
+    // Depressed to nothing, the edge keeps its polarity through decay and relearning
+    graph.apply_decay(0.5);
+    assert!(graph.get_edge(&rivalry).unwrap().is_inhibitory());
+    assert!((graph.adjust_conductance(&rivalry, 0.3, 0.0, 1.0).unwrap() + 0.3).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_network_effect_reports_suppression() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let db = Database::new(data_dir.path()).await.unwrap();
+    let source = db.add_node(serde_json::json!({})).await.unwrap();
+    let rival = db.add_node(serde_json::json!({})).await.unwrap();
+    db.connect_nodes_with_type(vec![source, rival], "competes".to_string(), -0.8,
+        EdgeType::Directional { from: source, to: vec![rival] }).await.unwrap();
+    
+    let effects = db.simulate_network_effect(source, 1.0).await.unwrap();
+    assert_eq!(effects.len(), 1);
+    assert_eq!(effects[0].node_id, rival);
+    assert!(effects[0].effect < 0.0);
+}
+
+#[tokio::test]
+async fn test_edge_delays_shape_cascade_timing() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let db = Database::new(data_dir.path()).await.unwrap();
+    let source = db.add_node(serde_json::json!({})).await.unwrap();
+    let relay = db.add_node(serde_json::json!({})).await.unwrap();
+    let target = db.add_node(serde_json::json!({})).await.unwrap();
+    
+    let direct = db.connect_nodes_with_type(vec![source, target], "slow".to_string(), 1.0,
+        EdgeType::Directional { from: source, to: vec![target] }).await.unwrap();
+    let via_relay = db.connect_nodes_with_type(vec![source, relay], "fast".to_string(), 1.0,
+        EdgeType::Directional { from: source, to: vec![relay] }).await.unwrap();
+    db.connect_nodes_with_type(vec![relay, target], "fast".to_string(), 1.0,
+        EdgeType::Directional { from: relay, to: vec![target] }).await.unwrap();
+    
+    db.set_edge_delay(direct, EdgeDelay::Fixed(5.0)).await.unwrap();
+    db.update_hyperedge(via_relay, EdgeUpdate {
+        properties: Some([("latency".to_string(), serde_json::json!(1.5))].into()),
+        ..Default::default()
+    }).await.unwrap();
+    db.set_edge_delay(via_relay, EdgeDelay::Property { key: "latency".to_string(), scale: 1.0 }).await.unwrap();
+    assert!(db.set_edge_delay(direct, EdgeDelay::Fixed(-1.0)).await.is_err());
+    
+    // The relay route (1.5 + 1 ms) beats the direct edge (5 ms)
+    let check = |effects: Vec<neurographite::CascadeArrival>| {
+        let reached = effects.iter().find(|effect| effect.node_id == target).unwrap();
+        assert!((reached.arrival_time - 2.5).abs() < 1e-9);
+        assert_eq!(reached.path, vec![source, relay, target]);
+        assert_eq!(reached.edges.len(), 2);
+        assert_eq!(reached.edges[0], via_relay);
+    };
+    check(db.simulate_network_effect(source, 1.0).await.unwrap());
+    
+    let mut graph = HyperGraph::new();
+    for id in [source, relay, target] {
+        graph.add_node(id, serde_json::json!({})).unwrap();
+    }
+    for id in [direct, via_relay] {
+        let edge = db.get_hyperedge(id).await.unwrap();
+        graph.add_typed_hyperedge(id, edge.node_ids, edge.relationship, edge.strength, edge.edge_type).unwrap();
+        graph.update_hyperedge(&id, EdgeUpdate { properties: Some(edge.properties), ..Default::default() }).unwrap();
+        graph.set_edge_delay(&id, edge.delay).unwrap();
+    }
+    graph.add_typed_hyperedge(Uuid::new_v4(), vec![relay, target], "fast".to_string(), 1.0,
+        EdgeType::Directional { from: relay, to: vec![target] }).unwrap();
+    let effect = NetworkAnalyzer::analyze_network_effects(&graph, source, 1.0, 10).unwrap();
+    check(effect.arrivals);
+    
+    // Delays survive WAL replay and snapshots
+    drop(db);
+    let db = Database::new(data_dir.path()).await.unwrap();
+    assert_eq!(db.get_hyperedge(direct).await.unwrap().delay, EdgeDelay::Fixed(5.0));
+    db.checkpoint().await.unwrap();
+    drop(db);
+    let db = Database::new(data_dir.path()).await.unwrap();
+    assert_eq!(db.get_hyperedge(direct).await.unwrap().delay, EdgeDelay::Fixed(5.0));
+    check(db.simulate_network_effect(source, 1.0).await.unwrap());
+    
+    // Age-based delays shrink as a relationship matures, within bounds
+    let mut edge = db.get_hyperedge(direct).await.unwrap();
+    edge.delay = EdgeDelay::Age { base: 10.0, per_day: -1.0, min: 2.0, max: 10.0 };
+    assert_eq!(edge.delay_at(edge.created_at, 1.0), 10.0);
+    assert!((edge.delay_at(edge.created_at + chrono::Duration::days(5), 1.0) - 5.0).abs() < 1e-9);
+    assert_eq!(edge.delay_at(edge.created_at + chrono::Duration::days(20), 1.0), 2.0);
+}
+
+#[tokio::test]
+async fn test_age_delays_follow_the_database_clock() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let config = DatabaseConfig {
+        data_dir: data_dir.path().to_string_lossy().to_string(),
+        ..Default::default()
+    };
+    // Well behind the wall clock, so ages measured against it would be zero
+    let start = chrono::Utc::now() - chrono::Duration::days(365);
+    let clock = Arc::new(VirtualClock::new(start));
+    
+    let (source, target, edge) = {
+        let db = Database::with_clock(config.clone(), clock.clone()).await.unwrap();
+        let source = db.add_node(serde_json::json!({})).await.unwrap();
+        let target = db.add_node(serde_json::json!({})).await.unwrap();
+        let edge = db.connect_nodes_with_type(vec![source, target], "mentor".to_string(), 1.0,
+            EdgeType::Directional { from: source, to: vec![target] }).await.unwrap();
+        db.set_edge_delay(edge, EdgeDelay::Age { base: 10.0, per_day: -1.0, min: 2.0, max: 10.0 }).await.unwrap();
+        assert_eq!(db.get_hyperedge(edge).await.unwrap().created_at, start);
+        (source, target, edge)
+    };
+    
+    // Replayed from the log with the times it was written at
+    clock.advance(chrono::Duration::days(5));
+    let db = Database::with_clock(config, clock.clone()).await.unwrap();
+    let created = db.get_hyperedge(edge).await.unwrap();
+    assert_eq!(created.created_at, start);
+    assert!((created.delay_at(db.now(), 1.0) - 5.0).abs() < 1e-9);
+    
+    let arrivals = db.simulate_network_effect(source, 1.0).await.unwrap();
+    let reached = arrivals.iter().find(|arrival| arrival.node_id == target).unwrap();
+    assert!((reached.arrival_time - 5.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_hyperedge_coincidence_detection() {
+    let mut graph = HyperGraph::new();
+    let ids: Vec<Uuid> = (0..4).map(|_| Uuid::new_v4()).collect();
+    for id in &ids {
+        graph.add_node(*id, serde_json::json!({})).unwrap();
+    }
+    let meeting = Uuid::new_v4();
+    graph.add_hyperedge(meeting, ids.clone(), "meeting".to_string(), 1.0).unwrap();
+    
+    let mut config = test_simulation_config();
+    config.firing_rules.insert("meeting".to_string(), FiringRule { threshold: 2, window: 5.0, strength: 0.8 });
+    let start = chrono::Utc::now();
+    let run_with = |config: &SimulationConfig, spikes: &[(usize, f64)]| {
+        let mut simulator = Simulator::new(&graph, config.clone(), start);
+        for (index, at) in spikes {
+            simulator.inject_spike(ids[*index], 1.0, *at);
+        }
+        simulator.run()
+    };
+    let fired = |result: &neurographite::SimulationResult, index: usize| {
+        result.spikes.iter().any(|spike| spike.node_id == ids[index])
+    };
+    
+    // One member alone doesn't fire the edge
+    let result = run_with(&config, &[(0, 0.0)]);
+    assert_eq!(result.spikes.len(), 1);
+    assert!(!result.peak_potentials.contains_key(&ids[2]));
+    
+    // Two within the window deliver to the other two members only
+    let result = run_with(&config, &[(0, 0.0), (1, 3.0)]);
+    assert!(fired(&result, 2) && fired(&result, 3));
+    assert_eq!(result.spikes.len(), 4);
+    assert_eq!(result.paths[&ids[2]].nodes, vec![ids[1], ids[2]]);
+    assert_eq!(result.paths[&ids[2]].time, 5.0);
+    
+    // Too far apart
+    let result = run_with(&config, &[(0, 0.0), (1, 6.0)]);
+    assert_eq!(result.spikes.len(), 2);
+    
+    // Edges without a rule keep relaying every spike
+    let result = run_with(&test_simulation_config(), &[(0, 0.0)]);
+    assert_eq!(result.spikes.len(), 4);
+    
+    assert!(FiringRule { threshold: 0, window: 5.0, strength: 1.0 }.validate().is_err());
+}
+
+#[tokio::test]
+async fn test_firing_rules_span_stimulations() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let config = DatabaseConfig {
+        data_dir: data_dir.path().to_string_lossy().to_string(),
+        ..Default::default()
+    };
+    let clock = Arc::new(VirtualClock::new(chrono::Utc::now()));
+    let rule = FiringRule { threshold: 2, window: 10.0, strength: 1.0 };
+    
+    let ids = {
+        let db = Database::with_clock(config.clone(), clock.clone()).await.unwrap();
+        db.set_firing_rule("syndicate", rule).await.unwrap();
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            ids.push(db.add_node(serde_json::json!({})).await.unwrap());
+        }
+        db.connect_nodes(ids.clone(), "syndicate".to_string(), 1.0).await.unwrap();
+        ids
+    };
+    
+    // Rules are persisted
+    let db = Database::with_clock(config, clock.clone()).await.unwrap();
+    assert_eq!(db.firing_rule("syndicate").await, Some(rule));
+    assert_eq!(db.firing_rule("friends").await, None);
+    assert!(db.set_firing_rule("friends", FiringRule { window: -1.0, ..rule }).await.is_err());
+    
+    // Two investors committing a few ms apart bring in the third
+    clock.advance(chrono::Duration::milliseconds(500));
+    let result = db.stimulate(vec![ids[0]], 1.0).await.unwrap();
+    assert_eq!(result.spikes.len(), 1);
+    clock.advance(chrono::Duration::milliseconds(4));
+    let result = db.stimulate(vec![ids[1]], 1.0).await.unwrap();
+    assert!(result.spikes.iter().any(|spike| spike.node_id == ids[2]));
+}
+
+#[tokio::test]
+async fn test_neural_state_v2_is_migrated() {
+    let data_dir = tempfile::tempdir().unwrap();
+    
+    // v2 was positional bincode; with no membranes its layout matches today's fields
+    let mut state = NeuralState { total_spikes: 11, ..Default::default() };
+    state.neuron_parameters.insert("investor".to_string(), LifParameters::with_threshold(0.9));
+    let mut contents = b"NGNS".to_vec();
+    contents.extend_from_slice(&2u32.to_le_bytes());
+    contents.extend_from_slice(&bincode::serialize(&state).unwrap());
+    std::fs::write(data_dir.path().join("neural.bin"), contents).unwrap();
+    
+    let db = Database::new(data_dir.path()).await.unwrap();
+    assert_eq!(db.stats().await.total_spikes, 11);
+    assert_eq!(db.neuron_parameters("investor").await.threshold, 0.9);
+}
+
+#[tokio::test]
+async fn test_neural_state_v1_is_migrated() {
+    let data_dir = tempfile::tempdir().unwrap();
+    
+    // v1 had no neuron parameters or membranes (two trailing empty maps)
+    let state = NeuralState { total_spikes: 7, ..Default::default() };
+    let mut payload = bincode::serialize(&state).unwrap();
+    payload.truncate(payload.len() - 16);
+    let mut contents = b"NGNS".to_vec();
+    contents.extend_from_slice(&1u32.to_le_bytes());
+    contents.extend_from_slice(&payload);
+    std::fs::write(data_dir.path().join("neural.bin"), contents).unwrap();
+    
+    let db = Database::new(data_dir.path()).await.unwrap();
+    assert_eq!(db.stats().await.total_spikes, 7);
+}
+
+#[test]
+fn test_gale_shapley_with_ties() {
+    let [a1, a2, a3] = [1, 2, 3].map(Uuid::from_u128);
+    let [b1, b2, b3] = [11, 12, 13].map(Uuid::from_u128);
+    
+    let mut preferences = Preferences::new();
+    preferences.insert(a1, vec![(b1, 0.9), (b2, 0.5), (b3, 0.1)]);
+    preferences.insert(a2, vec![(b1, 0.9), (b2, 0.8)]);
+    preferences.insert(a3, vec![(b2, 0.5), (b1, 0.5)]);
+    preferences.insert(b1, vec![(a2, 0.9), (a1, 0.7), (a3, 0.1)]);
+    preferences.insert(b2, vec![(a1, 0.9), (a3, 0.9), (a2, 0.2)]);
+    preferences.insert(b3, vec![(a1, 0.5)]);
+    
+    // Equal scores share a tier
+    assert_eq!(preferences.tiers(&a3), &[vec![b1, b2]]);
+    assert_eq!(preferences.rank(&b2, &a3), Some(0));
+    assert!(!preferences.acceptable(&a2, &b3));
+    
+    let matching = NetworkAnalyzer::gale_shapley(&[a1, a2, a3], &[b1, b2, b3], &preferences);
+    assert!(matching.is_stable());
+    assert_eq!(matching.partner(&a2), Some(b1));
+    // b2 is indifferent between a1 and a3; the tie goes to the lower id
+    assert_eq!(matching.partner(&a1), Some(b2));
+    assert_eq!(matching.unmatched, vec![a3, b3]);
+    
+    // A deliberately bad matching is reported as unstable
+    let partners: HashMap<Uuid, Uuid> = [(a1, b3), (b3, a1), (a2, b1), (b1, a2)].into_iter().collect();
+    let blocking = NetworkAnalyzer::blocking_pairs(&[a1, a2, a3], &[b1, b2, b3], &preferences, &partners);
+    assert_eq!(blocking, vec![(a1, b2), (a3, b2)]);
+}
+
+#[test]
+fn test_find_optimal_pairs_matches_each_node_once() {
+    let mut graph = HyperGraph::new();
+    let proposers = [1, 2].map(Uuid::from_u128);
+    let acceptors = [11, 12, 13].map(Uuid::from_u128);
+    for id in proposers.iter().chain(&acceptors) {
+        graph.add_node(*id, serde_json::json!({"sector": "climate"})).unwrap();
+    }
+    
+    // Every pair aligns well enough, but each node gets a single partner
+    let pairs = NetworkAnalyzer::find_optimal_pairs(&graph, &proposers, &acceptors, 10).unwrap();
+    assert_eq!(pairs.len(), 2);
+    let matched: HashSet<Uuid> = pairs.iter().flat_map(|pair| [pair.node1, pair.node2]).collect();
+    assert_eq!(matched.len(), 4);
+    assert!(pairs.iter().all(|pair| proposers.contains(&pair.node1) && acceptors.contains(&pair.node2)));
+    
+    let pairs = NetworkAnalyzer::find_optimal_pairs(&graph, &proposers, &acceptors, 1).unwrap();
+    assert_eq!(pairs.len(), 1);
+}
+
+#[tokio::test]
+async fn test_stable_matching_between_node_types() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let db = Database::new(data_dir.path()).await.unwrap();
+    
+    let add = |node_type: &str| {
+        let metadata = NodeMetadata { node_type: node_type.to_string(), ..Default::default() };
+        db.add_node_with_metadata(serde_json::json!({}), metadata)
+    };
+    let f1 = add("founder").await.unwrap();
+    let f2 = add("founder").await.unwrap();
+    let i1 = add("investor").await.unwrap();
+    let i2 = add("investor").await.unwrap();
+    let advisor = add("advisor").await.unwrap();
+    
+    db.connect_nodes(vec![f1, i1], "knows".to_string(), 0.9).await.unwrap();
+    db.connect_nodes(vec![f1, i2], "knows".to_string(), 0.6).await.unwrap();
+    db.connect_nodes(vec![f2, i1], "knows".to_string(), 0.7).await.unwrap();
+    db.connect_nodes(vec![f2, i2], "knows".to_string(), 0.5).await.unwrap();
+    db.connect_nodes(vec![f2, advisor], "knows".to_string(), 1.0).await.unwrap();
+    
+    let side = |node_type: &str| NodeFilter { node_types: vec![node_type.to_string()], ..Default::default() };
+    let config = MatchingConfig {
+        proposers: side("founder"),
+        acceptors: side("investor"),
+        source: PreferenceSource::Conductance,
+        min_score: 0.1,
+    };
+    let matching = db.stable_matching(&config).await.unwrap();
+    
+    assert!(matching.is_stable());
+    assert_eq!(matching.pairs.len(), 2);
+    assert_eq!(matching.partner(&f1), Some(i1));
+    assert_eq!(matching.partner(&f2), Some(i2));
+    assert!(matching.partner(&advisor).is_none());
+    
+    // A higher threshold leaves only the strongest pair acceptable
+    let config = MatchingConfig { min_score: 0.8, ..config };
+    let matching = db.stable_matching(&config).await.unwrap();
+    assert_eq!(matching.pairs.len(), 1);
+    assert_eq!(matching.unmatched.len(), 2);
+    
+    let config = MatchingConfig { source: PreferenceSource::Alignment, min_score: 0.0, ..config };
+    assert!(db.stable_matching(&config).await.unwrap().is_stable());
+}
+
+#[test]
+fn test_stable_roommates() {
+    let people: Vec<Uuid> = (1..=6).map(Uuid::from_u128).collect();
+    let ranked = |preferences: &mut Preferences, person: u128, order: &[u128]| {
+        let scores = order
+            .iter()
+            .enumerate()
+            .map(|(rank, other)| (Uuid::from_u128(*other), (order.len() - rank) as f64))
+            .collect();
+        preferences.insert(Uuid::from_u128(person), scores);
+    };
+    
+    // Needs both phases of Irving's algorithm to solve
+    let mut preferences = Preferences::new();
+    ranked(&mut preferences, 1, &[4, 6, 2, 5, 3]);
+    ranked(&mut preferences, 2, &[6, 3, 5, 1, 4]);
+    ranked(&mut preferences, 3, &[4, 5, 1, 6, 2]);
+    ranked(&mut preferences, 4, &[2, 6, 5, 1, 3]);
+    ranked(&mut preferences, 5, &[4, 2, 3, 6, 1]);
+    ranked(&mut preferences, 6, &[5, 1, 4, 2, 3]);
+    
+    let matching = NetworkAnalyzer::stable_roommates(&people, &preferences);
+    assert_eq!(matching.outcome, RoommatesOutcome::Stable);
+    assert!(matching.blocking_pairs.is_empty());
+    assert_eq!(matching.partner(&people[0]), Some(people[5]));
+    assert_eq!(matching.partner(&people[1]), Some(people[2]));
+    assert_eq!(matching.partner(&people[3]), Some(people[4]));
+    
+    // Three who each prefer the next in a cycle, and one nobody wants:
+    // whoever ends up with the fourth is always blocked
+    let mut preferences = Preferences::new();
+    ranked(&mut preferences, 1, &[2, 3, 4]);
+    ranked(&mut preferences, 2, &[3, 1, 4]);
+    ranked(&mut preferences, 3, &[1, 2, 4]);
+    ranked(&mut preferences, 4, &[1, 2, 3]);
+    
+    let matching = NetworkAnalyzer::stable_roommates(&people[..4], &preferences);
+    assert_eq!(matching.outcome, RoommatesOutcome::NoStableMatching);
+    assert!(!matching.is_stable());
+    assert_eq!(matching.pairs.len(), 2);
+    assert_eq!(matching.blocking_pairs.len(), 1);
+    
+    // An odd pool leaves someone out
+    let matching = NetworkAnalyzer::stable_roommates(&people[..5], &Preferences::new());
+    assert!(matching.is_stable());
+    assert!(matching.pairs.is_empty());
+    assert_eq!(matching.unmatched.len(), 5);
+    
+    // Many copies of that pool fall back to the local search together
+    let mut preferences = Preferences::new();
+    for copy in 0..40u128 {
+        let base = 100 + copy * 4;
+        ranked(&mut preferences, base + 1, &[base + 2, base + 3, base + 4]);
+        ranked(&mut preferences, base + 2, &[base + 3, base + 1, base + 4]);
+        ranked(&mut preferences, base + 3, &[base + 1, base + 2, base + 4]);
+        ranked(&mut preferences, base + 4, &[base + 1, base + 2, base + 3]);
+    }
+    let crowd: Vec<Uuid> = (101..261).map(Uuid::from_u128).collect();
+    let matching = NetworkAnalyzer::stable_roommates(&crowd, &preferences);
+    assert_eq!(matching.outcome, RoommatesOutcome::NoStableMatching);
+    assert_eq!(matching.pairs.len(), 80);
+    assert_eq!(matching.blocking_pairs.len(), 40);
+}
+
+#[tokio::test]
+async fn test_stable_roommates_by_alignment() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let db = Database::new(data_dir.path()).await.unwrap();
+    
+    let mut cofounders = Vec::new();
+    for goals in [["ai", "health"], ["ai", "health"], ["fintech", "payments"], ["fintech", "payments"]] {
+        let metadata = NodeMetadata {
+            node_type: "cofounder".to_string(),
+            tags: goals.map(str::to_string).to_vec(),
+            ..Default::default()
+        };
+        cofounders.push(db.add_node_with_metadata(serde_json::json!({}), metadata).await.unwrap());
+    }
+    let metadata = NodeMetadata { tags: vec!["ai".to_string(), "health".to_string()], ..Default::default() };
+    let outsider = db.add_node_with_metadata(serde_json::json!({}), metadata).await.unwrap();
+    
+    let filter = NodeFilter { node_types: vec!["cofounder".to_string()], ..Default::default() };
+    let matching = db.stable_roommates(&filter, 0.0).await.unwrap();
+    
+    assert!(matching.is_stable());
+    assert_eq!(matching.pairs.len(), 2);
+    assert_eq!(matching.partner(&cofounders[0]), Some(cofounders[1]));
+    assert_eq!(matching.partner(&cofounders[2]), Some(cofounders[3]));
+    assert!(matching.partner(&outsider).is_none());
+    
+    // Nobody is acceptable above a perfect score
+    let matching = db.stable_roommates(&filter, 1.1).await.unwrap();
+    assert!(matching.is_stable());
+    assert_eq!(matching.unmatched.len(), 4);
+}
+
+#[test]
+fn test_hospitals_residents_with_quotas() {
+    let [r1, r2, r3, r4] = [1, 2, 3, 4].map(Uuid::from_u128);
+    let [h1, h2, h3] = [11, 12, 13].map(Uuid::from_u128);
+    
+    let mut preferences = Preferences::new();
+    preferences.insert(r1, vec![(h1, 0.9), (h2, 0.5)]);
+    preferences.insert(r2, vec![(h1, 0.8), (h3, 0.6)]);
+    preferences.insert(r3, vec![(h1, 0.7), (h2, 0.6)]);
+    preferences.insert(r4, vec![(h3, 0.9), (h1, 0.1)]);
+    preferences.insert(h1, vec![(r3, 0.9), (r1, 0.8), (r2, 0.7), (r4, 0.1)]);
+    preferences.insert(h2, vec![(r1, 0.9), (r3, 0.8)]);
+    preferences.insert(h3, vec![(r2, 0.9), (r4, 0.8)]);
+    
+    let residents = [r1, r2, r3, r4];
+    let capacity = |upper, lower| Capacity { upper, lower };
+    let hospitals = [(h1, capacity(2, 0)), (h2, capacity(1, 0)), (h3, capacity(2, 2))];
+    let matching = NetworkAnalyzer::hospitals_residents(&residents, &hospitals, &preferences, "placed");
+    
+    assert!(matching.is_stable());
+    assert!(matching.closed.is_empty());
+    assert_eq!(matching.members(&h1), &[r3, r1]);
+    assert_eq!(matching.members(&h3), &[r2, r4]);
+    assert_eq!(matching.acceptor_of(&r4), Some(h3));
+    assert_eq!(matching.unmatched, vec![h2]);
+    
+    // Each group is a hub hyperedge around its hospital
+    let group = &matching.groups[0];
+    assert_eq!(group.node_ids, vec![h1, r3, r1]);
+    assert_eq!(group.relationship, "placed");
+    assert!((group.strength - 0.825).abs() < 1e-9);
+    
+    // A quota h3 can't reach closes it, and its residents look elsewhere
+    let hospitals = [(h1, capacity(2, 0)), (h2, capacity(1, 0)), (h3, capacity(3, 3))];
+    let matching = NetworkAnalyzer::hospitals_residents(&residents, &hospitals, &preferences, "placed");
+    assert_eq!(matching.closed, vec![h3]);
+    assert_eq!(matching.members(&h1), &[r3, r1]);
+    assert!(matching.members(&h3).is_empty());
+    assert!(matching.is_stable());
+    assert_eq!(matching.unmatched, vec![r2, r4, h2, h3]);
+}
+
+#[tokio::test]
+async fn test_capacitated_matching_commits_groups() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let db = Database::new(data_dir.path()).await.unwrap();
+    
+    let add = |node_type: &str, properties: serde_json::Value| {
+        let properties = serde_json::from_value(properties).unwrap();
+        let metadata = NodeMetadata { node_type: node_type.to_string(), properties, ..Default::default() };
+        db.add_node_with_metadata(serde_json::json!({}), metadata)
+    };
+    // Integral floats count as whole seats
+    let big_fund = add("investor", serde_json::json!({"seats": 2.0})).await.unwrap();
+    let angel = add("investor", serde_json::json!({})).await.unwrap();
+    let founders = [
+        add("founder", serde_json::json!({})).await.unwrap(),
+        add("founder", serde_json::json!({})).await.unwrap(),
+        add("founder", serde_json::json!({})).await.unwrap(),
+    ];
+    
+    for (founder, strengths) in founders.iter().zip([(0.9, 0.5), (0.8, 0.4), (0.7, 0.6)]) {
+        db.connect_nodes(vec![*founder, big_fund], "knows".to_string(), strengths.0).await.unwrap();
+        db.connect_nodes(vec![*founder, angel], "knows".to_string(), strengths.1).await.unwrap();
+    }
+    
+    let side = |node_type: &str| NodeFilter { node_types: vec![node_type.to_string()], ..Default::default() };
+    let config = CapacitatedMatchingConfig {
+        matching: MatchingConfig {
+            proposers: side("founder"),
+            acceptors: side("investor"),
+            source: PreferenceSource::Conductance,
+            min_score: 0.0,
+        },
+        capacity_property: "seats".to_string(),
+        relationship: "invests_in".to_string(),
+        ..Default::default()
+    };
+    let matching = db.capacitated_matching(&config).await.unwrap();
+    
+    assert!(matching.is_stable());
+    assert_eq!(matching.members(&big_fund), &[founders[0], founders[1]]);
+    assert_eq!(matching.members(&angel), &[founders[2]]);
+    
+    // Accepting the proposals creates one hub hyperedge per group
+    let edges_before = db.stats().await.edge_count;
+    let edge_ids = db.commit_proposals(&matching.groups).await.unwrap();
+    assert_eq!(edge_ids.len(), 2);
+    assert_eq!(db.stats().await.edge_count, edges_before + 2);
+    
+    let group = matching.groups.iter().position(|group| group.node_ids[0] == big_fund).unwrap();
+    let edge = db.get_hyperedge(edge_ids[group]).await.unwrap();
+    assert_eq!(edge.relationship, "invests_in");
+    assert_eq!(edge.node_ids, vec![big_fund, founders[0], founders[1]]);
+    assert_eq!(edge.edge_type, EdgeType::Hub { center: big_fund, periphery: vec![founders[0], founders[1]] });
+    
+    // Nothing is committed when any proposal is invalid
+    let bad = ProposedEdge {
+        node_ids: vec![angel, Uuid::new_v4()],
+        relationship: "invests_in".to_string(),
+        strength: 0.5,
+        edge_type: EdgeType::Symmetric,
+    };
+    assert!(db.commit_proposals(&[matching.groups[0].clone(), bad]).await.is_err());
+    assert_eq!(db.stats().await.edge_count, edges_before + 2);
+    
+    // Quotas that aren't whole numbers, or a lower quota above the capacity, are rejected
+    let config = CapacitatedMatchingConfig { lower_quota_property: Some("minimum".to_string()), ..config };
+    for properties in [
+        serde_json::json!({"seats": "lots"}),
+        serde_json::json!({"seats": 1.5}),
+        serde_json::json!({"seats": -1}),
+        serde_json::json!({"seats": 1, "minimum": 2}),
+    ] {
+        let investor = add("investor", properties).await.unwrap();
+        assert!(db.capacitated_matching(&config).await.is_err());
+        db.remove_node(investor, CascadePolicy::DropEdges).await.unwrap();
+    }
+    let investor = add("investor", serde_json::json!({"seats": "3", "minimum": 1})).await.unwrap();
+    assert!(db.capacitated_matching(&config).await.is_ok());
+    db.remove_node(investor, CascadePolicy::DropEdges).await.unwrap();
+}
+
+#[test]
+fn test_team_formation_with_roles() {
+    let types = ["engineer", "engineer", "engineer", "designer", "pm"];
+    let candidates: Vec<(Uuid, String)> =
+        types.iter().enumerate().map(|(i, node_type)| (Uuid::from_u128(i as u128 + 1), node_type.to_string())).collect();
+    let [e1, e2, _e3, d1, p1] = [1, 2, 3, 4, 5].map(Uuid::from_u128);
+    
+    // Engineers score highly with each other, less so with the others
+    let pair_scores = [
+        ((0, 1), 0.9), ((0, 2), 0.9), ((1, 2), 0.9),
+        ((0, 3), 0.8), ((1, 3), 0.5), ((2, 3), 0.4),
+        ((0, 4), 0.7), ((1, 4), 0.6), ((2, 4), 0.3),
+        ((3, 4), 0.5),
+    ];
+    let mut scores = vec![vec![(0.0, 0.0); 5]; 5];
+    for ((i, j), score) in pair_scores {
+        scores[i][j] = (score, score);
+        scores[j][i] = (score, score);
+    }
+    
+    let config = TeamConfig { size: 3, max_teams: 2, ..Default::default() };
+    let teams = NetworkAnalyzer::form_teams(&candidates, &scores, &config).unwrap();
+    assert_eq!(teams[0].members().len(), 3);
+    assert!(teams[0].members().iter().all(|member| ![d1, p1].contains(member)));
+    assert!((teams[0].score - 0.9).abs() < 1e-9);
+    
+    // One engineer and one designer per team
+    let roles = vec![
+        TeamRole { node_type: "engineer".to_string(), min: 1, max: 1 },
+        TeamRole { node_type: "designer".to_string(), min: 1, max: 1 },
+    ];
+    let config = TeamConfig { roles, ..config };
+    let teams = NetworkAnalyzer::form_teams(&candidates, &scores, &config).unwrap();
+    assert_eq!(teams.len(), 2);
+    assert_eq!(teams[0].members(), &[e1, d1, p1]);
+    assert_eq!(teams[1].members(), &[e2, d1, p1]);
+    assert!((teams[0].score - 2.0 / 3.0).abs() < 1e-9);
+    assert!(teams[0].score > teams[1].score);
+    assert_eq!(teams[0].edge.relationship, "team");
+    assert_eq!(teams[0].edge.strength, teams[0].score);
+    
+    // Only complementarity counts when alignment has no weight
+    scores[0][3].0 = 0.0;
+    scores[3][0].0 = 0.0;
+    let weighted = TeamConfig { alignment_weight: 0.0, ..config.clone() };
+    let teams = NetworkAnalyzer::form_teams(&candidates, &scores, &weighted).unwrap();
+    assert_eq!(teams[0].members(), &[e1, d1, p1]);
+    assert!((teams[0].score - teams[0].complementarity).abs() < 1e-9);
+    
+    assert!(NetworkAnalyzer::form_teams(&candidates, &scores, &TeamConfig { size: 1, ..config.clone() }).is_err());
+    let impossible = vec![TeamRole { node_type: "pm".to_string(), min: 4, max: 4 }];
+    assert!(NetworkAnalyzer::form_teams(&candidates, &scores, &TeamConfig { roles: impossible, ..config }).is_err());
+}
+
+#[tokio::test]
+async fn test_accept_team_proposal() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let db = Database::new(data_dir.path()).await.unwrap();
+    
+    let mut members = Vec::new();
+    for (node_type, tag) in [("engineer", "ai"), ("engineer", "ai"), ("designer", "ai"), ("designer", "web")] {
+        let metadata = NodeMetadata { node_type: node_type.to_string(), tags: vec![tag.to_string()], ..Default::default() };
+        members.push(db.add_node_with_metadata(serde_json::json!({}), metadata).await.unwrap());
+    }
+    
+    let config = TeamConfig {
+        candidates: NodeFilter { tags: vec!["ai".to_string()], ..Default::default() },
+        size: 2,
+        roles: vec![TeamRole { node_type: "designer".to_string(), min: 1, max: 1 }],
+        relationship: "cofounders".to_string(),
+        ..Default::default()
+    };
+    let teams = db.form_teams(&config).await.unwrap();
+    assert_eq!(teams.len(), 2);
+    for team in &teams {
+        assert!(team.members().contains(&members[2]));
+        assert!(!team.members().contains(&members[3]));
+    }
+    
+    let edge_id = db.accept_proposal(&teams[0].edge).await.unwrap();
+    let edge = db.get_hyperedge(edge_id).await.unwrap();
+    assert_eq!(edge.node_ids, teams[0].members());
+    assert_eq!(edge.relationship, "cofounders");
+    assert_eq!(edge.edge_type, EdgeType::Symmetric);
+    assert_eq!(edge.strength, teams[0].score);
+}
+
+#[test]
+fn test_hypergraph_centrality() {
+    let mut graph = HyperGraph::new();
+    let [a, b, c, d] = [1, 2, 3, 4].map(Uuid::from_u128);
+    for node_id in [a, b, c, d] {
+        graph.add_node(node_id, serde_json::json!({})).unwrap();
+    }
+    // One hyperedge joins a, b and c; d hangs off c
+    graph.add_hyperedge(Uuid::new_v4(), vec![a, b, c], "team".to_string(), 1.0).unwrap();
+    graph.add_hyperedge(Uuid::new_v4(), vec![c, d], "knows".to_string(), 1.0).unwrap();
+    
+    let exact = CentralityConfig::default();
+    let betweenness = NetworkAnalyzer::betweenness_centrality(&graph, &exact);
+    // c lies on the paths from d to a and to b, out of three pairs
+    assert!((betweenness[&c] - 2.0 / 3.0).abs() < 1e-9);
+    assert_eq!(betweenness[&a], 0.0);
+    assert_eq!(betweenness[&d], 0.0);
+    
+    let closeness = NetworkAnalyzer::closeness_centrality(&graph, &exact);
+    assert!((closeness[&c] - 1.0).abs() < 1e-9);
+    assert!((closeness[&a] - 3.0 / 4.0).abs() < 1e-9);
+    assert!((closeness[&d] - 3.0 / 5.0).abs() < 1e-9);
+    
+    let measures = NetworkAnalyzer::calculate_centrality_measures(&graph);
+    assert_eq!(measures[&c].betweenness, betweenness[&c]);
+    assert_eq!(measures[&c].degree, 2.0);
+    
+    // An isolated node reaches nobody and lowers everyone's reach
+    let e = Uuid::from_u128(5);
+    graph.add_node(e, serde_json::json!({})).unwrap();
+    let closeness = NetworkAnalyzer::closeness_centrality(&graph, &exact);
+    assert_eq!(closeness[&e], 0.0);
+    assert!((closeness[&c] - 3.0 / 4.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_weighted_centrality() {
+    let mut graph = HyperGraph::new();
+    let [a, b, c] = [1, 2, 3].map(Uuid::from_u128);
+    for node_id in [a, b, c] {
+        graph.add_node(node_id, serde_json::json!({})).unwrap();
+    }
+    graph.add_hyperedge(Uuid::new_v4(), vec![a, b], "strong".to_string(), 1.0).unwrap();
+    graph.add_hyperedge(Uuid::new_v4(), vec![b, c], "strong".to_string(), 1.0).unwrap();
+    graph.add_hyperedge(Uuid::new_v4(), vec![a, c], "weak".to_string(), 0.25).unwrap();
+    
+    // By hops a and c are adjacent; by conductance the way through b is shorter
+    let hops = NetworkAnalyzer::betweenness_centrality(&graph, &CentralityConfig::default());
+    assert_eq!(hops[&b], 0.0);
+    let weighted = CentralityConfig { weighted: true, ..Default::default() };
+    let conductance = NetworkAnalyzer::betweenness_centrality(&graph, &weighted);
+    assert!((conductance[&b] - 1.0).abs() < 1e-9);
+    assert!((NetworkAnalyzer::closeness_centrality(&graph, &weighted)[&b] - 1.0).abs() < 1e-9);
+    
+    // Equally short paths share the credit
+    let d = Uuid::from_u128(4);
+    graph.add_node(d, serde_json::json!({})).unwrap();
+    graph.add_hyperedge(Uuid::new_v4(), vec![a, d], "strong".to_string(), 1.0).unwrap();
+    graph.add_hyperedge(Uuid::new_v4(), vec![d, c], "strong".to_string(), 1.0).unwrap();
+    let conductance = NetworkAnalyzer::betweenness_centrality(&graph, &weighted);
+    // b and d each carry half of the paths between a and c
+    assert!((conductance[&b] - conductance[&d]).abs() < 1e-9);
+    assert!((conductance[&b] - 0.5 / 3.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_sampled_centrality() {
+    let mut graph = HyperGraph::new();
+    let hub = Uuid::new_v4();
+    graph.add_node(hub, serde_json::json!({})).unwrap();
+    for _ in 0..20 {
+        let leaf = Uuid::new_v4();
+        graph.add_node(leaf, serde_json::json!({})).unwrap();
+        graph.add_hyperedge(Uuid::new_v4(), vec![hub, leaf], "spoke".to_string(), 1.0).unwrap();
+    }
+    
+    let exact = NetworkAnalyzer::calculate_centrality_measures(&graph);
+    assert!((exact[&hub].betweenness - 1.0).abs() < 1e-9);
+    assert!((exact[&hub].closeness - 1.0).abs() < 1e-9);
+    
+    // Sampling every node is exact; fewer sources give an estimate
+    let all = CentralityConfig { samples: Some(100), ..Default::default() };
+    assert!((NetworkAnalyzer::betweenness_centrality(&graph, &all)[&hub] - 1.0).abs() < 1e-9);
+    
+    let sampled = CentralityConfig { samples: Some(10), seed: 7, ..Default::default() };
+    let estimate = NetworkAnalyzer::calculate_centrality_measures_with_config(&graph, &sampled);
+    assert!((estimate[&hub].betweenness - 1.0).abs() < 0.1);
+    assert!((estimate[&hub].closeness - 1.0).abs() < 1e-9);
+    let leaf = graph.nodes().keys().find(|id| **id != hub).unwrap();
+    assert_eq!(estimate[leaf].betweenness, 0.0);
+    assert!((estimate[leaf].closeness - exact[leaf].closeness).abs() < 0.05);
+    
+    // The same seed picks the same sources
+    let again = NetworkAnalyzer::calculate_centrality_measures_with_config(&graph, &sampled);
+    assert_eq!(again[&hub].betweenness, estimate[&hub].betweenness);
+}